@@ -1,4 +1,3 @@
-
 fn main() {
     {
         let path = env!("CARGO_MANIFEST_DIR");
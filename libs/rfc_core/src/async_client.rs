@@ -0,0 +1,207 @@
+//! An RfcClient for tokio applications, enabled with the `async` feature.
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use crate::client::write_param_map;
+use crate::error::*;
+use crate::rfc::RfcConnectionHandle;
+use crate::{FromMethod, IntoRfcParams, ParamType, RfcClient, RfcConnection, RfcFunction, RfcLib};
+
+/// Calls function modules without blocking the executor: logons and calls
+/// run on tokio's blocking thread pool. Clones share the connection pool.
+///
+/// Dropping the future of a running call cancels it with RfcCancel, and so
+/// does running into the timeout set with [`AsyncRfcClient::with_timeout`].
+/// A canceled connection is unusable, so it is closed instead of going
+/// back to the pool.
+#[derive(Clone)]
+pub struct AsyncRfcClient {
+    client: Arc<RfcClient<'static>>,
+    timeout: Option<Duration>,
+}
+
+impl AsyncRfcClient {
+    pub fn new() -> Result<Self, RfcError> {
+        Ok(Self::from_client(RfcClient::new()?))
+    }
+
+    /// Create a client for a named SAP system, see [`RfcClient::for_system`]
+    pub fn for_system(name: &str) -> Result<Self, RfcError> {
+        Ok(Self::from_client(RfcClient::for_system(name)?))
+    }
+
+    pub fn from_client(client: RfcClient<'static>) -> Self {
+        Self {
+            client: Arc::new(client),
+            timeout: None,
+        }
+    }
+
+    /// Cancel calls that take longer than `timeout`, including the time it
+    /// takes to get a connection. They fail with RfcTimeout.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// The blocking client that does the work
+    pub fn client(&self) -> &RfcClient<'static> {
+        &self.client
+    }
+
+    /// Call a function module, see [`RfcClient::execute`]. A call that is
+    /// canceled while it runs fails with RfcCanceled.
+    pub async fn execute<T>(
+        &self,
+        name: &str,
+        params: HashMap<&'static str, ParamType<'static>>,
+    ) -> Result<T, RfcError>
+    where
+        T: FromMethod + Send + 'static,
+    {
+        let lenient = self.client.lenient_input();
+        let on_ignored = self.client.ignored_input().cloned();
+        self.run(name, move |method| {
+            write_param_map(method, &params, lenient, on_ignored.as_ref())
+        })
+        .await
+    }
+
+    /// Call a function module with typed input and output, see
+    /// [`RfcClient::call`]
+    pub async fn call<P, T>(&self, name: &str, params: P) -> Result<T, RfcError>
+    where
+        P: IntoRfcParams + Send + 'static,
+        T: FromMethod + Send + 'static,
+    {
+        self.run(name, move |method| Ok(params.write_params(method)?))
+            .await
+    }
+
+    async fn run<T>(
+        &self,
+        name: &str,
+        write: impl FnMut(&mut RfcFunction) -> Result<(), RfcError> + Send + 'static,
+    ) -> Result<T, RfcError>
+    where
+        T: FromMethod + Send + 'static,
+    {
+        let call = Arc::new(InFlight::default());
+        let _cancel_on_drop = CancelOnDrop(Arc::clone(&call));
+
+        let client = Arc::clone(&self.client);
+        let worker = Arc::clone(&call);
+        let fname = name.to_string();
+        let task = tokio::task::spawn_blocking(move || {
+            client.execute_with(&fname, write, |conn, method| worker.invoke(conn, method))
+        });
+
+        let joined = match self.timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, task).await {
+                Ok(joined) => joined,
+                Err(_) => {
+                    call.cancel();
+                    return Err(RfcError::Communication {
+                        code: RfcRc::RfcTimeout,
+                        message: format!("{} did not finish within {:?}", name, timeout),
+                    });
+                }
+            },
+            None => task.await,
+        };
+        match joined {
+            Ok(res) => res,
+            Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+            Err(_) => Err(RfcError::Canceled {
+                message: "The runtime shut down before the call finished".to_string(),
+            }),
+        }
+    }
+}
+
+/// What a connection needs for RfcCancel
+struct CancelTarget {
+    handle: *mut RfcConnectionHandle,
+    rfc_lib: RfcLib,
+}
+
+// RfcCancel is meant to be called from another thread than the one that
+// runs the call
+unsafe impl Send for CancelTarget {}
+
+#[derive(Default)]
+enum CallState {
+    #[default]
+    Pending,
+    Running(CancelTarget),
+    Finished,
+    Canceled,
+}
+
+/// Shared between the future and the blocking worker of one call. The
+/// worker holds the lock while it leaves the Running state, so the
+/// connection handle stays valid while cancel uses it.
+#[derive(Default)]
+struct InFlight {
+    state: Mutex<CallState>,
+}
+
+impl InFlight {
+    fn invoke(&self, conn: &RfcConnection, method: &mut RfcFunction) -> Result<(), RfcError> {
+        {
+            let mut state = self.lock();
+            if let CallState::Canceled = *state {
+                return Err(RfcErrorInfo::with_code(
+                    RfcRc::RfcCanceled,
+                    "The call was canceled before it started",
+                )
+                .into());
+            }
+            *state = CallState::Running(CancelTarget {
+                handle: conn.connection_handle,
+                rfc_lib: conn.rfc_lib.clone(),
+            });
+        }
+        let res = method.call();
+        let mut state = self.lock();
+        if let CallState::Canceled = *state {
+            // RfcCancel may have hit the connection even if the call got
+            // through, so report the cancellation to have it closed
+            return Err(
+                RfcErrorInfo::with_code(RfcRc::RfcCanceled, "The call was canceled").into(),
+            );
+        }
+        *state = CallState::Finished;
+        Ok(res?)
+    }
+
+    fn cancel(&self) {
+        let mut state = self.lock();
+        if let CallState::Running(target) = &*state {
+            // If RfcCancel fails the call runs to its end; the connection
+            // is closed afterwards either way
+            let mut err_trunk = RfcErrorInfo::new();
+            unsafe {
+                target
+                    .rfc_lib
+                    .rfc_api
+                    .RfcCancel(target.handle, &mut err_trunk)
+            };
+        }
+        // Also after the call finished, so that no retry starts
+        *state = CallState::Canceled;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CallState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+struct CancelOnDrop(Arc<InFlight>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.cancel();
+    }
+}
@@ -0,0 +1,154 @@
+//! The RETURN parameter of BAPIs, which report errors as messages of type
+//! BAPIRET2 rather than as exceptions.
+use crate::error::*;
+use crate::{FromStructure, FromTable, RfcEnum, RfcFunction, RfcStructure, RfcTable};
+
+/// The TYPE of a BAPI message. Ordered by severity, so the most severe of
+/// several messages is their maximum.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, RfcEnum)]
+pub enum BapiSeverity {
+    /// A blank or unknown type, below all others
+    Other(String),
+    #[sap(value = "S")]
+    Success,
+    #[sap(value = "I")]
+    Info,
+    #[sap(value = "W")]
+    Warning,
+    #[sap(value = "E")]
+    Error,
+    #[sap(value = "A")]
+    Abort,
+    /// Not a value of the BAPI_MTYPE domain, but some BAPIs pass on the X
+    /// messages of a short dump
+    #[sap(value = "X")]
+    Exit,
+}
+
+impl BapiSeverity {
+    /// Whether the BAPI failed: an E, A or X message
+    pub fn is_error(&self) -> bool {
+        *self >= BapiSeverity::Error
+    }
+}
+
+/// A message of a BAPI, a row of a BAPIRET2 table or structure. Usable as
+/// a field of the derives, e.g. `#[sap(alias = "RETURN")] messages:
+/// Vec<BapiReturn>`. The fields BAPIRET1 and BAPIRET2 add to BAPIRETURN
+/// read as blank if missing.
+#[derive(Debug, Clone, PartialEq, RfcStructure, RfcTable)]
+#[sap(rename_all = "UPPERCASE")]
+pub struct BapiReturn {
+    pub r#type: BapiSeverity,
+    /// The message class
+    #[sap(default)]
+    pub id: String,
+    #[sap(default)]
+    pub number: String,
+    /// The text of the message with its variables filled in
+    pub message: String,
+    #[sap(default)]
+    pub log_no: String,
+    #[sap(default)]
+    pub log_msg_no: String,
+    pub message_v1: String,
+    pub message_v2: String,
+    pub message_v3: String,
+    pub message_v4: String,
+    /// The parameter, row and field of the input the message is about
+    #[sap(default)]
+    pub parameter: String,
+    #[sap(default)]
+    pub row: i32,
+    #[sap(default)]
+    pub field: String,
+    /// The logical system the message comes from
+    #[sap(default)]
+    pub system: String,
+}
+
+impl BapiReturn {
+    /// The message as a T100 message: class, type, number and variables
+    pub fn abap_message(&self) -> AbapMessage {
+        let r#type = match &self.r#type {
+            BapiSeverity::Other(value) => value.as_str(),
+            BapiSeverity::Success => "S",
+            BapiSeverity::Info => "I",
+            BapiSeverity::Warning => "W",
+            BapiSeverity::Error => "E",
+            BapiSeverity::Abort => "A",
+            BapiSeverity::Exit => "X",
+        };
+        AbapMessage {
+            class: self.id.clone(),
+            r#type: r#type.to_string(),
+            number: self.number.clone(),
+            v1: self.message_v1.clone(),
+            v2: self.message_v2.clone(),
+            v3: self.message_v3.clone(),
+            v4: self.message_v4.clone(),
+        }
+    }
+
+    /// The text of the message. A message without one is shown as its
+    /// T100 key and variables, e.g. `E042(ZMM) 4500000001 1000`.
+    pub fn text(&self) -> String {
+        if !self.message.is_empty() {
+            return self.message.clone();
+        }
+        [
+            &self.message_v1,
+            &self.message_v2,
+            &self.message_v3,
+            &self.message_v4,
+        ]
+        .into_iter()
+        .filter(|v| !v.is_empty())
+        .fold(self.abap_message().to_string(), |text, v| text + " " + v)
+    }
+
+    fn is_initial(&self) -> bool {
+        self.r#type == BapiSeverity::Other(String::new()) && self.message.is_empty()
+    }
+}
+
+impl std::fmt::Display for BapiReturn {
+    /// The text, followed by the T100 key if there is one, e.g.
+    /// `Purchase order 4500000001 is locked (E042(ZMM))`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.id.is_empty() || self.message.is_empty() {
+            write!(f, "{}", self.text())
+        } else {
+            write!(f, "{} ({})", self.message, self.abap_message())
+        }
+    }
+}
+
+/// The messages in the RETURN parameter of `method`, a BAPIRET2 structure
+/// or table. A function module without one has none, and so has an
+/// initial structure.
+// Reads RETURN through RfcParameter and passes on its RfcErrorInfo
+#[allow(clippy::result_large_err)]
+pub fn read_return(method: &mut RfcFunction) -> Result<Vec<BapiReturn>, RfcErrorInfo> {
+    let Some(param) = method.get_mut_parameter("RETURN") else {
+        return Ok(Vec::new());
+    };
+    let messages = if param.field_type.is_table() {
+        let total = param.get_row_count()?;
+        let indices = BapiReturn::field_indices(param)?;
+        let mut messages = Vec::with_capacity(total as usize);
+        for i in 0..total {
+            param.set_row(i)?;
+            messages.push(BapiReturn::from_row(param, &indices)?);
+        }
+        messages
+    } else if param.field_type.is_struct_or_table() {
+        vec![BapiReturn::from_structure(param)?]
+    } else {
+        return Err(RfcErrorInfo::invalid_parameter(&format!(
+            "RETURN is of type {:?}, not a BAPIRET2 structure or table",
+            param.field_type
+        )));
+    };
+    Ok(messages.into_iter().filter(|m| !m.is_initial()).collect())
+}
@@ -0,0 +1,137 @@
+//! Checks of derived types against the metadata of a function module, see
+//! [`RfcClient::check`](crate::RfcClient::check). The derives generate
+//! calls to these helpers; a mismatch is described in one line.
+//!
+//! Empty tables get a row, so check a function that is not called
+//! afterwards.
+use crate::rfc::RfcDirection;
+use crate::{RfcFunction, RfcParameter};
+
+/// What a field of a derived type expects of its parameter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldShape {
+    /// A scalar
+    Value,
+    /// A structure, or a scalar of a type the derives do not know, like an
+    /// enum
+    Structure,
+    /// A table
+    Table,
+    /// Anything, for fields converted with `#[sap(with = "...")]`
+    Any,
+}
+
+/// Whether the derived type reads or writes the parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+/// Check the parameter `name` of `method`. Returns the parameter if its
+/// fields are to be checked as well, i.e. if it is a structure or table.
+pub fn check_parameter<'m, 'conn, 'fun>(
+    method: &'m mut RfcFunction<'conn, 'fun>,
+    name: &str,
+    shape: FieldShape,
+    access: Access,
+    required: bool,
+    mismatches: &mut Vec<String>,
+) -> Option<&'m mut RfcParameter<'conn, 'fun>> {
+    let function_name = method.name().to_string();
+    let Some(param) = method.get_mut_parameter(name) else {
+        if required {
+            mismatches.push(format!(
+                "Function module {} has no parameter {}",
+                function_name, name
+            ));
+        }
+        return None;
+    };
+    let accessible = match access {
+        Access::Read => param.direction.can_read(),
+        Access::Write => param.direction.can_write(),
+    };
+    if !accessible {
+        mismatches.push(not_accessible(param, &function_name, access));
+    }
+    check_shape(param, shape, mismatches)
+}
+
+/// The problem with a parameter whose direction does not allow `access`
+pub(crate) fn not_accessible(param: &RfcParameter, function_name: &str, access: Access) -> String {
+    let direction = match param.direction {
+        RfcDirection::RfcImport => "IMPORTING",
+        RfcDirection::RfcExport => "EXPORTING",
+        RfcDirection::RfcChanging => "CHANGING",
+        RfcDirection::RfcTables => "TABLES",
+    };
+    let verb = match access {
+        Access::Read => "read",
+        Access::Write => "written",
+    };
+    format!(
+        "Parameter {} of {} is {} and cannot be {}",
+        param.name, function_name, direction, verb
+    )
+}
+
+/// Check the field `name` of the structure or table `param`. Returns the
+/// field if it is a structure or table whose fields are to be checked.
+// The field lookup is an SDK call that fails with RfcErrorInfo
+#[allow(clippy::result_large_err)]
+pub fn check_field<'p, 'conn, 'strct>(
+    param: &'p mut RfcParameter<'conn, 'strct>,
+    name: &str,
+    shape: FieldShape,
+    required: bool,
+    mismatches: &mut Vec<String>,
+) -> Option<&'p mut RfcParameter<'conn, 'strct>> {
+    if !param.has_field(name) {
+        if required {
+            mismatches.push(format!("{} has no field {}", param.name, name));
+        }
+        return None;
+    }
+    let field = param
+        .get_field_index_by_name(name)
+        .and_then(|idx| param.get_field_by_index(idx));
+    match field {
+        Ok(field) => check_shape(field, shape, mismatches),
+        Err(err) => {
+            mismatches.push(err.to_string());
+            None
+        }
+    }
+}
+
+fn check_shape<'p, 'conn, 'strct>(
+    param: &'p mut RfcParameter<'conn, 'strct>,
+    shape: FieldShape,
+    mismatches: &mut Vec<String>,
+) -> Option<&'p mut RfcParameter<'conn, 'strct>> {
+    let expected = match (shape, param.field_type.is_table()) {
+        (FieldShape::Any, _) => return None,
+        (FieldShape::Table, true) => {
+            // Structures and tables in rows exist once there is a row
+            let has_row = match param.get_row_count() {
+                Ok(0) => param.append_rows(1).is_ok(),
+                count => count.is_ok(),
+            };
+            return has_row.then_some(param);
+        }
+        (FieldShape::Structure, false) if param.field_type.is_struct_or_table() => {
+            return Some(param)
+        }
+        (FieldShape::Structure, false) => return None,
+        (FieldShape::Value, _) if !param.field_type.is_struct_or_table() => return None,
+        (FieldShape::Table, false) => "a table",
+        (FieldShape::Structure, true) => "a structure",
+        (FieldShape::Value, _) => "a value",
+    };
+    mismatches.push(format!(
+        "{} is of type {:?}, not {}",
+        param.name, param.field_type, expected
+    ));
+    None
+}
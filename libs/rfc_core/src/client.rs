@@ -1,37 +1,192 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Local, NaiveTime};
 use dotenv::dotenv;
 use figment::providers::{Env, Format, Toml};
 use figment::Figment;
 
+use crate::bapi;
+use crate::check::{self, Access};
+use crate::pool::PoolTarget;
+use crate::rfc::RfcType;
 use crate::{
-    RfcConnection, RfcConnectionParameters, RfcErrorInfo, RfcFunction, RfcLib, RfcParameter,
+    FromRfcValue, RetryPolicy, RfcConnection, RfcConnectionParameters, RfcError, RfcErrorInfo,
+    RfcFunction, RfcLib, RfcParameter, RfcPool, RfcPoolConfig, RfcRc,
 };
 
+// Implemented by RfcResult, which passes on the errors of RfcParameter
+#[allow(clippy::result_large_err)]
 pub trait FromMethod {
     fn from_method(method: &mut RfcFunction) -> Result<Self, RfcErrorInfo>
     where
         Self: Sized;
+
+    /// Add what does not fit the parameters of `method` to `mismatches`,
+    /// see [`RfcClient::check`]
+    fn check(_method: &mut RfcFunction, _mismatches: &mut Vec<String>)
+    where
+        Self: Sized,
+    {
+    }
 }
 
+/// A table row. The derive looks up the fields once per table with
+/// `field_indices` and reads every row with `from_row`.
+// Reads rows with the SDK's table cursor, whose errors are RfcErrorInfo
+#[allow(clippy::result_large_err)]
 pub trait FromTable {
     fn from_table(param: &mut RfcParameter) -> Result<Self, RfcErrorInfo>
     where
         Self: Sized;
+
+    /// The indices of the fields that are read, None for a field that may
+    /// be missing
+    fn field_indices(_param: &mut RfcParameter) -> Result<Vec<Option<u32>>, RfcErrorInfo>
+    where
+        Self: Sized,
+    {
+        Ok(Vec::new())
+    }
+
+    /// Read the current row with the indices from `field_indices`
+    fn from_row(param: &mut RfcParameter, _indices: &[Option<u32>]) -> Result<Self, RfcErrorInfo>
+    where
+        Self: Sized,
+    {
+        Self::from_table(param)
+    }
+
+    /// Add what does not fit the fields of `param` to `mismatches`
+    fn check_fields(_param: &mut RfcParameter, _mismatches: &mut Vec<String>)
+    where
+        Self: Sized,
+    {
+    }
+}
+
+/// A structure parameter, or a structure nested in another structure or
+/// in a table row
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is neither a scalar nor a structure",
+    label = "read as a structure",
+    note = "the derives read types that are not scalars as structures, which must derive `RfcStructure`",
+    note = "scalars of your own implement `FromRfcValue` or derive `RfcEnum`"
+)]
+// Like FromRfcValue, fails with the RfcErrorInfo of the field it reads
+#[allow(clippy::result_large_err)]
+pub trait FromStructure {
+    fn from_structure(param: &mut RfcParameter) -> Result<Self, RfcErrorInfo>
+    where
+        Self: Sized;
+
+    /// Add what does not fit the fields of `param` to `mismatches`
+    fn check_fields(_param: &mut RfcParameter, _mismatches: &mut Vec<String>)
+    where
+        Self: Sized,
+    {
+    }
+}
+
+/// The derives read fields of types they do not know as structures, so
+/// values of your own types, like enums deriving `RfcEnum`, go through here
+impl<T: FromRfcValue> FromStructure for T {
+    fn from_structure(param: &mut RfcParameter) -> Result<Self, RfcErrorInfo> {
+        T::from_rfc(param)
+    }
+}
+
+/// No result
+impl FromMethod for () {
+    fn from_method(_method: &mut RfcFunction) -> Result<Self, RfcErrorInfo> {
+        Ok(())
+    }
+}
+
+/// The input of a function module, written to its IMPORTING, CHANGING and
+/// TABLES parameters before the call. Derive it with `#[derive(RfcParams)]`.
+// Implemented by RfcParams, which passes on the errors of RfcParameter
+#[allow(clippy::result_large_err)]
+pub trait IntoRfcParams {
+    fn write_params(&self, method: &mut RfcFunction) -> Result<(), RfcErrorInfo>;
+
+    /// Add what does not fit the parameters of `method` to `mismatches`,
+    /// see [`RfcClient::check`]
+    fn check(_method: &mut RfcFunction, _mismatches: &mut Vec<String>)
+    where
+        Self: Sized,
+    {
+    }
+}
+
+/// No input
+impl IntoRfcParams for () {
+    fn write_params(&self, _method: &mut RfcFunction) -> Result<(), RfcErrorInfo> {
+        Ok(())
+    }
+}
+
+/// A table row written from a Rust value, derived with `#[derive(RfcRow)]`
+/// like the `ToRfcValue` that writes it to a structure
+// Appends rows with RfcAppendNewRow, which fails with an RfcErrorInfo
+#[allow(clippy::result_large_err)]
+pub trait ToTable {
+    /// The indices of the fields that are written, None for a field that
+    /// may be missing
+    fn field_indices(param: &mut RfcParameter) -> Result<Vec<Option<u32>>, RfcErrorInfo>
+    where
+        Self: Sized;
+
+    /// Write the current row with the indices from `field_indices`
+    fn to_row(&self, param: &mut RfcParameter, indices: &[Option<u32>])
+        -> Result<(), RfcErrorInfo>;
+
+    /// Add what does not fit the fields of `param` to `mismatches`
+    fn check_fields(_param: &mut RfcParameter, _mismatches: &mut Vec<String>)
+    where
+        Self: Sized,
+    {
+    }
+}
+
+impl<T: ToTable> ToTable for &T {
+    fn field_indices(param: &mut RfcParameter) -> Result<Vec<Option<u32>>, RfcErrorInfo> {
+        T::field_indices(param)
+    }
+
+    fn to_row(
+        &self,
+        param: &mut RfcParameter,
+        indices: &[Option<u32>],
+    ) -> Result<(), RfcErrorInfo> {
+        (**self).to_row(param, indices)
+    }
+
+    fn check_fields(param: &mut RfcParameter, mismatches: &mut Vec<String>) {
+        T::check_fields(param, mismatches)
+    }
 }
 
 #[derive(Debug, Clone)]
 pub enum ParamValue<'a> {
     Str(&'a str),
+    /// Goes through f64, use `Decimal` for amounts that must stay exact
     Dec(f64),
+    /// The date for DATS, the time for TIMS and the point in time for
+    /// timestamps
     Date(DateTime<Local>),
+    Time(NaiveTime),
     Int(i64),
+    #[cfg(feature = "rust_decimal")]
+    Decimal(rust_decimal::Decimal),
 }
 
-trait RfcLibTrait {
-    fn connect<'t>(&self) -> Result<RfcConnection, RfcErrorInfo>;
+// Returns the RfcErrorInfo of RfcOpenConnection
+#[allow(clippy::result_large_err)]
+pub(crate) trait RfcLibTrait {
+    fn connect(&self, system: Option<&str>, file: &Path) -> Result<RfcConnection, RfcErrorInfo>;
 }
 
 impl RfcLibTrait for RfcLib {
@@ -49,6 +204,10 @@ impl RfcLibTrait for RfcLib {
     /// lang = "LANG"
     /// ```
     ///
+    /// Every field of RfcConnectionParameters can be set this way, e.g.
+    /// `mshost`, `sysid` and `group` for load balancing or `dest` for a
+    /// destination in sapnwrfc.ini.
+    ///
     /// Example of environment variables:
     /// ```bash
     /// export SAP_ASHOST="127.0.0.1"
@@ -58,113 +217,208 @@ impl RfcLibTrait for RfcLib {
     /// export SAP_PASSWD="password"
     /// export SAP_LANG="LANG"
     /// ```
-    fn connect<'t>(&self) -> Result<RfcConnection, RfcErrorInfo> {
+    ///
+    /// Named systems live in their own section and use their own
+    /// environment variables. A system can inherit the parameters it does
+    /// not set from another one, and at the end of the chain from `[SAP]`:
+    /// ```toml
+    /// [SAP]
+    /// lang = "EN"
+    ///
+    /// [SAP.erp]
+    /// client = "100"
+    ///
+    /// [SAP.prd]
+    /// inherits = "erp"
+    /// ashost = "prd.example.com"
+    /// sysnr = "00"
+    /// ```
+    /// ```bash
+    /// export SAP_PRD_USER="username"
+    /// export SAP_PRD_PASSWD="password"
+    /// ```
+    fn connect(&self, system: Option<&str>, file: &Path) -> Result<RfcConnection, RfcErrorInfo> {
         dotenv().ok();
 
         // 首先尝试从 config.toml 文件中读取配置
-        let figment = Figment::from(Toml::file("config.toml").nested())
+        let figment = Figment::from(Toml::file(file).nested())
             .merge(Env::prefixed("SAP_").global())
             .select("SAP");
 
         // 尝试获取各个参数，优先使用环境变量
-        let ashost = get_param_value(&figment, "ashost", "SAP_ASHOST")?;
-        let sysnr = get_param_value(&figment, "sysnr", "SAP_SYSNR")?;
-        let client = get_param_value(&figment, "client", "SAP_CLIENT")?;
-        let user = get_param_value(&figment, "user", "SAP_USER")?;
-        let passwd = get_param_value(&figment, "passwd", "SAP_PASSWD")?;
-        let lang = get_param_value(&figment, "lang", "SAP_LANG")?;
-
-        let conn_params = RfcConnectionParameters {
-            ashost,
-            sysnr,
-            client,
-            user,
-            passwd,
-            lang,
-        };
+        let profiles = profile_chain(&figment, system)?;
+        let conn_params = RfcConnectionParameters::from_lookup(|key| {
+            profiles
+                .iter()
+                .find_map(|profile| profile.get(&figment, key))
+        })?;
 
-        let conn = RfcConnection::new(&conn_params, &self)?;
+        let conn = RfcConnection::new(&conn_params, self)?;
         Ok(conn)
     }
 }
 
+/// One section of the configuration: `[SAP]` with the `SAP_` environment
+/// variables, or a named system such as `[SAP.prd]` with `SAP_PRD_`.
+struct Profile(Option<String>);
+
+impl Profile {
+    fn name(&self) -> &str {
+        self.0.as_deref().unwrap_or("SAP")
+    }
+
+    fn get(&self, figment: &Figment, key: &str) -> Option<String> {
+        match &self.0 {
+            None => get_param_value(figment, key, &format!("SAP_{}", key.to_uppercase())),
+            Some(name) => get_param_value(
+                figment,
+                &format!("{}.{}", name, key),
+                &format!("SAP_{}_{}", name.to_uppercase(), key.to_uppercase()),
+            ),
+        }
+    }
+
+    fn exists(&self, figment: &Figment) -> bool {
+        match &self.0 {
+            None => true,
+            Some(name) => {
+                let prefix = format!("SAP_{}_", name.to_uppercase());
+                figment.find_value(name).is_ok() || env::vars().any(|(k, _)| k.starts_with(&prefix))
+            }
+        }
+    }
+}
+
+// 按继承顺序列出配置：先是系统本身，然后是 inherits 指向的系统，最后是 [SAP]
+// Config errors surface like the logon errors of RfcLibTrait::connect
+#[allow(clippy::result_large_err)]
+fn profile_chain(figment: &Figment, system: Option<&str>) -> Result<Vec<Profile>, RfcErrorInfo> {
+    let mut chain: Vec<Profile> = Vec::new();
+    let mut next = Some(Profile(system.map(str::to_lowercase)));
+    while let Some(profile) = next.take() {
+        if !profile.exists(figment) {
+            return Err(RfcErrorInfo::invalid_parameter(&format!(
+                "SAP system {} not configured in environment variables or config.toml",
+                profile.name()
+            )));
+        }
+        if chain.iter().any(|p| p.0 == profile.0) {
+            let names: Vec<_> = chain.iter().chain([&profile]).map(Profile::name).collect();
+            return Err(RfcErrorInfo::invalid_parameter(&format!(
+                "SAP system {} is inherited in a cycle: {}",
+                profile.name(),
+                names.join(" -> ")
+            )));
+        }
+        next = match profile.get(figment, "inherits") {
+            Some(parent) => Some(Profile(Some(parent.to_lowercase()))),
+            // Named systems share the defaults of [SAP]
+            None if profile.0.is_some() => Some(Profile(None)),
+            None => None,
+        };
+        chain.push(profile);
+    }
+    Ok(chain)
+}
+
 // 辅助函数，从 figment 或环境变量中获取参数值
-fn get_param_value(figment: &Figment, key: &str, env_key: &str) -> Result<String, RfcErrorInfo> {
+fn get_param_value(figment: &Figment, key: &str, env_key: &str) -> Option<String> {
     // 首先尝试从环境变量中获取
     if let Ok(value) = env::var(env_key) {
         if !value.is_empty() {
-            return Ok(value);
+            return Some(value);
         }
     }
 
     // 如果环境变量不存在或为空，尝试从 figment 中获取
     if let Ok(value) = figment.extract_inner::<String>(key) {
-        if !value.is_empty() {
-            return Ok(value);
-        }
+        return Some(value);
     }
-
-    // 如果都不存在，返回错误
-    Err(RfcErrorInfo::custom(&format!(
-        "{} not set in environment variables or config.toml",
-        key
-    )))
+    // config.toml 中也可以写数字或布尔值，例如 trace = 2
+    if let Ok(value) = figment.extract_inner::<i64>(key) {
+        return Some(value.to_string());
+    }
+    figment
+        .extract_inner::<bool>(key)
+        .ok()
+        .map(|v| if v { "1" } else { "0" }.to_string())
 }
 
+// Returns the RfcErrorInfo of RfcGetFunctionDesc and RfcCreateFunction
+#[allow(clippy::result_large_err)]
 trait RfcConnectionTrait {
-    fn with_method(&self, name: &str) -> Result<RfcFunction, RfcErrorInfo>;
+    fn with_method(&self, name: &str) -> Result<RfcFunction<'_, '_>, RfcErrorInfo>;
 }
 
-impl<'conn> RfcConnectionTrait for RfcConnection<'conn> {
-    fn with_method(&self, name: &str) -> Result<RfcFunction, RfcErrorInfo> {
+impl RfcConnectionTrait for RfcConnection {
+    fn with_method(&self, name: &str) -> Result<RfcFunction<'_, '_>, RfcErrorInfo> {
         self.get_function(name)
     }
 }
 
-trait RfcParameterTrait<'a> {
-    fn set_value(&mut self, value: ParamValue);
-    fn set_struct(&mut self, value: Vec<(&'a str, ParamValue<'a>)>);
-    fn set_table(&mut self, value: Vec<Vec<(&'a str, ParamValue<'a>)>>);
+/// Writing the input of `execute`. Problems are collected rather than
+/// returned at the first one, each naming its parameter or field, like
+/// `CT_DATA[1]-MENGE`.
+trait RfcParameterTrait {
+    fn set_value(&mut self, value: &ParamValue, path: &str, problems: &mut Vec<String>);
+    fn set_struct(&mut self, value: &[(&str, ParamValue)], path: &str, problems: &mut Vec<String>);
+    fn set_table(
+        &mut self,
+        value: &[Vec<(&str, ParamValue)>],
+        path: &str,
+        problems: &mut Vec<String>,
+    );
 }
 
-impl<'a> RfcParameterTrait<'a> for RfcParameter<'_, '_> {
-    fn set_value(&mut self, value: ParamValue) {
-        match match value {
+impl RfcParameterTrait for RfcParameter<'_, '_> {
+    fn set_value(&mut self, value: &ParamValue, path: &str, problems: &mut Vec<String>) {
+        if let Err(err) = match *value {
             ParamValue::Str(v) => self.set_string(v),
             ParamValue::Dec(v) => self.set_float(v),
-            ParamValue::Date(v) => self.set_date(v.format("%Y%m%d").to_string().as_str()),
+            ParamValue::Date(v) => self.set(v),
+            ParamValue::Time(v) => self.set(v),
             ParamValue::Int(v) => self.set_int(v),
+            #[cfg(feature = "rust_decimal")]
+            ParamValue::Decimal(v) => self.set_decimal(v),
         } {
-            Err(err) => eprintln!("error while set value for params, error: {}", err),
-            _ => {}
+            problems.push(format!("{}: {}", path, err))
         };
     }
 
-    fn set_struct(&mut self, value: Vec<(&'a str, ParamValue<'a>)>) {
-        value
-            .into_iter()
-            .for_each(|(name, value)| match self.get_field_index_by_name(name) {
-                Ok(index) => match self.get_field_by_index(index) {
-                    Ok(param) => param.set_value(value.clone()), // 使用 clone() 以避免移动
-                    Err(err) => eprintln!("error while get field by index, error: {}", err),
-                },
-                Err(err) => eprintln!(
-                    "error while get field index by name: {}, error: {}",
-                    name, err
-                ),
-            });
-    }
-
-    fn set_table(&mut self, value: Vec<Vec<(&'a str, ParamValue<'a>)>>) {
-        value
-            .into_iter()
-            .for_each(|item| match self.append_rows(1) {
-                Ok(_) => match self.first_row() {
-                    Ok(_) => self.set_struct(item),
-                    Err(_) => todo!(),
-                },
-                Err(_) => todo!(),
-            });
+    // The field lookups are SDK calls that fail with RfcErrorInfo
+    #[allow(clippy::result_large_err)]
+    fn set_struct(&mut self, value: &[(&str, ParamValue)], path: &str, problems: &mut Vec<String>) {
+        for &(name, ref value) in value {
+            if !self.has_field(name) {
+                problems.push(format!("{} has no field {}", path, name));
+                continue;
+            }
+            let field_path = format!("{}-{}", path, name);
+            match self
+                .get_field_index_by_name(name)
+                .and_then(|index| self.get_field_by_index(index))
+            {
+                Ok(field) => field.set_value(value, &field_path, problems),
+                Err(err) => problems.push(format!("{}: {}", field_path, err)),
+            }
+        }
+    }
+
+    fn set_table(
+        &mut self,
+        value: &[Vec<(&str, ParamValue)>],
+        path: &str,
+        problems: &mut Vec<String>,
+    ) {
+        for (i, item) in value.iter().enumerate() {
+            match self.append_row() {
+                Ok(row) => row.set_struct(item, &format!("{}[{}]", path, i), problems),
+                Err(err) => {
+                    problems.push(format!("{}: {}", path, err));
+                    break;
+                }
+            }
+        }
     }
 }
 
@@ -173,19 +427,148 @@ pub enum ParamType<'a> {
     Struct(Vec<(&'a str, ParamValue<'a>)>),
     Table(Vec<Vec<(&'a str, ParamValue<'a>)>>),
 }
+/// Told about the input `execute` ignored in lenient mode, with the name of
+/// the function module and the problems, see
+/// [`RfcClient::with_ignored_input`]
+pub type IgnoredInputHook = Arc<dyn Fn(&str, &[String]) + Send + Sync>;
+
+/// Calls function modules on the SAP system configured in config.toml or
+/// environment variables. Connections are kept in a pool and reused, and
+/// a client can be shared between threads.
 pub struct RfcClient<'t> {
     params: HashMap<&'t str, ParamType<'t>>,
-    rfc_lib: RfcLib,
+    pool: RfcPool,
+    lenient_input: bool,
+    ignored_input: Option<IgnoredInputHook>,
+    retry_policy: RetryPolicy,
+    read_only: HashSet<String>,
+    bapi_errors: bool,
 }
 
 impl<'client> RfcClient<'client> {
-    pub fn new() -> Result<Self, RfcErrorInfo> {
-        let rfc_lib = RfcLib::new().map_err(|e| RfcErrorInfo::custom(&e))?;
+    pub fn new() -> Result<Self, RfcError> {
+        let rfc_lib = RfcLib::new()?;
+
+        Ok(Self::from_lib(rfc_lib))
+    }
 
-        Ok(Self {
-            rfc_lib,
+    /// Create a client on top of an already loaded RFC library, e.g. one
+    /// loaded with [`RfcLib::from_path`]
+    pub fn from_lib(rfc_lib: RfcLib) -> Self {
+        Self {
             params: HashMap::new(),
-        })
+            pool: RfcPool::with_target(
+                rfc_lib,
+                PoolTarget::Config {
+                    system: None,
+                    file: PathBuf::from("config.toml"),
+                },
+                RfcPoolConfig::default(),
+            ),
+            lenient_input: false,
+            ignored_input: None,
+            retry_policy: RetryPolicy::default(),
+            read_only: HashSet::new(),
+            bapi_errors: false,
+        }
+    }
+
+    /// Create a client for a named SAP system, configured in `[SAP.<name>]`
+    /// of config.toml and `SAP_<NAME>_*` environment variables
+    pub fn for_system(name: &str) -> Result<Self, RfcError> {
+        Ok(Self::new()?.with_system(name))
+    }
+
+    /// Connect to a named SAP system instead of the one configured in `[SAP]`
+    pub fn with_system(mut self, name: &str) -> Self {
+        if let PoolTarget::Config { system, .. } = &mut self.pool.target {
+            *system = Some(name.to_string());
+        }
+        self
+    }
+
+    /// Read the configuration from `path` instead of config.toml
+    pub fn with_config_file(mut self, path: impl Into<PathBuf>) -> Self {
+        if let PoolTarget::Config { file, .. } = &mut self.pool.target {
+            *file = path.into();
+        }
+        self
+    }
+
+    /// Change the settings of the connection pool
+    pub fn with_pool_config(mut self, config: RfcPoolConfig) -> Self {
+        self.pool.config = config;
+        self
+    }
+
+    /// Let `execute` call the function module even if some of its input
+    /// cannot be written, ignoring the problems instead of failing. Meant
+    /// for migrating code that relied on this; without it, any problem
+    /// fails the call before it is sent. `with_ignored_input` gets to see
+    /// the problems, e.g. to log them.
+    pub fn with_lenient_input(mut self, lenient: bool) -> Self {
+        self.lenient_input = lenient;
+        self
+    }
+
+    /// Whether `execute` calls the function module despite input it cannot
+    /// write, see `with_lenient_input`
+    pub fn lenient_input(&self) -> bool {
+        self.lenient_input
+    }
+
+    /// Call `hook` with the problems of the input that lenient mode
+    /// ignored, see `with_lenient_input`
+    pub fn with_ignored_input(
+        mut self,
+        hook: impl Fn(&str, &[String]) + Send + Sync + 'static,
+    ) -> Self {
+        self.ignored_input = Some(Arc::new(hook));
+        self
+    }
+
+    /// The hook set with `with_ignored_input`
+    pub fn ignored_input(&self) -> Option<&IgnoredInputHook> {
+        self.ignored_input.as_ref()
+    }
+
+    /// Repeat calls that fail with a communication failure, a closed
+    /// connection or a timeout according to `policy`. By default, calls
+    /// of the function modules marked with `with_read_only` are made up
+    /// to three times.
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    /// Mark a function module as read-only, i.e. safe to call again if a
+    /// call fails on the way, so that the retry policy applies to it
+    pub fn with_read_only(mut self, name: &str) -> Self {
+        self.read_only.insert(name.to_uppercase());
+        self
+    }
+
+    /// Whether failed calls of the function module `name` are repeated
+    pub fn retries(&self, name: &str) -> bool {
+        self.retry_policy.max_attempts > 1
+            && (self.retry_policy.all_functions || self.read_only.contains(&name.to_uppercase()))
+    }
+
+    /// Fail calls whose RETURN parameter, a BAPIRET2 structure or table,
+    /// holds an E, A or X message with `RfcError::BapiError`, instead of
+    /// leaving it to the result type to look at the messages
+    pub fn with_bapi_errors(mut self, fail: bool) -> Self {
+        self.bapi_errors = fail;
+        self
+    }
+
+    /// The pool the client takes its connections from
+    pub fn pool(&self) -> &RfcPool {
+        &self.pool
     }
 
     pub fn clear(&mut self) {
@@ -196,32 +579,191 @@ impl<'client> RfcClient<'client> {
         self.params.insert(name, value);
     }
 
+    /// Call a function module with untyped input. Unknown parameters and
+    /// fields, values that do not convert and parameters that cannot be
+    /// written fail the call with `RfcError::InvalidInput` before it is
+    /// sent, listing every problem; see `with_lenient_input`.
     pub fn execute<T: FromMethod>(
-        self,
+        &self,
         name: &str,
         params: HashMap<&str, ParamType>,
-    ) -> Result<T, RfcErrorInfo> {
-        let conn = self.rfc_lib.connect()?;
+    ) -> Result<T, RfcError> {
+        self.execute_with(
+            name,
+            |method| {
+                write_param_map(
+                    method,
+                    &params,
+                    self.lenient_input,
+                    self.ignored_input.as_ref(),
+                )
+            },
+            |_, method| Ok(method.call()?),
+        )
+    }
+
+    /// Call a function module with typed input and output, e.g. structs
+    /// deriving `RfcParams` and `RfcResult`. An input that cannot be
+    /// written fails the call before it is sent.
+    pub fn call<P, T>(&self, name: &str, params: &P) -> Result<T, RfcError>
+    where
+        P: IntoRfcParams + ?Sized,
+        T: FromMethod,
+    {
+        self.execute_with(
+            name,
+            |method| Ok(params.write_params(method)?),
+            |_, method| Ok(method.call()?),
+        )
+    }
+
+    /// Check the input type `P` and the result type `T` against the
+    /// metadata of a function module, e.g. at startup. Returns every
+    /// mismatch, none if the types fit. Use `()` for a type you do not have.
+    pub fn check<P, T>(&self, name: &str) -> Result<Vec<String>, RfcError>
+    where
+        P: IntoRfcParams,
+        T: FromMethod,
+    {
+        let conn = self.pool.checkout()?;
         let mut method = conn.with_method(name)?;
 
-        params
-            .into_iter()
-            .for_each(|(name, value)| match method.get_mut_parameter(name) {
-                Some(param) => match value {
-                    ParamType::Value(v) => param.set_value(v),
-                    ParamType::Struct(v) => param.set_struct(v),
-                    ParamType::Table(v) => param.set_table(v),
-                },
-                None => eprintln!("input param {} not exists.", name),
-            });
+        let mut mismatches = Vec::new();
+        P::check(&mut method, &mut mismatches);
+        T::check(&mut method, &mut mismatches);
+        Ok(mismatches)
+    }
+
+    /// Let `write` fill in the parameters and `invoke` run the function
+    /// module on the checked out connection, once per attempt of the retry
+    /// policy
+    pub(crate) fn execute_with<T: FromMethod>(
+        &self,
+        name: &str,
+        mut write: impl FnMut(&mut RfcFunction) -> Result<(), RfcError>,
+        mut invoke: impl FnMut(&RfcConnection, &mut RfcFunction) -> Result<(), RfcError>,
+    ) -> Result<T, RfcError> {
+        let max_attempts = if self.retries(name) {
+            self.retry_policy.max_attempts
+        } else {
+            1
+        };
+        let mut attempt = 1;
+        loop {
+            match self.attempt(name, &mut write, &mut invoke) {
+                Err(err) if attempt < max_attempts && err.is_retryable() => {
+                    let backoff = self.retry_policy.backoff(attempt);
+                    if let Some(on_retry) = &self.retry_policy.on_retry {
+                        on_retry(name, attempt, backoff, &err);
+                    }
+                    std::thread::sleep(backoff);
+                    attempt += 1;
+                }
+                res => return res,
+            }
+        }
+    }
+
+    /// One attempt of `execute_with`. A connection that failed with a
+    /// retryable error or was canceled is closed rather than returned to
+    /// the pool.
+    fn attempt<T: FromMethod>(
+        &self,
+        name: &str,
+        write: &mut impl FnMut(&mut RfcFunction) -> Result<(), RfcError>,
+        invoke: &mut impl FnMut(&RfcConnection, &mut RfcFunction) -> Result<(), RfcError>,
+    ) -> Result<T, RfcError> {
+        let conn = self.pool.checkout()?;
+        let res = run_method(&conn, name, write, invoke, self.bapi_errors);
+        if matches!(&res, Err(err) if err.is_retryable() || err.code() == RfcRc::RfcCanceled) {
+            conn.discard();
+        }
+        res
+    }
+}
+
+fn run_method<T: FromMethod>(
+    conn: &RfcConnection,
+    name: &str,
+    write: &mut impl FnMut(&mut RfcFunction) -> Result<(), RfcError>,
+    invoke: &mut impl FnMut(&RfcConnection, &mut RfcFunction) -> Result<(), RfcError>,
+    bapi_errors: bool,
+) -> Result<T, RfcError> {
+    let mut method = conn.with_method(name)?;
 
-        method.call()?;
-        let result = T::from_method(&mut method);
+    write(&mut method)?;
+    invoke(conn, &mut method)?;
+    if bapi_errors {
+        let messages = bapi::read_return(&mut method)?;
+        if let Some(err) = RfcError::from_bapi_return(name, &messages) {
+            return Err(err);
+        }
+    }
+    Ok(T::from_method(&mut method)?)
+}
 
-        // 确保 conn 在整个方法执行期间保持有效
-        // drop(method);
-        // drop(conn);
+/// Write the input of `execute`. Every unknown parameter or field, value
+/// that does not convert and parameter that cannot be written is
+/// collected; they fail the call with `RfcError::InvalidInput`, or are
+/// passed to `on_ignored` if `lenient`.
+pub(crate) fn write_param_map(
+    method: &mut RfcFunction,
+    params: &HashMap<&str, ParamType>,
+    lenient: bool,
+    on_ignored: Option<&IgnoredInputHook>,
+) -> Result<(), RfcError> {
+    let function_name = method.name().to_string();
+    let mut problems = Vec::new();
+    // Sorted, so that the problems are reported in a stable order
+    let mut params: Vec<_> = params.iter().collect();
+    params.sort_by_key(|(name, _)| name.to_uppercase());
 
-        result
+    for (name, value) in params {
+        let Some(param) = method.get_mut_parameter(name) else {
+            problems.push(format!(
+                "Function module {} has no parameter {}",
+                function_name, name
+            ));
+            continue;
+        };
+        if !param.direction.can_write() {
+            problems.push(check::not_accessible(param, &function_name, Access::Write));
+            continue;
+        }
+        let path = param.name.clone();
+        let shape = match (&value, param.field_type) {
+            (ParamType::Value(_), t) if !t.is_struct_or_table() => None,
+            (ParamType::Struct(_), RfcType::Structure) => None,
+            (ParamType::Table(_), RfcType::Table) => None,
+            (ParamType::Value(_), _) => Some("a value"),
+            (ParamType::Struct(_), _) => Some("a structure"),
+            (ParamType::Table(_), _) => Some("a table"),
+        };
+        if let Some(shape) = shape {
+            problems.push(format!(
+                "{} is of type {:?}, not {}",
+                path, param.field_type, shape
+            ));
+            continue;
+        }
+        match value {
+            ParamType::Value(v) => param.set_value(v, &path, &mut problems),
+            ParamType::Struct(v) => param.set_struct(v, &path, &mut problems),
+            ParamType::Table(v) => param.set_table(v, &path, &mut problems),
+        }
+    }
+
+    if problems.is_empty() {
+        return Ok(());
+    }
+    if lenient {
+        if let Some(on_ignored) = on_ignored {
+            on_ignored(&function_name, &problems);
+        }
+        return Ok(());
     }
+    Err(RfcError::InvalidInput {
+        function: function_name,
+        problems,
+    })
 }
@@ -1,9 +1,12 @@
+use serde::Deserialize;
 use widestring::*;
 
+use crate::error::RfcErrorInfo;
 use crate::rfc::*;
 
 /// Simple structure that supplies arbitrary key,value
 /// pairs to the SAP RFC library
+#[derive(Default)]
 pub struct RfcConnParmHelper {
     parms: Vec<(Vec<u16>, Vec<u16>)>,
 }
@@ -36,3 +39,354 @@ impl RfcConnParmHelper {
         f(pp)
     }
 }
+
+/// All connection parameters the SAP NW RFC library understands. Only set
+/// the ones you need; everything else is left to the library's defaults
+/// or, with `dest`, to sapnwrfc.ini.
+///
+/// A connection goes either to a specific application server (`ashost`,
+/// `sysnr`), through a message server to a logon group (`mshost`,
+/// `sysid` or `msserv`, `group`) or to a destination from sapnwrfc.ini
+/// (`dest`). Explicitly set parameters override the ones of the
+/// destination.
+///
+/// Start from `new` or `Default` and add parameters with the `with_*`
+/// methods, then check them with `validate`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct RfcConnectionParameters {
+    /// Destination in sapnwrfc.ini
+    pub dest: Option<String>,
+
+    /// Application server host
+    pub ashost: Option<String>,
+    /// System number, two digits
+    pub sysnr: Option<String>,
+    /// Gateway host, if it differs from the application server
+    pub gwhost: Option<String>,
+    /// Gateway service, if it differs from sapgw<sysnr>
+    pub gwserv: Option<String>,
+
+    /// Message server host for load balancing
+    pub mshost: Option<String>,
+    /// Message server service (port), alternative to `sysid`
+    pub msserv: Option<String>,
+    /// System ID, used to look up the message server service
+    pub sysid: Option<String>,
+    /// Logon group, PUBLIC if not set
+    pub group: Option<String>,
+
+    /// SAProuter string, e.g. /H/router.example.com/S/3299/H/
+    pub saprouter: Option<String>,
+
+    /// Client, three digits
+    pub client: Option<String>,
+    pub user: Option<String>,
+    pub passwd: Option<String>,
+    /// Logon language, e.g. EN
+    pub lang: Option<String>,
+    /// Logon with an SSO ticket (MYSAPSSO2) instead of a password
+    pub mysapsso2: Option<String>,
+    /// Logon with a base64 encoded X.509 certificate. Requires SNC.
+    pub x509cert: Option<String>,
+
+    /// Enable secure network communication
+    pub snc_mode: Option<bool>,
+    /// SNC name of the application server
+    pub snc_partnername: Option<String>,
+    /// SNC quality of protection, 1 to 9
+    pub snc_qop: Option<String>,
+    /// Own SNC name, if it differs from the default
+    pub snc_myname: Option<String>,
+    /// Path of the SNC library
+    pub snc_lib: Option<String>,
+
+    /// RFC trace level, 0 (off) to 3 (full)
+    pub trace: Option<u8>,
+    /// 0 to not use SAPGUI, 1 to attach a SAPGUI to the connection and
+    /// 2 to attach a hidden one
+    pub use_sapgui: Option<u8>,
+    /// SAP codepage of the partner, four digits, e.g. 1100
+    pub codepage: Option<String>,
+}
+
+macro_rules! setters {
+    ($($(#[$doc:meta])* $setter:ident => $field:ident: $t:ty;)*) => {$(
+        $(#[$doc])*
+        pub fn $setter(mut self, value: impl Into<$t>) -> Self {
+            self.$field = Some(value.into());
+            self
+        }
+    )*};
+}
+
+impl RfcConnectionParameters {
+    /// Logon to an application server with user and password, the
+    /// parameters this struct used to be limited to. Everything else is
+    /// added with the `with_*` methods.
+    pub fn new(
+        ashost: &str,
+        sysnr: &str,
+        client: &str,
+        user: &str,
+        passwd: &str,
+        lang: &str,
+    ) -> RfcConnectionParameters {
+        RfcConnectionParameters::default()
+            .with_ashost(ashost)
+            .with_sysnr(sysnr)
+            .with_client(client)
+            .with_user(user)
+            .with_passwd(passwd)
+            .with_lang(lang)
+    }
+
+    setters! {
+        /// Use a destination from sapnwrfc.ini
+        with_dest => dest: String;
+        /// Connect to an application server
+        with_ashost => ashost: String;
+        /// System number of the application server
+        with_sysnr => sysnr: String;
+        /// Gateway host, if it differs from the application server
+        with_gwhost => gwhost: String;
+        /// Gateway service, if it differs from sapgw<sysnr>
+        with_gwserv => gwserv: String;
+        /// Connect through a message server
+        with_mshost => mshost: String;
+        /// Message server service (port)
+        with_msserv => msserv: String;
+        /// System ID of the message server
+        with_sysid => sysid: String;
+        /// Logon group on the message server
+        with_group => group: String;
+        /// Route the connection through a SAProuter
+        with_saprouter => saprouter: String;
+        /// Logon client
+        with_client => client: String;
+        /// Logon user
+        with_user => user: String;
+        /// Logon password
+        with_passwd => passwd: String;
+        /// Logon language
+        with_lang => lang: String;
+        /// Logon with an SSO ticket
+        with_mysapsso2 => mysapsso2: String;
+        /// Logon with an X.509 certificate
+        with_x509cert => x509cert: String;
+        /// Enable or disable SNC
+        with_snc_mode => snc_mode: bool;
+        /// SNC name of the application server
+        with_snc_partnername => snc_partnername: String;
+        /// SNC quality of protection
+        with_snc_qop => snc_qop: String;
+        /// Own SNC name
+        with_snc_myname => snc_myname: String;
+        /// Path of the SNC library
+        with_snc_lib => snc_lib: String;
+        /// RFC trace level
+        with_trace => trace: u8;
+        /// Attach a SAPGUI to the connection
+        with_use_sapgui => use_sapgui: u8;
+        /// SAP codepage of the partner
+        with_codepage => codepage: String;
+    }
+
+    /// Build the parameters from a lookup function that returns the value
+    /// of a parameter by its lower case name, e.g. `ashost`.
+    // RfcClient hands the error on like the ones of RfcConnection::new
+    #[allow(clippy::result_large_err)]
+    pub fn from_lookup<F>(lookup: F) -> Result<RfcConnectionParameters, RfcErrorInfo>
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        Self::parse(lookup).map_err(|msg| invalid(&msg))
+    }
+
+    fn parse<F>(mut lookup: F) -> Result<RfcConnectionParameters, String>
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        let mut get = |key: &str| lookup(key).filter(|v| !v.is_empty());
+        Ok(RfcConnectionParameters {
+            dest: get("dest"),
+            ashost: get("ashost"),
+            sysnr: get("sysnr"),
+            gwhost: get("gwhost"),
+            gwserv: get("gwserv"),
+            mshost: get("mshost"),
+            msserv: get("msserv"),
+            sysid: get("sysid"),
+            group: get("group"),
+            saprouter: get("saprouter"),
+            client: get("client"),
+            user: get("user"),
+            passwd: get("passwd"),
+            lang: get("lang"),
+            mysapsso2: get("mysapsso2"),
+            x509cert: get("x509cert"),
+            snc_mode: get("snc_mode")
+                .map(|v| parse_flag("snc_mode", &v))
+                .transpose()?,
+            snc_partnername: get("snc_partnername"),
+            snc_qop: get("snc_qop"),
+            snc_myname: get("snc_myname"),
+            snc_lib: get("snc_lib"),
+            trace: get("trace")
+                .map(|v| parse_number("trace", &v))
+                .transpose()?,
+            use_sapgui: get("use_sapgui")
+                .map(|v| parse_number("use_sapgui", &v))
+                .transpose()?,
+            codepage: get("codepage"),
+        })
+    }
+
+    /// Check for missing and contradicting parameters
+    // Returns the error of RfcConnection::new, which runs it before connecting
+    #[allow(clippy::result_large_err)]
+    pub fn validate(&self) -> Result<(), RfcErrorInfo> {
+        self.check().map_err(|msg| invalid(&msg))
+    }
+
+    fn check(&self) -> Result<(), String> {
+        let dest = self.dest.is_some();
+        if self.ashost.is_some() && self.mshost.is_some() {
+            return Err("ashost and mshost are mutually exclusive".to_string());
+        }
+        if !dest && self.ashost.is_none() && self.mshost.is_none() {
+            return Err("one of ashost, mshost or dest is required".to_string());
+        }
+        if self.ashost.is_some() && !dest && self.sysnr.is_none() {
+            return Err("ashost requires sysnr".to_string());
+        }
+        if self.mshost.is_some() && !dest && self.sysid.is_none() && self.msserv.is_none() {
+            return Err("mshost requires sysid or msserv".to_string());
+        }
+        if self.mshost.is_none() && !dest {
+            for (name, value) in [("msserv", &self.msserv), ("group", &self.group)] {
+                if value.is_some() {
+                    return Err(format!("{} requires mshost", name));
+                }
+            }
+        }
+        if self.ashost.is_none() && !dest {
+            for (name, value) in [("gwhost", &self.gwhost), ("gwserv", &self.gwserv)] {
+                if value.is_some() {
+                    return Err(format!("{} requires ashost", name));
+                }
+            }
+        }
+
+        let credentials = [
+            ("passwd", &self.passwd),
+            ("mysapsso2", &self.mysapsso2),
+            ("x509cert", &self.x509cert),
+        ];
+        let given: Vec<&str> = credentials
+            .iter()
+            .filter(|(_, v)| v.is_some())
+            .map(|(n, _)| *n)
+            .collect();
+        if given.len() > 1 {
+            return Err(format!("{} are mutually exclusive", given.join(" and ")));
+        }
+        if self.passwd.is_some() && self.user.is_none() && !dest {
+            return Err("passwd requires user".to_string());
+        }
+        let snc = self.snc_mode.unwrap_or(false);
+        if self.x509cert.is_some() && !snc && !dest {
+            return Err("x509cert requires snc_mode".to_string());
+        }
+        if snc && self.snc_partnername.is_none() && !dest {
+            return Err("snc_mode requires snc_partnername".to_string());
+        }
+
+        check_digits("sysnr", &self.sysnr, 2)?;
+        check_digits("client", &self.client, 3)?;
+        check_digits("codepage", &self.codepage, 4)?;
+        if let Some(qop) = &self.snc_qop {
+            if !matches!(qop.as_str(), "1" | "2" | "3" | "8" | "9") {
+                return Err("snc_qop must be one of 1, 2, 3, 8 or 9".to_string());
+            }
+        }
+        if self.trace.is_some_and(|t| t > 3) {
+            return Err("trace must be between 0 and 3".to_string());
+        }
+        if self.use_sapgui.is_some_and(|g| g > 2) {
+            return Err("use_sapgui must be between 0 and 2".to_string());
+        }
+        Ok(())
+    }
+
+    /// Convert to a more generic RfcConnParmHelper structure
+    pub fn convert(&self) -> RfcConnParmHelper {
+        let mut parms = RfcConnParmHelper::new();
+        let strings = [
+            ("dest", &self.dest),
+            ("ashost", &self.ashost),
+            ("sysnr", &self.sysnr),
+            ("gwhost", &self.gwhost),
+            ("gwserv", &self.gwserv),
+            ("mshost", &self.mshost),
+            ("msserv", &self.msserv),
+            ("sysid", &self.sysid),
+            ("group", &self.group),
+            ("saprouter", &self.saprouter),
+            ("client", &self.client),
+            ("user", &self.user),
+            ("passwd", &self.passwd),
+            ("lang", &self.lang),
+            ("mysapsso2", &self.mysapsso2),
+            ("x509cert", &self.x509cert),
+            ("snc_partnername", &self.snc_partnername),
+            ("snc_qop", &self.snc_qop),
+            ("snc_myname", &self.snc_myname),
+            ("snc_lib", &self.snc_lib),
+            ("codepage", &self.codepage),
+        ];
+        for (name, value) in strings {
+            if let Some(value) = value {
+                parms.add(name, value);
+            }
+        }
+        if let Some(snc_mode) = self.snc_mode {
+            parms.add("snc_mode", if snc_mode { "1" } else { "0" });
+        }
+        if let Some(trace) = self.trace {
+            parms.add("trace", &trace.to_string());
+        }
+        if let Some(use_sapgui) = self.use_sapgui {
+            parms.add("use_sapgui", &use_sapgui.to_string());
+        }
+        parms
+    }
+}
+
+fn invalid(msg: &str) -> RfcErrorInfo {
+    RfcErrorInfo::invalid_parameter(&format!("Invalid connection parameters: {}", msg))
+}
+
+fn check_digits(name: &str, value: &Option<String>, len: usize) -> Result<(), String> {
+    match value {
+        Some(v) if v.len() != len || !v.bytes().all(|b| b.is_ascii_digit()) => Err(format!(
+            "{} must consist of {} digits, got {:?}",
+            name, len, v
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn parse_flag(name: &str, value: &str) -> Result<bool, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "1" | "true" | "x" => Ok(true),
+        "0" | "false" | "" => Ok(false),
+        _ => Err(format!("{} must be 0 or 1, got {:?}", name, value)),
+    }
+}
+
+fn parse_number(name: &str, value: &str) -> Result<u8, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{} must be a number, got {:?}", name, value))
+}
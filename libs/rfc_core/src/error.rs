@@ -1,3 +1,8 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::{BapiReturn, RfcVersion};
+
 /// Various kinds of RFC errors
 #[repr(u32)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -32,8 +37,10 @@ pub enum RfcRc {
     RfcAbapClassException, // /< The called function module raised a class based exception
     RfcUnknownError,   // /< "Something" went wrong, but I don't know what...
     RfcAuthorizationFailure, // /< Authorization check error
-
-    RfcCaiberp = 65536, // CaiberP custom error
+    RfcAuthenticationFailure, // /< The authentication of the partner failed
+    RfcCryptolibFailure, // /< The cryptographic library reported an error
+    RfcIoFailure,      // /< Reading or writing a file failed
+    RfcLockingFailure, // /< Acquiring or releasing a mutex failed
 }
 
 impl RfcRc {
@@ -41,6 +48,29 @@ impl RfcRc {
     pub fn is_ok(&self) -> bool {
         self == &RfcRc::RfcOk
     }
+
+    /// The group the SAP NW RFC library reports errors with this code in
+    pub fn group(&self) -> RfcErrorGroup {
+        match self {
+            RfcRc::RfcOk => RfcErrorGroup::Ok,
+            RfcRc::RfcAbapException | RfcRc::RfcAbapClassException => {
+                RfcErrorGroup::AbapApplicationFailure
+            }
+            // E, A and X messages end the call like a runtime error
+            RfcRc::RfcAbapRuntimeFailure | RfcRc::RfcAbapMessage => {
+                RfcErrorGroup::AbapRuntimeFailure
+            }
+            RfcRc::RfcLogonFailure => RfcErrorGroup::LogonFailure,
+            RfcRc::RfcCommunicationFailure | RfcRc::RfcClosed | RfcRc::RfcTimeout => {
+                RfcErrorGroup::CommunicationFailure
+            }
+            RfcRc::RfcAuthorizationFailure => RfcErrorGroup::ExternalAuthorizationFailure,
+            RfcRc::RfcAuthenticationFailure => RfcErrorGroup::ExternalAuthenticationFailure,
+            RfcRc::RfcCryptolibFailure => RfcErrorGroup::CryptolibFailure,
+            RfcRc::RfcLockingFailure => RfcErrorGroup::LockingFailure,
+            _ => RfcErrorGroup::ExternalRuntimeFailure,
+        }
+    }
 }
 
 #[repr(u32)]
@@ -54,8 +84,9 @@ pub enum RfcErrorGroup {
     ExternalRuntimeFailure,
     ExternalApplicationFailure,
     ExternalAuthorizationFailure,
-
-    CaiberP = 65536, // Custom error
+    ExternalAuthenticationFailure,
+    CryptolibFailure,
+    LockingFailure,
 }
 
 #[repr(C)]
@@ -75,18 +106,382 @@ pub struct RfcErrorInfo {
 
 impl std::fmt::Debug for RfcErrorInfo {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let msg = String::from_utf16_lossy(&self.message);
-        let msg = msg.trim();
-        write!(f, "{}", msg)
+        write!(f, "{}", decode(&self.message))
     }
 }
 
 impl std::fmt::Display for RfcErrorInfo {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let msg = String::from_utf16_lossy(&self.message);
-        let msg = msg.trim();
-        write!(f, "{}", msg)
+        write!(f, "{}", decode(&self.message))
     }
 }
 
 impl std::error::Error for RfcErrorInfo {}
+
+/// One location where the SAP NW RFC library was looked for
+#[derive(Debug)]
+pub struct RfcLoadAttempt {
+    /// The path handed to the dynamic loader. A bare file name means
+    /// the loader's own search path was used.
+    pub path: PathBuf,
+    /// Why the library could not be loaded from there
+    pub reason: dlopen::Error,
+}
+
+/// The SAP NW RFC library could not be loaded
+#[derive(Debug)]
+pub enum RfcLoadError {
+    /// No usable library was found. Lists every location that was
+    /// tried, in search order.
+    NotLoaded(Vec<RfcLoadAttempt>),
+    /// The library was loaded but is older than required
+    VersionTooOld {
+        found: RfcVersion,
+        required: RfcVersion,
+    },
+}
+
+impl std::fmt::Display for RfcLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RfcLoadError::NotLoaded(attempts) => {
+                write!(f, "Error trying to load the SAP NW RFC library")?;
+                for attempt in attempts {
+                    write!(f, "\n  {}: {}", attempt.path.display(), attempt.reason)?;
+                }
+                Ok(())
+            }
+            RfcLoadError::VersionTooOld { found, required } => write!(
+                f,
+                "SAP NW RFC library version {} is older than the required {}",
+                found, required
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RfcLoadError {}
+
+/// Compares the reasons of failed attempts by their message, as
+/// `dlopen::Error` has no equality of its own
+impl PartialEq for RfcLoadError {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (RfcLoadError::NotLoaded(a), RfcLoadError::NotLoaded(b)) => {
+                a.len() == b.len()
+                    && a.iter().zip(b).all(|(a, b)| {
+                        a.path == b.path && a.reason.to_string() == b.reason.to_string()
+                    })
+            }
+            (
+                RfcLoadError::VersionTooOld { found, required },
+                RfcLoadError::VersionTooOld {
+                    found: other_found,
+                    required: other_required,
+                },
+            ) => found == other_found && required == other_required,
+            _ => false,
+        }
+    }
+}
+
+/// The T100 message of an ABAP `MESSAGE` statement: class, type, number
+/// and the four variables
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct AbapMessage {
+    pub class: String,
+    /// E, A, X, W, I or S
+    pub r#type: String,
+    pub number: String,
+    pub v1: String,
+    pub v2: String,
+    pub v3: String,
+    pub v4: String,
+}
+
+impl std::fmt::Display for AbapMessage {
+    /// SAP's short notation, e.g. `E001(ZMM)`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}({})", self.r#type, self.number, self.class)
+    }
+}
+
+/// An error of a call, with the strings of `RfcErrorInfo` decoded. The
+/// client returns it; the lower layers return `RfcErrorInfo`, which
+/// converts into it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RfcError {
+    /// Logging on failed, e.g. because of a wrong password or a locked
+    /// user
+    Logon { message: String },
+    /// The network connection failed, was closed by the other side or
+    /// timed out
+    Communication { code: RfcRc, message: String },
+    /// The call was canceled, e.g. by dropping the future of an
+    /// `AsyncRfcClient` call
+    Canceled { message: String },
+    /// The function module raised an exception, classic (RAISE) or class
+    /// based. `key` is its name, `abap_message` set if it was raised with
+    /// MESSAGE ... RAISING.
+    AbapException {
+        code: RfcRc,
+        key: String,
+        message: String,
+        abap_message: Option<Box<AbapMessage>>,
+    },
+    /// The function module sent an E, A or X message
+    AbapMessage {
+        message: String,
+        abap_message: Box<AbapMessage>,
+    },
+    /// The function module ended in a runtime error (short dump),
+    /// SYSTEM_FAILURE. `key` is the name of the runtime error.
+    AbapRuntime { key: String, message: String },
+    /// The user lacks an authorization, or the partner could not be
+    /// authenticated
+    Authorization { code: RfcRc, message: String },
+    /// The function module or a type does not exist
+    NotFound { message: String },
+    /// A parameter or field that does not exist or cannot be written
+    InvalidParameter { message: String },
+    /// The input of `RfcClient::execute` could not be written. Lists every
+    /// problem, each naming its parameter or field.
+    InvalidInput {
+        function: String,
+        problems: Vec<String>,
+    },
+    /// A value that does not fit its parameter or field, or one of
+    /// another type
+    Conversion { code: RfcRc, message: String },
+    /// A BAPI reported an E, A or X message in its RETURN parameter. Holds
+    /// all of its messages, the most severe first.
+    BapiError {
+        function: String,
+        messages: Vec<BapiReturn>,
+    },
+    /// The SAP NW RFC library could not be loaded. `error` lists every
+    /// location that was tried.
+    LibraryLoad { error: Arc<RfcLoadError> },
+    /// Any other error of the SAP NW RFC library
+    Other {
+        code: RfcRc,
+        group: RfcErrorGroup,
+        key: String,
+        message: String,
+    },
+}
+
+impl RfcError {
+    /// The return code of the SAP NW RFC library. Errors that do not come
+    /// from the library have the code closest to them; a library that
+    /// cannot be loaded is RfcNotFound.
+    pub fn code(&self) -> RfcRc {
+        match self {
+            RfcError::Logon { .. } => RfcRc::RfcLogonFailure,
+            RfcError::AbapMessage { .. } | RfcError::BapiError { .. } => RfcRc::RfcAbapMessage,
+            RfcError::Canceled { .. } => RfcRc::RfcCanceled,
+            RfcError::AbapRuntime { .. } => RfcRc::RfcAbapRuntimeFailure,
+            RfcError::NotFound { .. } | RfcError::LibraryLoad { .. } => RfcRc::RfcNotFound,
+            RfcError::InvalidParameter { .. } | RfcError::InvalidInput { .. } => {
+                RfcRc::RfcInvalidParameter
+            }
+            RfcError::Communication { code, .. }
+            | RfcError::AbapException { code, .. }
+            | RfcError::Authorization { code, .. }
+            | RfcError::Conversion { code, .. }
+            | RfcError::Other { code, .. } => *code,
+        }
+    }
+
+    /// The group of the error, see [`RfcRc::group`]
+    pub fn group(&self) -> RfcErrorGroup {
+        match self {
+            RfcError::Other { group, .. } => *group,
+            _ => self.code().group(),
+        }
+    }
+
+    /// Whether the call may succeed if it is repeated: communication
+    /// failures, closed connections and timeouts. Logon failures, errors
+    /// raised by the function module and invalid input are permanent.
+    pub fn is_retryable(&self) -> bool {
+        match self.code() {
+            RfcRc::RfcCommunicationFailure | RfcRc::RfcClosed | RfcRc::RfcTimeout => true,
+            _ => self.group() == RfcErrorGroup::CommunicationFailure,
+        }
+    }
+
+    /// The message, without the key or T100 fields that `Display` adds
+    pub fn message(&self) -> String {
+        match self {
+            RfcError::Logon { message }
+            | RfcError::Communication { message, .. }
+            | RfcError::Canceled { message }
+            | RfcError::AbapException { message, .. }
+            | RfcError::AbapMessage { message, .. }
+            | RfcError::AbapRuntime { message, .. }
+            | RfcError::Authorization { message, .. }
+            | RfcError::NotFound { message }
+            | RfcError::InvalidParameter { message }
+            | RfcError::Conversion { message, .. }
+            | RfcError::Other { message, .. } => message.clone(),
+            RfcError::LibraryLoad { error } => error.to_string(),
+            RfcError::InvalidInput { function, problems } => {
+                format!("Invalid input for {}: {}", function, problems.join("; "))
+            }
+            RfcError::BapiError { function, messages } => {
+                let errors: Vec<_> = bapi_errors(messages).map(BapiReturn::text).collect();
+                format!("{} failed: {}", function, errors.join("; "))
+            }
+        }
+    }
+
+    /// The error for the `messages` of the RETURN parameter of `function`,
+    /// if one of them is of type E, A or X
+    pub fn from_bapi_return(function: &str, messages: &[BapiReturn]) -> Option<RfcError> {
+        if !messages.iter().any(|m| m.r#type.is_error()) {
+            return None;
+        }
+        let mut messages = messages.to_vec();
+        messages.sort_by(|a, b| b.r#type.cmp(&a.r#type));
+        Some(RfcError::BapiError {
+            function: function.to_string(),
+            messages,
+        })
+    }
+}
+
+impl std::fmt::Display for RfcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RfcError::AbapException {
+                key,
+                message,
+                abap_message,
+                ..
+            } => {
+                write!(f, "{} (exception {}", message, key)?;
+                if let Some(abap_message) = abap_message {
+                    write!(f, ", message {}", abap_message)?;
+                }
+                write!(f, ")")
+            }
+            RfcError::AbapMessage {
+                message,
+                abap_message,
+            } => write!(f, "{} (message {})", message, abap_message),
+            RfcError::AbapRuntime { key, message } if !key.is_empty() => {
+                write!(f, "{} (runtime error {})", message, key)
+            }
+            RfcError::BapiError { function, messages } => {
+                write!(f, "{} failed: ", function)?;
+                for (i, message) in bapi_errors(messages).enumerate() {
+                    if i > 0 {
+                        write!(f, "; ")?;
+                    }
+                    write!(f, "{}", message)?;
+                }
+                Ok(())
+            }
+            _ => write!(f, "{}", self.message()),
+        }
+    }
+}
+
+impl std::error::Error for RfcError {}
+
+fn bapi_errors(messages: &[BapiReturn]) -> impl Iterator<Item = &BapiReturn> {
+    messages.iter().filter(|m| m.r#type.is_error())
+}
+
+/// Decode a NUL terminated UTF-16 field of RfcErrorInfo
+fn decode(field: &[u16]) -> String {
+    let len = field.iter().position(|c| *c == 0).unwrap_or(field.len());
+    String::from_utf16_lossy(&field[..len]).trim().to_string()
+}
+
+impl RfcErrorInfo {
+    /// The T100 message, if the error came with one
+    pub fn abap_message(&self) -> Option<AbapMessage> {
+        let message = AbapMessage {
+            class: decode(&self.abap_msg_class),
+            r#type: decode(&self.abap_msg_type),
+            number: decode(&self.abap_msg_number),
+            v1: decode(&self.abap_msg_v1),
+            v2: decode(&self.abap_msg_v2),
+            v3: decode(&self.abap_msg_v3),
+            v4: decode(&self.abap_msg_v4),
+        };
+        (!message.class.is_empty() || !message.number.is_empty()).then_some(message)
+    }
+}
+
+impl From<RfcErrorInfo> for RfcError {
+    fn from(err: RfcErrorInfo) -> Self {
+        RfcError::from(&err)
+    }
+}
+
+impl From<&RfcErrorInfo> for RfcError {
+    fn from(err: &RfcErrorInfo) -> Self {
+        let key = decode(&err.key);
+        let message = decode(&err.message);
+        match err.code {
+            RfcRc::RfcLogonFailure => RfcError::Logon { message },
+            RfcRc::RfcCommunicationFailure | RfcRc::RfcClosed | RfcRc::RfcTimeout => {
+                RfcError::Communication {
+                    code: err.code,
+                    message,
+                }
+            }
+            RfcRc::RfcCanceled => RfcError::Canceled { message },
+            RfcRc::RfcAbapException | RfcRc::RfcAbapClassException => RfcError::AbapException {
+                code: err.code,
+                key,
+                message,
+                abap_message: err.abap_message().map(Box::new),
+            },
+            RfcRc::RfcAbapMessage => RfcError::AbapMessage {
+                message,
+                abap_message: Box::new(err.abap_message().unwrap_or_default()),
+            },
+            RfcRc::RfcAbapRuntimeFailure => RfcError::AbapRuntime { key, message },
+            RfcRc::RfcAuthorizationFailure | RfcRc::RfcAuthenticationFailure => {
+                RfcError::Authorization {
+                    code: err.code,
+                    message,
+                }
+            }
+            RfcRc::RfcNotFound => RfcError::NotFound { message },
+            RfcRc::RfcInvalidParameter => RfcError::InvalidParameter { message },
+            RfcRc::RfcConversionFailure
+            | RfcRc::RfcCodepageConversionFailure
+            | RfcRc::RfcBufferTooSmall => RfcError::Conversion {
+                code: err.code,
+                message,
+            },
+            _ => match err.group {
+                RfcErrorGroup::LogonFailure => RfcError::Logon { message },
+                RfcErrorGroup::CommunicationFailure => RfcError::Communication {
+                    code: err.code,
+                    message,
+                },
+                RfcErrorGroup::AbapRuntimeFailure => RfcError::AbapRuntime { key, message },
+                group => RfcError::Other {
+                    code: err.code,
+                    group,
+                    key,
+                    message,
+                },
+            },
+        }
+    }
+}
+
+impl From<RfcLoadError> for RfcError {
+    fn from(err: RfcLoadError) -> Self {
+        RfcError::LibraryLoad {
+            error: Arc::new(err),
+        }
+    }
+}
@@ -2,65 +2,79 @@ extern crate dlopen;
 #[macro_use]
 extern crate dlopen_derive;
 extern crate widestring;
+// The derives refer to the crate as rsrfc, also within it
+extern crate self as rsrfc;
 
 use std::collections::HashMap;
+use std::env;
+use std::ffi::OsString;
+use std::mem::ManuallyDrop;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use widestring::*;
 
+#[cfg(feature = "async")]
+mod async_client;
+pub mod bapi;
+pub mod check;
 mod client;
 pub mod connparams;
 pub mod error;
+mod pool;
+mod retry;
 mod rfc;
+mod value;
+
+#[cfg(feature = "rust_decimal")]
+#[doc(hidden)]
+pub use crate::rfc::decfloat;
 
 use crate::connparams::*;
 use crate::error::*;
 use crate::rfc::*;
+#[cfg(feature = "async")]
+pub use async_client::AsyncRfcClient;
+pub use bapi::{BapiReturn, BapiSeverity};
 pub use client::*;
+pub use connparams::RfcConnectionParameters;
+pub use pool::*;
+pub use retry::{RetryHook, RetryPolicy};
 pub use rfc::RfcParameter;
+pub use value::{FromRfcValue, ToRfcValue};
 
 pub use rfc_derive::*;
 
-use serde::Deserialize;
-
-/// Simple RFC connections require only a few parameters.
-/// You can use this struct to supply them.
-// #[serde_as]
-#[derive(Debug, Deserialize)]
-pub struct RfcConnectionParameters {
-    pub ashost: String,
-    pub sysnr: String,
-    pub client: String,
-    pub user: String,
-    pub passwd: String,
-    pub lang: String,
-}
-
 pub enum RfcValue {
     RfcString(String),
 }
 
-impl RfcConnectionParameters {
-    /// Convert to a more generic RfcConnParmHelper structure
-    fn convert(&self) -> RfcConnParmHelper {
-        let mut parms = RfcConnParmHelper::new();
-        parms.add("ashost", &self.ashost);
-        parms.add("sysnr", &self.sysnr);
-        parms.add("client", &self.client);
-        parms.add("user", &self.user);
-        parms.add("passwd", &self.passwd);
-        parms.add("lang", &self.lang);
-        parms
-    }
-}
-
-/// An open RFC connection
-pub struct RfcConnection<'rfclib> {
+/// An open RFC connection.
+///
+/// The SDK allows using a connection from any thread, but not from two
+/// threads at the same time. Connections are therefore `Send` but not
+/// `Sync`; to share one, put it into a `Mutex`, e.g.
+/// `Arc<Mutex<RfcConnection>>`, or use an RfcPool.
+pub struct RfcConnection {
     connection_handle: *mut RfcConnectionHandle,
-    rfc_lib: &'rfclib RfcLib,
+    rfc_lib: RfcLib,
 }
 
+unsafe impl Send for RfcConnection {}
+
+// RfcLib only holds the library handle and function pointers, which may be
+// used from any thread.
+const _: () = {
+    const fn send<T: Send>() {}
+    const fn sync<T: Sync>() {}
+    send::<RfcLib>();
+    sync::<RfcLib>();
+    send::<RfcConnection>();
+};
+
 /// An RFC function
 pub struct RfcFunction<'conn, 'fun: 'conn> {
-    connection: &'conn RfcConnection<'conn>,
+    name: String,
+    connection: &'conn RfcConnection,
     fun: *mut RfcDataContainerHandle,
     fun_desc: Vec<RfcParameter<'conn, 'fun>>,
 }
@@ -69,89 +83,197 @@ pub struct RfcFunction<'conn, 'fun: 'conn> {
 /// If you need to specify more RFC connection parameters than
 /// RfcConnectinoParameters would allow you, use from_parm_helper or from_hashmap
 /// methods instead.
+///
+/// RfcLib is a cheap handle to the loaded library: clones share it, and it
+/// can be used from any thread. The library stays loaded until the
+/// process exits: the SDK starts threads of its own and keeps ICU and
+/// trace state, so unloading it at runtime is not safe.
+#[derive(Clone)]
 pub struct RfcLib {
-    rfc_api: Option<dlopen::wrapper::Container<crate::rfc::RfcApi>>,
+    rfc_api: Arc<ManuallyDrop<dlopen::wrapper::Container<crate::rfc::RfcApi>>>,
+}
+
+/// Version of the SAP NW RFC library as reported by RfcGetVersion.
+/// SDK 7.50 patch level 12, for instance, reports 7500.0.12.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RfcVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl RfcVersion {
+    pub fn new(major: u32, minor: u32, patch: u32) -> RfcVersion {
+        RfcVersion {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl std::fmt::Display for RfcVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
 }
 
+/// File name of the SAP NW RFC library on this platform
+#[cfg(all(target_family = "unix", not(target_vendor = "apple")))]
+pub const RFC_LIB_NAME: &str = "libsapnwrfc.so";
+/// File name of the SAP NW RFC library on this platform
+#[cfg(all(target_family = "unix", target_vendor = "apple"))]
+pub const RFC_LIB_NAME: &str = "libsapnwrfc.dylib";
+/// File name of the SAP NW RFC library on this platform
+#[cfg(target_family = "windows")]
+pub const RFC_LIB_NAME: &str = "sapnwrfc.dll";
+
+/// Environment variable that points to the RFC library, or to the
+/// directory containing it
+pub const RFC_LIB_PATH_ENV: &str = "RSRFC_LIB_PATH";
+
 impl RfcLib {
-    #[cfg(all(target_family = "unix", not(target_vendor = "apple")))]
-    pub fn new() -> Result<RfcLib, String> {
-        let rfc_api: dlopen::wrapper::Container<crate::rfc::RfcApi> =
-            unsafe { dlopen::wrapper::Container::load("libsapnwrfc.so") }
-                .map_err(|e| format!("Error trying to load libsapnwrfc: {:?}", e))?;
-        Ok(RfcLib {
-            rfc_api: Some(rfc_api),
-        })
+    /// Load the SAP NW RFC library. The following locations are tried
+    /// in order and the first one that loads wins:
+    ///
+    /// 1. `$RSRFC_LIB_PATH`, either the library itself or its directory
+    /// 2. the dynamic loader's search path (`LD_LIBRARY_PATH`,
+    ///    `DYLD_LIBRARY_PATH`, `PATH` on Windows, ...)
+    /// 3. `$SAPNWRFC_HOME/lib`, the location the SDK documentation suggests
+    /// 4. the directory of the running executable
+    pub fn new() -> Result<RfcLib, RfcLoadError> {
+        let mut attempts = Vec::new();
+        for path in RfcLib::search_path(None, |name| env::var_os(name)) {
+            match RfcLib::load(&path) {
+                Ok(rfc_lib) => return Ok(rfc_lib),
+                Err(reason) => attempts.push(RfcLoadAttempt { path, reason }),
+            }
+        }
+        Err(RfcLoadError::NotLoaded(attempts))
     }
 
-    #[cfg(all(target_family = "unix", target_vendor = "apple"))]
-    pub fn new() -> Result<RfcLib, String> {
-        let rfc_api: dlopen::wrapper::Container<crate::rfc::RfcApi> =
-            unsafe { dlopen::wrapper::Container::load("libsapnwrfc.dylib") }
-                .map_err(|e| format!("Error trying to load libsapnwrfc: {:?}", e))?;
-        Ok(RfcLib {
-            rfc_api: Some(rfc_api),
-        })
+    /// Load the SAP NW RFC library from an explicit location. `path` may
+    /// name the library itself or the directory containing it.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<RfcLib, RfcLoadError> {
+        let path = RfcLib::lib_file(path.as_ref());
+        RfcLib::load(&path)
+            .map_err(|reason| RfcLoadError::NotLoaded(vec![RfcLoadAttempt { path, reason }]))
+    }
+
+    /// Version of the loaded library
+    pub fn version(&self) -> RfcVersion {
+        let (mut major, mut minor, mut patch) = (0, 0, 0);
+        unsafe {
+            self.rfc_api
+                .RfcGetVersion(&mut major, &mut minor, &mut patch);
+        }
+        RfcVersion {
+            major,
+            minor,
+            patch,
+        }
     }
 
-    #[cfg(target_family = "windows")]
-    pub fn new() -> Result<RfcLib, String> {
+    /// Refuse libraries older than `min`, e.g. SDK builds with known bugs:
+    ///
+    /// ```no_run
+    /// use rsrfc::{RfcLib, RfcVersion};
+    ///
+    /// let rfc_lib = RfcLib::new()?.require_version(RfcVersion::new(7500, 0, 12))?;
+    /// # Ok::<(), rsrfc::error::RfcLoadError>(())
+    /// ```
+    pub fn require_version(self, min: RfcVersion) -> Result<RfcLib, RfcLoadError> {
+        let found = self.version();
+        if found < min {
+            return Err(RfcLoadError::VersionTooOld {
+                found,
+                required: min,
+            });
+        }
+        Ok(self)
+    }
+
+    fn load(path: &Path) -> Result<RfcLib, dlopen::Error> {
         let rfc_api: dlopen::wrapper::Container<crate::rfc::RfcApi> =
-            unsafe { dlopen::wrapper::Container::load("sapnwrfc.dll") }
-                .map_err(|e| format!("Error trying to load libsapnwrfc: {:?}", e))?;
+            unsafe { dlopen::wrapper::Container::load(path.as_os_str()) }?;
         Ok(RfcLib {
-            rfc_api: Some(rfc_api),
+            rfc_api: Arc::new(ManuallyDrop::new(rfc_api)),
         })
     }
-}
 
-impl Drop for RfcLib {
-    fn drop(&mut self) {
-        if let Some(api) = self.rfc_api.take() {
-            std::mem::forget(api);
+    fn lib_file(path: &Path) -> PathBuf {
+        if path.is_dir() {
+            path.join(RFC_LIB_NAME)
+        } else {
+            path.to_path_buf()
+        }
+    }
+
+    /// The locations [`RfcLib::new`] tries, in order, preceded by
+    /// `explicit` if given. `var` looks up environment variables, like
+    /// `std::env::var_os`.
+    pub fn search_path(
+        explicit: Option<&Path>,
+        var: impl Fn(&str) -> Option<OsString>,
+    ) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        if let Some(p) = explicit {
+            paths.push(RfcLib::lib_file(p));
+        }
+        if let Some(p) = var(RFC_LIB_PATH_ENV).filter(|p| !p.is_empty()) {
+            paths.push(RfcLib::lib_file(Path::new(&p)));
+        }
+        paths.push(PathBuf::from(RFC_LIB_NAME));
+        if let Some(home) = var("SAPNWRFC_HOME").filter(|p| !p.is_empty()) {
+            paths.push(Path::new(&home).join("lib").join(RFC_LIB_NAME));
+        }
+        if let Some(dir) = env::current_exe().ok().as_deref().and_then(Path::parent) {
+            paths.push(dir.join(RFC_LIB_NAME));
         }
+        paths
     }
 }
 
-impl<'rfclib> RfcConnection<'rfclib> {
-    pub fn new<'conn>(
+// Wraps the SDK's connection calls and returns their RfcErrorInfo
+#[allow(clippy::result_large_err)]
+impl RfcConnection {
+    pub fn new(
         conn_info: &RfcConnectionParameters,
-        rfc_lib: &'conn RfcLib,
-    ) -> Result<RfcConnection<'conn>, RfcErrorInfo> {
+        rfc_lib: &RfcLib,
+    ) -> Result<RfcConnection, RfcErrorInfo> {
+        conn_info.validate()?;
         let parms = conn_info.convert();
         RfcConnection::from_parm_helper(parms, rfc_lib)
     }
 
     /// Open a connection to an SAP system via RFC
-    pub fn from_parm_helper<'conn>(
+    pub fn from_parm_helper(
         parms: RfcConnParmHelper,
-        rfc_lib: &'conn RfcLib,
-    ) -> Result<RfcConnection<'conn>, RfcErrorInfo> {
+        rfc_lib: &RfcLib,
+    ) -> Result<RfcConnection, RfcErrorInfo> {
         let mut err_trunk = RfcErrorInfo::new();
         unsafe {
             let ch = parms.as_vec(|pv| {
-                rfc_lib.rfc_api.as_ref().unwrap().RfcOpenConnection(
-                    pv.as_ptr(),
-                    pv.len() as u32,
-                    &mut err_trunk,
-                )
+                rfc_lib
+                    .rfc_api
+                    .RfcOpenConnection(pv.as_ptr(), pv.len() as u32, &mut err_trunk)
             });
             if ch.is_null() {
                 Err(err_trunk)
             } else {
                 Ok(RfcConnection {
                     connection_handle: ch,
-                    rfc_lib,
+                    rfc_lib: rfc_lib.clone(),
                 })
             }
         }
     }
 
     /// Open a connection to an SAP system via RFC
-    pub fn from_hashmap<'conn>(
+    pub fn from_hashmap(
         parms: &HashMap<String, String>,
-        rfc_lib: &'conn RfcLib,
-    ) -> Result<RfcConnection<'conn>, RfcErrorInfo> {
+        rfc_lib: &RfcLib,
+    ) -> Result<RfcConnection, RfcErrorInfo> {
         let mut ph = RfcConnParmHelper::new();
         for (k, v) in parms {
             ph.add(k, v);
@@ -159,6 +281,21 @@ impl<'rfclib> RfcConnection<'rfclib> {
         RfcConnection::from_parm_helper(ph, rfc_lib)
     }
 
+    /// Check that the connection is still alive
+    pub fn ping(&self) -> Result<(), RfcErrorInfo> {
+        let mut err_trunk = RfcErrorInfo::new();
+        let res = unsafe {
+            self.rfc_lib
+                .rfc_api
+                .RfcPing(self.connection_handle, &mut err_trunk)
+        };
+        if res.is_ok() {
+            Ok(())
+        } else {
+            Err(err_trunk)
+        }
+    }
+
     /// Return a reference to an RFC enabled function, if it exists on
     /// the remote system.
     pub fn get_function<'conn, 'fun: 'conn>(
@@ -168,7 +305,7 @@ impl<'rfclib> RfcConnection<'rfclib> {
         let name_uc = U16CString::from_str(name).unwrap().into_vec_with_nul();
         let mut err_trunk = RfcErrorInfo::new();
         unsafe {
-            let fd = self.rfc_lib.rfc_api.as_ref().unwrap().RfcGetFunctionDesc(
+            let fd = self.rfc_lib.rfc_api.RfcGetFunctionDesc(
                 self.connection_handle,
                 name_uc.as_ptr(),
                 &mut err_trunk,
@@ -176,12 +313,7 @@ impl<'rfclib> RfcConnection<'rfclib> {
             if fd.is_null() {
                 return Err(err_trunk);
             }
-            let ff = self
-                .rfc_lib
-                .rfc_api
-                .as_ref()
-                .unwrap()
-                .RfcCreateFunction(fd, &mut err_trunk);
+            let ff = self.rfc_lib.rfc_api.RfcCreateFunction(fd, &mut err_trunk);
             if ff.is_null() {
                 return Err(err_trunk);
             }
@@ -193,35 +325,35 @@ impl<'rfclib> RfcConnection<'rfclib> {
             let fun_desc = RfcDecodedFieldDesc::from_handle(fftd)?;
             */
             let mut parm_count: u32 = 0;
-            let res = self.rfc_lib.rfc_api.as_ref().unwrap().RfcGetParameterCount(
-                fd,
-                &mut parm_count,
-                &mut err_trunk,
-            );
+            let res =
+                self.rfc_lib
+                    .rfc_api
+                    .RfcGetParameterCount(fd, &mut parm_count, &mut err_trunk);
             if !res.is_ok() {
                 return Err(err_trunk);
             }
 
             let mut fun_desc = Vec::new();
             {
-                let mut rpd = RfcParameterDesc::new(&self.rfc_lib.rfc_api.as_ref().unwrap());
+                let mut rpd = RfcParameterDesc::new();
                 fun_desc.reserve_exact(parm_count as usize);
                 for i in 0..parm_count {
-                    let res = self
-                        .rfc_lib
-                        .rfc_api
-                        .as_ref()
-                        .unwrap()
-                        .RfcGetParameterDescByIndex(fd, i, &mut rpd, &mut err_trunk);
+                    let res = self.rfc_lib.rfc_api.RfcGetParameterDescByIndex(
+                        fd,
+                        i,
+                        &mut rpd,
+                        &mut err_trunk,
+                    );
                     if !res.is_ok() {
                         return Err(err_trunk);
                     }
-                    let parm = rpd.to_parameter(i, ff)?;
+                    let parm = rpd.to_parameter(&self.rfc_lib.rfc_api, i, ff)?;
                     fun_desc.push(parm);
                 }
             }
 
             Ok(RfcFunction {
+                name: name.to_uppercase(),
                 connection: self,
                 fun: ff,
                 fun_desc,
@@ -230,7 +362,14 @@ impl<'rfclib> RfcConnection<'rfclib> {
     }
 }
 
+// Wraps RfcInvoke and the parameter calls, returning their RfcErrorInfo
+#[allow(clippy::result_large_err)]
 impl<'conn, 'fun> RfcFunction<'conn, 'fun> {
+    /// The name of the function module
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     /// Get a reference to an RFC parameter using the parameter index.
     pub fn get_parameter_by_index(
         &mut self,
@@ -245,30 +384,24 @@ impl<'conn, 'fun> RfcFunction<'conn, 'fun> {
         &mut self,
         parameter_name: &str,
     ) -> Option<&mut RfcParameter<'conn, 'fun>> {
-        for p in self.fun_desc.iter_mut() {
-            if p.name.eq_ignore_ascii_case(parameter_name) {
-                return Some(p);
-            }
-        }
-        None
+        self.fun_desc
+            .iter_mut()
+            .find(|p| p.name.eq_ignore_ascii_case(parameter_name))
     }
 
     /// Get a reference to an RFC parameter using the parameter name. This
     /// is a case insensitive operation.
     pub fn get_parameter(&self, parameter_name: &str) -> Option<&RfcParameter<'conn, 'fun>> {
-        for p in self.fun_desc.iter() {
-            if p.name.eq_ignore_ascii_case(parameter_name) {
-                return Some(p);
-            }
-        }
-        None
+        self.fun_desc
+            .iter()
+            .find(|p| p.name.eq_ignore_ascii_case(parameter_name))
     }
 
     /// Call the remote function
     pub fn call(&mut self) -> Result<(), RfcErrorInfo> {
         let mut err_trunk = RfcErrorInfo::new();
         let res = unsafe {
-            self.connection.rfc_lib.rfc_api.as_ref().unwrap().RfcInvoke(
+            self.connection.rfc_lib.rfc_api.RfcInvoke(
                 self.connection.connection_handle,
                 self.fun,
                 &mut err_trunk,
@@ -281,15 +414,13 @@ impl<'conn, 'fun> RfcFunction<'conn, 'fun> {
     }
 }
 
-impl<'rfclib> Drop for RfcConnection<'rfclib> {
+impl Drop for RfcConnection {
     fn drop(&mut self) {
         if !self.connection_handle.is_null() {
             let mut err_trunk = RfcErrorInfo::new();
             let res = unsafe {
                 self.rfc_lib
                     .rfc_api
-                    .as_ref()
-                    .unwrap()
                     .RfcCloseConnection(self.connection_handle, &mut err_trunk)
             };
             if !res.is_ok() {
@@ -310,8 +441,6 @@ impl<'conn, 'fun> Drop for RfcFunction<'conn, 'fun> {
                 self.connection
                     .rfc_lib
                     .rfc_api
-                    .as_ref()
-                    .unwrap()
                     .RfcDestroyFunction(self.fun, &mut err_trunk)
             };
             if !res.is_ok() {
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use rsrfc::{error::*, *};
+use rsrfc::*;
 
 fn main() {
     println!("main函数开始执行");
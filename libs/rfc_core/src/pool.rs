@@ -0,0 +1,244 @@
+//! A pool of open RFC connections, so that not every call pays for a logon.
+use std::ops::Deref;
+use std::path::PathBuf;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::client::RfcLibTrait;
+use crate::error::*;
+use crate::{RfcConnection, RfcConnectionParameters, RfcLib};
+
+/// Settings of an RfcPool
+#[derive(Debug, Clone)]
+pub struct RfcPoolConfig {
+    /// Maximum number of open connections, idle or checked out. Checkouts
+    /// beyond that wait until a connection is returned.
+    pub max_size: usize,
+    /// Idle connections are closed after this time
+    pub idle_timeout: Duration,
+    /// How long a checkout waits for a connection before it fails with
+    /// RfcTimeout
+    pub checkout_timeout: Duration,
+    /// Check idle connections with RfcPing before handing them out, and
+    /// replace the ones that do not answer
+    pub health_check: bool,
+}
+
+impl Default for RfcPoolConfig {
+    fn default() -> Self {
+        RfcPoolConfig {
+            max_size: 4,
+            idle_timeout: Duration::from_secs(300),
+            checkout_timeout: Duration::from_secs(30),
+            health_check: true,
+        }
+    }
+}
+
+/// Where the pool takes the parameters of new connections from
+pub(crate) enum PoolTarget {
+    /// A configuration file and environment variables, see RfcClient
+    Config {
+        system: Option<String>,
+        file: PathBuf,
+    },
+    Parameters(Box<RfcConnectionParameters>),
+}
+
+struct IdleConnection {
+    conn: RfcConnection,
+    since: Instant,
+}
+
+struct PoolState {
+    idle: Vec<IdleConnection>,
+    open: usize,
+}
+
+/// A pool of RFC connections to one SAP system. Connections are checked
+/// out with [`RfcPool::checkout`] and go back to the pool when the
+/// returned guard is dropped. The pool can be shared between threads.
+pub struct RfcPool {
+    rfc_lib: RfcLib,
+    pub(crate) target: PoolTarget,
+    pub(crate) config: RfcPoolConfig,
+    state: Mutex<PoolState>,
+    returned: Condvar,
+}
+
+// Opening a connection fails with the RfcErrorInfo of RfcOpenConnection
+#[allow(clippy::result_large_err)]
+impl RfcPool {
+    /// Create a pool for connections with the given parameters. No
+    /// connection is opened before the first checkout.
+    pub fn new(rfc_lib: RfcLib, params: RfcConnectionParameters, config: RfcPoolConfig) -> RfcPool {
+        RfcPool::with_target(rfc_lib, PoolTarget::Parameters(Box::new(params)), config)
+    }
+
+    pub(crate) fn with_target(
+        rfc_lib: RfcLib,
+        target: PoolTarget,
+        config: RfcPoolConfig,
+    ) -> RfcPool {
+        RfcPool {
+            rfc_lib,
+            target,
+            config,
+            state: Mutex::new(PoolState {
+                idle: Vec::new(),
+                open: 0,
+            }),
+            returned: Condvar::new(),
+        }
+    }
+
+    pub fn config(&self) -> &RfcPoolConfig {
+        &self.config
+    }
+
+    /// Number of open connections, idle or checked out
+    pub fn size(&self) -> usize {
+        self.lock().open
+    }
+
+    /// Number of idle connections
+    pub fn idle(&self) -> usize {
+        self.lock().idle.len()
+    }
+
+    /// Take a connection out of the pool, opening a new one if none is
+    /// idle and the pool is not full yet
+    pub fn checkout(&self) -> Result<PooledConnection<'_>, RfcErrorInfo> {
+        let deadline = Instant::now() + self.config.checkout_timeout;
+        let mut state = self.lock();
+        loop {
+            let expired = self.take_expired(&mut state);
+            if !expired.is_empty() {
+                drop(state);
+                // Closing is a round trip to the server; the lock is free
+                // meanwhile
+                drop(expired);
+                self.returned.notify_one();
+                state = self.lock();
+            }
+
+            if let Some(IdleConnection { conn, .. }) = state.idle.pop() {
+                drop(state);
+                if !self.config.health_check || conn.ping().is_ok() {
+                    return Ok(PooledConnection {
+                        pool: self,
+                        conn: Some(conn),
+                    });
+                }
+                // Dropping closes the broken connection
+                drop(conn);
+                state = self.lock();
+                state.open -= 1;
+                continue;
+            }
+
+            if state.open < self.config.max_size {
+                state.open += 1;
+                drop(state);
+                return match self.open() {
+                    Ok(conn) => Ok(PooledConnection {
+                        pool: self,
+                        conn: Some(conn),
+                    }),
+                    Err(e) => {
+                        self.lock().open -= 1;
+                        self.returned.notify_one();
+                        Err(e)
+                    }
+                };
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(RfcErrorInfo::with_code(
+                    RfcRc::RfcTimeout,
+                    &format!(
+                        "No connection became available within {:?}",
+                        self.config.checkout_timeout
+                    ),
+                ));
+            }
+            state = self
+                .returned
+                .wait_timeout(state, deadline - now)
+                .unwrap_or_else(|e| e.into_inner())
+                .0;
+        }
+    }
+
+    fn open(&self) -> Result<RfcConnection, RfcErrorInfo> {
+        match &self.target {
+            PoolTarget::Config { system, file } => self.rfc_lib.connect(system.as_deref(), file),
+            PoolTarget::Parameters(params) => RfcConnection::new(params, &self.rfc_lib),
+        }
+    }
+
+    fn checkin(&self, conn: RfcConnection) {
+        let mut state = self.lock();
+        state.idle.push(IdleConnection {
+            conn,
+            since: Instant::now(),
+        });
+        let expired = self.take_expired(&mut state);
+        drop(state);
+        self.returned.notify_one();
+        // Dropping closes the connections, after the lock is released
+        drop(expired);
+    }
+
+    /// Remove the idle connections that timed out. They are closed when
+    /// the result is dropped, which should happen without holding the lock.
+    fn take_expired(&self, state: &mut PoolState) -> Vec<IdleConnection> {
+        let timeout = self.config.idle_timeout;
+        let (expired, idle): (Vec<_>, Vec<_>) = std::mem::take(&mut state.idle)
+            .into_iter()
+            .partition(|c| c.since.elapsed() >= timeout);
+        state.idle = idle;
+        state.open -= expired.len();
+        expired
+    }
+
+    fn lock(&self) -> MutexGuard<'_, PoolState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A connection checked out of an RfcPool. It goes back to the pool when
+/// dropped.
+pub struct PooledConnection<'pool> {
+    pool: &'pool RfcPool,
+    conn: Option<RfcConnection>,
+}
+
+impl PooledConnection<'_> {
+    /// Close the connection instead of returning it to the pool, e.g.
+    /// after a communication failure broke it
+    pub fn discard(mut self) {
+        if let Some(conn) = self.conn.take() {
+            drop(conn);
+            self.pool.lock().open -= 1;
+            self.pool.returned.notify_one();
+        }
+    }
+}
+
+impl Deref for PooledConnection<'_> {
+    type Target = RfcConnection;
+
+    fn deref(&self) -> &RfcConnection {
+        self.conn.as_ref().unwrap()
+    }
+}
+
+impl Drop for PooledConnection<'_> {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            self.pool.checkin(conn);
+        }
+    }
+}
@@ -0,0 +1,113 @@
+//! Repeating calls that failed for a transient reason, see
+//! [`RfcClient::with_retry_policy`](crate::RfcClient::with_retry_policy).
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::error::RfcError;
+
+/// Called before each retry with the name of the function module, the
+/// number of the retry starting at 1, the backoff and the error of the
+/// failed attempt
+pub type RetryHook = Arc<dyn Fn(&str, u32, Duration, &RfcError) + Send + Sync>;
+
+/// When and how often the client repeats a call that failed with an error
+/// for which [`RfcError::is_retryable`](crate::error::RfcError::is_retryable)
+/// holds. The connection of the failed call is closed and the next attempt
+/// logs on again.
+///
+/// A function module that changes data may have done so before the
+/// connection broke, so only the ones marked with
+/// [`RfcClient::with_read_only`](crate::RfcClient::with_read_only) are
+/// retried, unless `all_functions` is set.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    /// Attempts per call, including the first one. 1 turns retries off.
+    pub max_attempts: u32,
+    /// Wait before the first retry
+    pub initial_backoff: Duration,
+    /// Each further retry waits this many times as long as the one before
+    pub multiplier: f64,
+    /// Upper bound of the wait before a retry
+    pub max_backoff: Duration,
+    /// Wait a random time between half and all of the backoff, so that
+    /// clients that failed together do not retry together
+    pub jitter: bool,
+    /// Retry every function module, not only the read-only ones. Only set
+    /// this if all function modules the client calls are safe to call
+    /// twice.
+    pub all_functions: bool,
+    /// Told about every retry, e.g. to log it. Retries are silent without
+    /// one.
+    pub on_retry: Option<RetryHook>,
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("initial_backoff", &self.initial_backoff)
+            .field("multiplier", &self.multiplier)
+            .field("max_backoff", &self.max_backoff)
+            .field("jitter", &self.jitter)
+            .field("all_functions", &self.all_functions)
+            .field("on_retry", &self.on_retry.as_ref().map(|_| "Fn"))
+            .finish()
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(200),
+            multiplier: 2.0,
+            max_backoff: Duration::from_secs(10),
+            jitter: true,
+            all_functions: false,
+            on_retry: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries
+    pub fn none() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// Set `on_retry`
+    pub fn on_retry(
+        mut self,
+        hook: impl Fn(&str, u32, Duration, &RfcError) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_retry = Some(Arc::new(hook));
+        self
+    }
+
+    /// The wait before retry number `retry`, starting at 1
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponent = retry.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let backoff = self.initial_backoff.as_secs_f64() * self.multiplier.powi(exponent);
+        let backoff = Duration::try_from_secs_f64(backoff)
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff);
+        if self.jitter {
+            backoff.mul_f64(0.5 + 0.5 * random())
+        } else {
+            backoff
+        }
+    }
+}
+
+/// A random number in [0, 1). Every RandomState has new keys, so hashing
+/// nothing with it is random enough for spreading out retries.
+fn random() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
@@ -1,17 +1,28 @@
 #![allow(non_snake_case)]
 
-use dlopen::wrapper::{Container, WrapperApi};
+use dlopen::wrapper::{Container, WrapperApi, WrapperMultiApi};
 use std::marker::PhantomData;
+use std::ops::Deref;
 use std::ptr::null_mut;
 
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
 use std::cmp::min;
 use widestring::U16CString;
 
 use crate::error::*;
+use crate::value::{FromRfcValue, ToRfcValue};
+use crate::ToTable;
+
+pub(crate) mod datetime;
+#[cfg(feature = "rust_decimal")]
+pub mod decfloat;
+#[cfg(target_pointer_width = "64")]
+mod layout;
 
 pub enum RfcFunctionDescHandle {}
 pub enum RfcConnectionHandle {}
 pub enum RfcDataContainerHandle {}
+pub enum RfcTypeDescHandle {}
 pub enum RfcExtendedDescription {}
 
 /// Parameters specifying the RFC connection details
@@ -21,6 +32,23 @@ pub struct RfcConnectionParameter {
     pub value: *const u16,
 }
 
+/// RFC_DECF16, an IEEE 754 decimal64. Like in sapdecf.h, the union gives
+/// it the alignment of a double.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub union RfcDecF16 {
+    bytes: [u8; 8],
+    _align: f64,
+}
+
+/// RFC_DECF34, an IEEE 754 decimal128
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub union RfcDecF34 {
+    bytes: [u8; 16],
+    _align: f64,
+}
+
 /// RFC data type
 #[repr(u32)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -49,14 +77,17 @@ pub enum RfcType {
     UtcSecond = 33,
     UtcMinute = 34,
     DtDay = 35,
-    DtMonth = 36,
-    TSecond = 37,
-    TMinute = 38,
-    CDay = 39,
-    Box = 40,
-    GenericBox = 41,
+    DtWeek = 36,
+    DtMonth = 37,
+    TSecond = 38,
+    TMinute = 39,
+    CDay = 40,
+    Box = 41,
+    GenericBox = 42,
 }
 
+// The checks fail with the same RfcErrorInfo as the SDK calls they guard
+#[allow(clippy::result_large_err)]
 impl RfcType {
     /// Return true if the RFC type is a table or a struct.
     /// (A table is a list of structs, if you will)
@@ -80,7 +111,7 @@ impl RfcType {
         if self.is_struct_or_table() {
             Ok(())
         } else {
-            Err(RfcErrorInfo::custom("Expected struct table"))
+            Err(RfcErrorInfo::invalid_parameter("Expected struct table"))
         }
     }
 
@@ -89,7 +120,7 @@ impl RfcType {
         if self.is_table() {
             Ok(())
         } else {
-            Err(RfcErrorInfo::custom("Expected table"))
+            Err(RfcErrorInfo::invalid_parameter("Expected table"))
         }
     }
 }
@@ -98,6 +129,7 @@ impl RfcType {
 /// This enum specified the kind.
 #[repr(u32)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[allow(clippy::enum_variant_names)]
 pub enum RfcDirection {
     /// A parameter goes from the caller to the callee
     RfcImport = 1,
@@ -113,28 +145,29 @@ pub enum RfcDirection {
 impl RfcDirection {
     /// Return true if the RFC parameter can be modified
     pub fn can_write(&self) -> bool {
-        match &self {
-            &RfcDirection::RfcImport => true,
-            &RfcDirection::RfcExport => false,
-            &RfcDirection::RfcChanging => true,
-            &RfcDirection::RfcTables => true,
+        match self {
+            RfcDirection::RfcImport => true,
+            RfcDirection::RfcExport => false,
+            RfcDirection::RfcChanging => true,
+            RfcDirection::RfcTables => true,
         }
     }
 
     /// Return true if the RFC parameter can be read
     pub fn can_read(&self) -> bool {
-        match &self {
-            &RfcDirection::RfcImport => false,
-            &RfcDirection::RfcExport => true,
-            &RfcDirection::RfcChanging => true,
-            &RfcDirection::RfcTables => true,
+        match self {
+            RfcDirection::RfcImport => false,
+            RfcDirection::RfcExport => true,
+            RfcDirection::RfcChanging => true,
+            RfcDirection::RfcTables => true,
         }
     }
 }
 
-/// Internal RFC lib structure describing one RFC parameter.
+/// Internal RFC lib structure describing one field of a structure or
+/// table (RFC_FIELD_DESC).
 #[repr(C)]
-pub struct RfcFieldDesc<'conn> {
+pub struct RfcFieldDesc {
     name: [u16; 31],
     field_type: RfcType,
     nuc_length: u32,
@@ -142,16 +175,17 @@ pub struct RfcFieldDesc<'conn> {
     uc_length: u32,
     uc_offset: u32,
     decimals: u32,
-    type_desc_handle: *mut RfcDataContainerHandle,
+    type_desc_handle: *mut RfcTypeDescHandle,
     extended_description: *mut RfcExtendedDescription,
-    rfc_api: &'conn Container<RfcApi>,
 }
 
 /// Create an empty RFC field desciption
-impl<'conn> RfcFieldDesc<'conn> {
-    pub fn new(rfc_api: &'conn Container<RfcApi>) -> RfcFieldDesc<'conn> {
+// Returns the RfcErrorInfo of RfcGetFieldDescByIndex
+#[allow(clippy::result_large_err)]
+impl RfcFieldDesc {
+    pub fn new() -> RfcFieldDesc {
         RfcFieldDesc {
-            name: [0 as u16; 31],
+            name: [0_u16; 31],
             field_type: RfcType::String,
             nuc_length: 0,
             nuc_offset: 0,
@@ -160,55 +194,44 @@ impl<'conn> RfcFieldDesc<'conn> {
             decimals: 0,
             type_desc_handle: null_mut(),
             extended_description: null_mut(),
-            rfc_api,
         }
     }
 
-    /// Convert to an RFC parameter
-    pub fn to_parameter<'strct: 'conn>(
+    /// Convert to an RFC parameter. The structures and tables nested in
+    /// a table row differ from row to row; for fields of a table they are
+    /// looked up when the field is accessed, see
+    /// RfcParameter::get_field_by_index.
+    pub fn to_parameter<'conn, 'strct: 'conn>(
         &self,
+        rfc_api: &'conn Container<RfcApi>,
         index: u32,
         fun: *mut RfcDataContainerHandle,
+        in_table: bool,
     ) -> Result<RfcParameter<'conn, 'strct>, RfcErrorInfo> {
         let name_s = unsafe { U16CString::from_ptr_with_nul(self.name.as_ptr(), 31) };
         if let Err(e) = name_s {
-            return Err(RfcErrorInfo::custom(&e.to_string()));
+            return Err(RfcErrorInfo::conversion(&e.to_string()));
         }
         let name_s = name_s.unwrap().to_string();
         if let Err(e) = name_s {
-            return Err(RfcErrorInfo::custom(&e.to_string()));
+            return Err(RfcErrorInfo::conversion(&e.to_string()));
         }
         let name_s = name_s.unwrap();
 
-        let mut structure_or_table = null_mut();
-        if &self.field_type == &RfcType::Structure {
-            let mut err_trunk = RfcErrorInfo::new();
-            let res = unsafe {
-                self.rfc_api.RfcGetStructureByIndex(
-                    fun,
-                    index,
-                    &mut structure_or_table,
-                    &mut err_trunk,
-                )
-            };
-            if !res.is_ok() {
-                return Err(err_trunk);
-            }
-        } else if &self.field_type == &RfcType::Table {
-            let mut err_trunk = RfcErrorInfo::new();
-            let res = unsafe {
-                self.rfc_api
-                    .RfcGetTableByIndex(fun, index, &mut structure_or_table, &mut err_trunk)
-            };
-            if !res.is_ok() {
-                return Err(err_trunk);
-            }
-        }
+        let structure_or_table = if in_table {
+            null_mut()
+        } else {
+            sub_container(rfc_api, self.field_type, fun, index)?
+        };
 
         let struct_def = if structure_or_table.is_null() {
             None
         } else {
-            let res = RfcDecodedFieldDesc::from_handle(self.rfc_api, structure_or_table)?;
+            let res = RfcDecodedFieldDesc::from_handle(
+                rfc_api,
+                structure_or_table,
+                self.field_type.is_table(),
+            )?;
             Some(res)
         };
 
@@ -218,18 +241,47 @@ impl<'conn> RfcFieldDesc<'conn> {
             field_type: self.field_type,
             direction: RfcDirection::RfcChanging,
             len: self.uc_length,
+            decimals: self.decimals,
             struct_def,
             default_value: None,
             optional: false,
             fun,
             structure_or_table,
+            row: None,
             p1: PhantomData,
             p2: PhantomData,
-            rfc_api: self.rfc_api,
+            rfc_api,
         })
     }
 }
 
+/// The handle of the structure or table in field `index` of a container,
+/// or null for other types
+// Returns the RfcErrorInfo of RfcGetStructure and RfcGetTable
+#[allow(clippy::result_large_err)]
+fn sub_container(
+    rfc_api: &Container<RfcApi>,
+    field_type: RfcType,
+    fun: *mut RfcDataContainerHandle,
+    index: u32,
+) -> Result<*mut RfcDataContainerHandle, RfcErrorInfo> {
+    let mut structure_or_table = null_mut();
+    let mut err_trunk = RfcErrorInfo::new();
+    let res = match field_type {
+        RfcType::Structure => unsafe {
+            rfc_api.RfcGetStructureByIndex(fun, index, &mut structure_or_table, &mut err_trunk)
+        },
+        RfcType::Table => unsafe {
+            rfc_api.RfcGetTableByIndex(fun, index, &mut structure_or_table, &mut err_trunk)
+        },
+        _ => return Ok(null_mut()),
+    };
+    if !res.is_ok() {
+        return Err(err_trunk);
+    }
+    Ok(structure_or_table)
+}
+
 /// Decoded RFC field description
 pub struct RfcDecodedFieldDesc<'conn, 'strct: 'conn> {
     pub fields: Vec<RfcDecodedField<'conn, 'strct>>,
@@ -237,52 +289,54 @@ pub struct RfcDecodedFieldDesc<'conn, 'strct: 'conn> {
 }
 
 /// An RFC parameter description, RFC library internal structure
+/// (RFC_PARAMETER_DESC)
 #[repr(C)]
-pub struct RfcParameterDesc<'conn> {
+pub struct RfcParameterDesc {
     pub name: [u16; 31],
     pub field_type: RfcType,
     pub direction: RfcDirection,
     pub nuc_length: u32,
     pub uc_length: u32,
     pub decimals: u32,
-    pub type_desc_handle: *mut RfcDataContainerHandle,
+    pub type_desc_handle: *mut RfcTypeDescHandle,
     pub default_value: [u16; 31],
     pub parameter_text: [u16; 80],
     pub optional: u8,
-    pub extended_description: *mut u8,
-    rfc_api: &'conn Container<RfcApi>,
+    pub extended_description: *mut RfcExtendedDescription,
 }
 
-impl<'conn> RfcParameterDesc<'conn> {
-    pub fn new(rfc_api: &'conn Container<RfcApi>) -> RfcParameterDesc<'conn> {
+// Returns the RfcErrorInfo of RfcGetParameterDescByIndex
+#[allow(clippy::result_large_err)]
+impl RfcParameterDesc {
+    pub fn new() -> RfcParameterDesc {
         RfcParameterDesc {
-            name: [0 as u16; 31],
+            name: [0_u16; 31],
             field_type: RfcType::String,
             direction: RfcDirection::RfcExport,
             nuc_length: 0,
             uc_length: 0,
             decimals: 0,
             type_desc_handle: null_mut(),
-            default_value: [0 as u16; 31],
-            parameter_text: [0 as u16; 80],
-            optional: 0 as u8,
+            default_value: [0_u16; 31],
+            parameter_text: [0_u16; 80],
+            optional: 0_u8,
             extended_description: null_mut(),
-            rfc_api,
         }
     }
 
-    pub fn to_parameter<'strct: 'conn>(
+    pub fn to_parameter<'conn, 'strct: 'conn>(
         &self,
+        rfc_api: &'conn Container<RfcApi>,
         index: u32,
         fun: *mut RfcDataContainerHandle,
     ) -> Result<RfcParameter<'conn, 'strct>, RfcErrorInfo> {
         let name_s = unsafe { U16CString::from_ptr_with_nul(self.name.as_ptr(), 31) };
         if let Err(e) = name_s {
-            return Err(RfcErrorInfo::custom(&e.to_string()));
+            return Err(RfcErrorInfo::conversion(&e.to_string()));
         }
         let name_s = name_s.unwrap().to_string();
         if let Err(e) = name_s {
-            return Err(RfcErrorInfo::custom(&e.to_string()));
+            return Err(RfcErrorInfo::conversion(&e.to_string()));
         }
         let name_s = name_s.unwrap();
 
@@ -292,34 +346,28 @@ impl<'conn> RfcParameterDesc<'conn> {
             let default_value_s =
                 unsafe { U16CString::from_ptr_with_nul(self.default_value.as_ptr(), 31) };
             if let Err(e) = default_value_s {
-                return Err(RfcErrorInfo::custom(&e.to_string()));
+                return Err(RfcErrorInfo::conversion(&e.to_string()));
             }
             let default_value_s = default_value_s.unwrap().to_string();
             if let Err(e) = default_value_s {
-                return Err(RfcErrorInfo::custom(&e.to_string()));
+                return Err(RfcErrorInfo::conversion(&e.to_string()));
             }
             Some(default_value_s.unwrap())
         };
 
         let mut structure_or_table = null_mut();
-        if &self.field_type == &RfcType::Structure {
+        if self.field_type == RfcType::Structure {
             let mut err_trunk = RfcErrorInfo::new();
             let res = unsafe {
-                self.rfc_api.RfcGetStructureByIndex(
-                    fun,
-                    index,
-                    &mut structure_or_table,
-                    &mut err_trunk,
-                )
+                rfc_api.RfcGetStructureByIndex(fun, index, &mut structure_or_table, &mut err_trunk)
             };
             if !res.is_ok() {
                 return Err(err_trunk);
             }
-        } else if &self.field_type == &RfcType::Table {
+        } else if self.field_type == RfcType::Table {
             let mut err_trunk = RfcErrorInfo::new();
             let res = unsafe {
-                self.rfc_api
-                    .RfcGetTableByIndex(fun, index, &mut structure_or_table, &mut err_trunk)
+                rfc_api.RfcGetTableByIndex(fun, index, &mut structure_or_table, &mut err_trunk)
             };
             if !res.is_ok() {
                 return Err(err_trunk);
@@ -329,7 +377,11 @@ impl<'conn> RfcParameterDesc<'conn> {
         let struct_def = if structure_or_table.is_null() {
             None
         } else {
-            let res = RfcDecodedFieldDesc::from_handle(self.rfc_api, structure_or_table)?;
+            let res = RfcDecodedFieldDesc::from_handle(
+                rfc_api,
+                structure_or_table,
+                self.field_type.is_table(),
+            )?;
             Some(res)
         };
 
@@ -339,14 +391,16 @@ impl<'conn> RfcParameterDesc<'conn> {
             field_type: self.field_type,
             direction: self.direction,
             len: self.uc_length,
+            decimals: self.decimals,
             struct_def,
             default_value,
             optional: self.optional != 0,
             fun,
             structure_or_table,
+            row: None,
             p1: PhantomData,
             p2: PhantomData,
-            rfc_api: self.rfc_api,
+            rfc_api,
         })
     }
 }
@@ -359,29 +413,49 @@ pub struct RfcParameter<'conn, 'strct: 'conn> {
     pub field_type: RfcType,
     pub direction: RfcDirection,
     pub len: u32,
+    /// Number of decimals of a BCD
+    pub decimals: u32,
     struct_def: Option<RfcDecodedFieldDesc<'conn, 'strct>>,
     default_value: Option<String>,
     optional: bool,
     fun: *mut RfcDataContainerHandle,
     structure_or_table: *mut RfcDataContainerHandle,
+    /// The last row appended with `append_row`, as a structure
+    row: Option<Box<RfcParameter<'conn, 'strct>>>,
     p1: PhantomData<&'conn RfcConnectionHandle>,
     p2: PhantomData<&'strct RfcDataContainerHandle>,
     rfc_api: &'conn Container<RfcApi>,
 }
 
+// Returns the RfcErrorInfo of the SDK's type description calls
+#[allow(clippy::result_large_err)]
 impl<'conn, 'strct: 'conn> RfcDecodedFieldDesc<'conn, 'strct> {
     pub fn from_handle(
         rfc_api: &'conn Container<RfcApi>,
         handle: *mut RfcDataContainerHandle,
+        is_table: bool,
     ) -> Result<RfcDecodedFieldDesc<'conn, 'strct>, RfcErrorInfo> {
-        let mut count: u32 = 0;
         let mut err_trunk = RfcErrorInfo::new();
 
         let type_handle = unsafe { rfc_api.RfcDescribeType(handle, &mut err_trunk) };
         if type_handle.is_null() {
             return Err(err_trunk);
         }
+        RfcDecodedFieldDesc::from_type_handle(rfc_api, type_handle, handle, is_table)
+    }
 
+    /// Decode a type description. Parameters can only be created for an
+    /// actual data container, so nested types, which are decoded from
+    /// their type description alone, pass a null handle and only get
+    /// their fields decoded.
+    fn from_type_handle(
+        rfc_api: &'conn Container<RfcApi>,
+        type_handle: *mut RfcTypeDescHandle,
+        handle: *mut RfcDataContainerHandle,
+        is_table: bool,
+    ) -> Result<RfcDecodedFieldDesc<'conn, 'strct>, RfcErrorInfo> {
+        let mut count: u32 = 0;
+        let mut err_trunk = RfcErrorInfo::new();
         {
             let res = unsafe { rfc_api.RfcGetFieldCount(type_handle, &mut count, &mut err_trunk) };
             if !res.is_ok() {
@@ -391,7 +465,7 @@ impl<'conn, 'strct: 'conn> RfcDecodedFieldDesc<'conn, 'strct> {
         let mut fields = Vec::new();
         let mut parameters = Vec::new();
         {
-            let mut rfc_field_desc = RfcFieldDesc::new(rfc_api);
+            let mut rfc_field_desc = RfcFieldDesc::new();
             for i in 0..count {
                 let res = unsafe {
                     rfc_api.RfcGetFieldDescByIndex(
@@ -401,10 +475,12 @@ impl<'conn, 'strct: 'conn> RfcDecodedFieldDesc<'conn, 'strct> {
                         &mut err_trunk,
                     )
                 };
-                parameters.push(rfc_field_desc.to_parameter(i, handle)?);
                 if !res.is_ok() {
                     return Err(err_trunk);
                 }
+                if !handle.is_null() {
+                    parameters.push(rfc_field_desc.to_parameter(rfc_api, i, handle, is_table)?);
+                }
                 let name = unsafe {
                     U16CString::from_ptr_with_nul(rfc_field_desc.name.as_ptr(), 31)
                         .unwrap()
@@ -413,8 +489,12 @@ impl<'conn, 'strct: 'conn> RfcDecodedFieldDesc<'conn, 'strct> {
                 let sub_fields = if rfc_field_desc.type_desc_handle.is_null() {
                     None
                 } else {
-                    let d =
-                        RfcDecodedFieldDesc::from_handle(rfc_api, rfc_field_desc.type_desc_handle)?;
+                    let d = RfcDecodedFieldDesc::from_type_handle(
+                        rfc_api,
+                        rfc_field_desc.type_desc_handle,
+                        null_mut(),
+                        false,
+                    )?;
                     Some(Box::new(d))
                 };
                 let field = RfcDecodedField {
@@ -443,24 +523,48 @@ pub struct RfcDecodedField<'conn, 'strct: 'conn> {
     phantom: PhantomData<&'strct RfcDataContainerHandle>,
 }
 
+impl Default for RfcErrorInfo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl RfcErrorInfo {
     pub fn new() -> RfcErrorInfo {
         RfcErrorInfo {
             code: RfcRc::RfcOk,
             group: RfcErrorGroup::Ok,
-            key: [0 as u16; 128],
-            message: [0 as u16; 512],
-            abap_msg_class: [0 as u16; 21],
-            abap_msg_type: [0 as u16; 2],
-            abap_msg_number: [0 as u16; 4],
-            abap_msg_v1: [0 as u16; 51],
-            abap_msg_v2: [0 as u16; 51],
-            abap_msg_v3: [0 as u16; 51],
-            abap_msg_v4: [0 as u16; 51],
+            key: [0_u16; 128],
+            message: [0_u16; 512],
+            abap_msg_class: [0_u16; 21],
+            abap_msg_type: [0_u16; 2],
+            abap_msg_number: [0_u16; 4],
+            abap_msg_v1: [0_u16; 51],
+            abap_msg_v2: [0_u16; 51],
+            abap_msg_v3: [0_u16; 51],
+            abap_msg_v4: [0_u16; 51],
         }
     }
 
+    /// An error of this crate rather than of the SAP NW RFC library,
+    /// reported as RfcUnknownError
     pub fn custom(msg: &str) -> RfcErrorInfo {
+        RfcErrorInfo::with_code(RfcRc::RfcUnknownError, msg)
+    }
+
+    /// A value that does not fit its parameter, or is of another type
+    pub fn conversion(msg: &str) -> RfcErrorInfo {
+        RfcErrorInfo::with_code(RfcRc::RfcConversionFailure, msg)
+    }
+
+    /// A parameter or field that does not exist or cannot be written
+    pub fn invalid_parameter(msg: &str) -> RfcErrorInfo {
+        RfcErrorInfo::with_code(RfcRc::RfcInvalidParameter, msg)
+    }
+
+    /// An error of this crate with the code of the SAP NW RFC library that
+    /// is closest to it, and the group of that code
+    pub fn with_code(code: RfcRc, msg: &str) -> RfcErrorInfo {
         let mut err_trunk = RfcErrorInfo::new();
         let msg_enc = U16CString::from_str(msg).unwrap();
         let msg_enc = msg_enc.into_vec_with_nul();
@@ -469,12 +573,14 @@ impl RfcErrorInfo {
             std::ptr::copy(msg_enc.as_ptr(), err_trunk.message.as_mut_ptr(), len);
         }
 
-        err_trunk.code = RfcRc::RfcCaiberp;
-        err_trunk.group = RfcErrorGroup::CaiberP;
+        err_trunk.code = code;
+        err_trunk.group = code.group();
         err_trunk
     }
 }
 
+// Every method wraps SDK calls that report errors in an RfcErrorInfo
+#[allow(clippy::result_large_err)]
 impl<'conn, 'strct: 'conn> RfcParameter<'conn, 'strct> {
     pub fn append_rows(&self, count: u32) -> Result<(), RfcErrorInfo> {
         self.field_type.ensure_table()?;
@@ -490,6 +596,87 @@ impl<'conn, 'strct: 'conn> RfcParameter<'conn, 'strct> {
         }
     }
 
+    /// Append a row to the table and return it as a structure to write
+    /// its fields to. The row is the current row afterwards.
+    ///
+    /// Its fields are written through the handle of the new row. The
+    /// fields are decoded for the first row only and reused for the rows
+    /// appended after it.
+    pub fn append_row(&mut self) -> Result<&mut RfcParameter<'conn, 'strct>, RfcErrorInfo> {
+        self.field_type.ensure_table()?;
+        let mut err_trunk = RfcErrorInfo::new();
+        let handle = unsafe {
+            self.rfc_api
+                .RfcAppendNewRow(self.structure_or_table, &mut err_trunk)
+        };
+        if handle.is_null() {
+            return Err(err_trunk);
+        }
+
+        match &mut self.row {
+            Some(row) => row.bind(handle)?,
+            None => {
+                self.row = Some(Box::new(RfcParameter {
+                    index: self.index,
+                    name: self.name.clone(),
+                    field_type: RfcType::Structure,
+                    direction: self.direction,
+                    len: self.len,
+                    decimals: 0,
+                    struct_def: Some(RfcDecodedFieldDesc::from_handle(
+                        self.rfc_api,
+                        handle,
+                        false,
+                    )?),
+                    default_value: None,
+                    optional: false,
+                    fun: self.structure_or_table,
+                    structure_or_table: handle,
+                    row: None,
+                    p1: PhantomData,
+                    p2: PhantomData,
+                    rfc_api: self.rfc_api,
+                }))
+            }
+        }
+        Ok(self.row.as_deref_mut().unwrap())
+    }
+
+    /// Append a row for each item of `rows`, written with `ToTable`. The
+    /// fields are looked up once, which makes this the way to fill large
+    /// tables.
+    pub fn append_rows_from<I>(&mut self, rows: I) -> Result<(), RfcErrorInfo>
+    where
+        I: IntoIterator,
+        I::Item: ToTable,
+    {
+        let mut indices = Vec::new();
+        for (i, item) in rows.into_iter().enumerate() {
+            let row = self.append_row()?;
+            if i == 0 {
+                indices = I::Item::field_indices(row)?;
+            }
+            item.to_row(row, &indices)?;
+        }
+        Ok(())
+    }
+
+    /// Point this structure, and the structures and tables in it, to the
+    /// container `handle` of the same type
+    fn bind(&mut self, handle: *mut RfcDataContainerHandle) -> Result<(), RfcErrorInfo> {
+        self.structure_or_table = handle;
+        if let Some(rpd) = self.struct_def.as_mut() {
+            for field in rpd.parameters.iter_mut() {
+                field.fun = handle;
+                if field.field_type.is_struct_or_table() {
+                    let sub = sub_container(self.rfc_api, field.field_type, handle, field.index)?;
+                    field.bind(sub)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
     pub fn first_row(&self) -> Result<(), RfcErrorInfo> {
         self.field_type.ensure_table()?;
         let mut err_trunk = RfcErrorInfo::new();
@@ -589,38 +776,69 @@ impl<'conn, 'strct: 'conn> RfcParameter<'conn, 'strct> {
             .struct_def
             .as_mut()
             .ok_or(RfcErrorInfo::custom("Logic error at 01D4"))?;
-        let mut i = 0;
-        for field in &rpd.fields {
+        for (i, field) in rpd.fields.iter().enumerate() {
             if field.name.as_str().eq(key) {
-                return Ok(i);
+                return Ok(i as u32);
             }
-            i += 1;
         }
-        Err(RfcErrorInfo::custom("Unknown field"))
+        Err(RfcErrorInfo::invalid_parameter(&format!(
+            "{} has no field {}",
+            self.name, key
+        )))
     }
 
+    /// Whether the structure, or the rows of the table, have a field `key`
+    pub fn has_field(&self, key: &str) -> bool {
+        self.struct_def
+            .as_ref()
+            .is_some_and(|rpd| rpd.fields.iter().any(|field| field.name == key))
+    }
+
+    /// A field of a structure, or of the current row of a table. A
+    /// structure or table nested in a table row is looked up for the
+    /// current row.
     pub fn get_field_by_index(
         &mut self,
         index: u32,
     ) -> Result<&mut RfcParameter<'conn, 'strct>, RfcErrorInfo> {
         self.field_type.ensure_struct_or_table()?;
+        let in_table = self.field_type.is_table();
         let rpd = self
             .struct_def
             .as_mut()
             .ok_or(RfcErrorInfo::custom("Logic error at 01D4"))?;
-        rpd.parameters
+        let field = rpd
+            .parameters
             .get_mut(index as usize)
-            .ok_or(RfcErrorInfo::custom("illegal index"))
+            .ok_or(RfcErrorInfo::invalid_parameter("illegal index"))?;
+        if in_table && field.field_type.is_struct_or_table() {
+            field.attach_row_container()?;
+        }
+        Ok(field)
+    }
+
+    /// Decode the structure or table of a field in the current row of its
+    /// table. Rows may be replaced, e.g. by a call, so this is done on
+    /// every access rather than cached by handle.
+    fn attach_row_container(&mut self) -> Result<(), RfcErrorInfo> {
+        let handle = sub_container(self.rfc_api, self.field_type, self.fun, self.index)?;
+        self.struct_def = Some(RfcDecodedFieldDesc::from_handle(
+            self.rfc_api,
+            handle,
+            self.field_type.is_table(),
+        )?);
+        self.structure_or_table = handle;
+        Ok(())
     }
 
     pub fn set_string(&mut self, value: &str) -> Result<(), RfcErrorInfo> {
         if !self.direction.can_write() {
-            return Err(RfcErrorInfo::custom("Read-only parameter"));
+            return Err(RfcErrorInfo::invalid_parameter("Read-only parameter"));
         }
-        if &self.field_type == &RfcType::String || &self.field_type == &RfcType::Char {
+        if self.field_type == RfcType::String || self.field_type == RfcType::Char {
             let v = U16CString::from_str(value);
             if let Err(e) = v {
-                return Err(RfcErrorInfo::custom(&e.to_string()));
+                return Err(RfcErrorInfo::conversion(&e.to_string()));
             }
             let v = v.unwrap();
             let mut err_trunk = RfcErrorInfo::new();
@@ -639,30 +857,247 @@ impl<'conn, 'strct: 'conn> RfcParameter<'conn, 'strct> {
             }
             Ok(())
         } else {
-            Err(RfcErrorInfo::custom(
+            Err(RfcErrorInfo::conversion(
                 "Not a string datatype, cannot use set_string",
             ))
         }
     }
 
+    /// Set the value from its character representation, which the SDK
+    /// converts to the type of the parameter, e.g. "12.50" for a BCD.
+    pub fn set_chars(&mut self, value: &str) -> Result<(), RfcErrorInfo> {
+        if !self.direction.can_write() {
+            return Err(RfcErrorInfo::invalid_parameter("Read-only parameter"));
+        }
+        let v =
+            U16CString::from_str(value).map_err(|e| RfcErrorInfo::conversion(&e.to_string()))?;
+        let v = v.into_vec();
+        let mut err_trunk = RfcErrorInfo::new();
+        let res = unsafe {
+            self.rfc_api.RfcSetCharsByIndex(
+                self.fun,
+                self.index,
+                v.as_ptr(),
+                v.len() as u32,
+                &mut err_trunk,
+            )
+        };
+        if !res.is_ok() {
+            return Err(err_trunk);
+        }
+        Ok(())
+    }
+
+    /// Set a value of any supported type, see [`ToRfcValue`]
+    pub fn set<T: ToRfcValue>(&mut self, value: T) -> Result<(), RfcErrorInfo> {
+        value.to_rfc(self)
+    }
+
+    /// Read a value of any supported type, see [`FromRfcValue`]
+    pub fn get<T: FromRfcValue>(&self) -> Result<T, RfcErrorInfo> {
+        T::from_rfc(self)
+    }
+
+    /// Set an integer with the setter matching the type of the parameter.
+    /// Values outside the range of INT1, INT2 or INT are an error; other
+    /// types, e.g. NUMC or CHAR, are set with RfcSetInt.
     pub fn set_int(&mut self, value: i64) -> Result<(), RfcErrorInfo> {
         if !self.direction.can_write() {
-            return Err(RfcErrorInfo::custom("Read-only parameter"));
+            return Err(RfcErrorInfo::invalid_parameter("Read-only parameter"));
+        }
+        let mut err_trunk = RfcErrorInfo::new();
+        let res = match self.field_type {
+            RfcType::Int1 => {
+                let value = self.int_in_range::<u8>(value)?;
+                unsafe {
+                    self.rfc_api
+                        .RfcSetInt1ByIndex(self.fun, self.index, value, &mut err_trunk)
+                }
+            }
+            RfcType::Int2 => {
+                let value = self.int_in_range::<i16>(value)?;
+                unsafe {
+                    self.rfc_api
+                        .RfcSetInt2ByIndex(self.fun, self.index, value, &mut err_trunk)
+                }
+            }
+            RfcType::Int8 => unsafe {
+                self.int8_api()?
+                    .RfcSetInt8ByIndex(self.fun, self.index, value, &mut err_trunk)
+            },
+            _ => {
+                let value = self.int_in_range::<i32>(value)?;
+                unsafe {
+                    self.rfc_api
+                        .RfcSetIntByIndex(self.fun, self.index, value, &mut err_trunk)
+                }
+            }
+        };
+        if !res.is_ok() {
+            return Err(err_trunk);
+        }
+        Ok(())
+    }
+
+    /// Read an integer with the getter matching the type of the parameter.
+    /// NUMC values may be longer than the 32 bits of RfcGetInt, they are
+    /// read with get_num.
+    pub fn get_int(&self) -> Result<i64, RfcErrorInfo> {
+        if !self.direction.can_read() {
+            return Err(RfcErrorInfo::invalid_parameter("Read-only parameter"));
+        }
+        if self.field_type == RfcType::Num {
+            let value = self.get_num()?;
+            return i64::try_from(value).map_err(|_| {
+                RfcErrorInfo::conversion(&format!(
+                    "Value {} of parameter {} does not fit into i64",
+                    value, self.name
+                ))
+            });
+        }
+        let mut err_trunk = RfcErrorInfo::new();
+        let (res, value) = match self.field_type {
+            RfcType::Int1 => {
+                let mut v = 0_u8;
+                let res = unsafe {
+                    self.rfc_api
+                        .RfcGetInt1ByIndex(self.fun, self.index, &mut v, &mut err_trunk)
+                };
+                (res, v as i64)
+            }
+            RfcType::Int2 => {
+                let mut v = 0_i16;
+                let res = unsafe {
+                    self.rfc_api
+                        .RfcGetInt2ByIndex(self.fun, self.index, &mut v, &mut err_trunk)
+                };
+                (res, v as i64)
+            }
+            RfcType::Int8 => {
+                let mut v = 0_i64;
+                let res = unsafe {
+                    self.int8_api()?
+                        .RfcGetInt8ByIndex(self.fun, self.index, &mut v, &mut err_trunk)
+                };
+                (res, v)
+            }
+            _ => {
+                let mut v = 0_i32;
+                let res = unsafe {
+                    self.rfc_api
+                        .RfcGetIntByIndex(self.fun, self.index, &mut v, &mut err_trunk)
+                };
+                (res, v as i64)
+            }
+        };
+        if !res.is_ok() {
+            return Err(err_trunk);
         }
+        Ok(value)
+    }
+
+    /// Read a FLOAT, or any packed or integer parameter as a float
+    pub fn get_float(&self) -> Result<f64, RfcErrorInfo> {
+        if !self.direction.can_read() {
+            return Err(RfcErrorInfo::invalid_parameter("Read-only parameter"));
+        }
+        match self.field_type {
+            RfcType::Float
+            | RfcType::Bcd
+            | RfcType::Decf16
+            | RfcType::Decf34
+            | RfcType::Int
+            | RfcType::Int1
+            | RfcType::Int2
+            | RfcType::Int8 => {}
+            _ => {
+                return Err(RfcErrorInfo::conversion(
+                    "Not a numeric datatype, cannot use get_float",
+                ))
+            }
+        }
+        let mut value = 0.0;
         let mut err_trunk = RfcErrorInfo::new();
         let res = unsafe {
             self.rfc_api
-                .RfcSetIntByIndex(self.fun, self.index, value, &mut err_trunk)
+                .RfcGetFloatByIndex(self.fun, self.index, &mut value, &mut err_trunk)
         };
         if !res.is_ok() {
             return Err(err_trunk);
         }
-        Ok(())
+        Ok(value)
+    }
+
+    /// Set a NUMC, padded with leading zeros to the length of the field
+    pub fn set_num(&mut self, value: u64) -> Result<(), RfcErrorInfo> {
+        if self.field_type != RfcType::Num {
+            return Err(RfcErrorInfo::conversion(
+                "Not a NUMC datatype, cannot use set_num",
+            ));
+        }
+        // The length is in bytes, two per character
+        let len = self.len as usize / 2;
+        let digits = value.to_string();
+        if digits.len() > len {
+            return Err(RfcErrorInfo::conversion(&format!(
+                "{} has more digits than parameter {} of type NUMC {}",
+                value, self.name, len
+            )));
+        }
+        self.set_chars(&format!("{:0>len$}", digits))
+    }
+
+    /// Read a NUMC as a number. Fails if the field holds other characters
+    /// than digits or a number too large for u64.
+    pub fn get_num(&self) -> Result<u64, RfcErrorInfo> {
+        if self.field_type != RfcType::Num {
+            return Err(RfcErrorInfo::conversion(
+                "Not a NUMC datatype, cannot use get_num",
+            ));
+        }
+        let chars = self.get_chars()?;
+        let digits = chars.trim();
+        if digits.is_empty() {
+            return Ok(0);
+        }
+        if !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(RfcErrorInfo::conversion(&format!(
+                "Value '{}' of NUMC parameter {} contains other characters than digits",
+                chars, self.name
+            )));
+        }
+        digits.parse().map_err(|_| {
+            RfcErrorInfo::conversion(&format!(
+                "Value {} of parameter {} does not fit into u64",
+                digits, self.name
+            ))
+        })
+    }
+
+    fn int_in_range<T: TryFrom<i64>>(&self, value: i64) -> Result<T, RfcErrorInfo> {
+        T::try_from(value).map_err(|_| {
+            RfcErrorInfo::conversion(&format!(
+                "{} is out of range for parameter {} of type {:?}",
+                value, self.name, self.field_type
+            ))
+        })
+    }
+
+    fn int8_api(&self) -> Result<&RfcInt8Api, RfcErrorInfo> {
+        self.rfc_api.int8.as_ref().ok_or_else(|| {
+            RfcErrorInfo::with_code(
+                RfcRc::RfcNotSupported,
+                &format!(
+                    "Parameter {} is an INT8, which needs SDK 7.50 or later",
+                    self.name
+                ),
+            )
+        })
     }
 
     pub fn set_float(&mut self, value: f64) -> Result<(), RfcErrorInfo> {
         if !self.direction.can_write() {
-            return Err(RfcErrorInfo::custom("Read-only parameter"));
+            return Err(RfcErrorInfo::invalid_parameter("Read-only parameter"));
         }
         let mut err_trunk = RfcErrorInfo::new();
         let res = unsafe {
@@ -677,12 +1112,12 @@ impl<'conn, 'strct: 'conn> RfcParameter<'conn, 'strct> {
 
     pub fn set_date(&mut self, value: &str) -> Result<(), RfcErrorInfo> {
         if !self.direction.can_write() {
-            return Err(RfcErrorInfo::custom("Read-only parameter"));
+            return Err(RfcErrorInfo::invalid_parameter("Read-only parameter"));
         }
-        if &self.field_type == &RfcType::Date {
+        if self.field_type == RfcType::Date {
             let v = U16CString::from_str(value);
             if let Err(e) = v {
-                return Err(RfcErrorInfo::custom(&e.to_string()));
+                return Err(RfcErrorInfo::conversion(&e.to_string()));
             }
             let v = v.unwrap();
             let mut err_trunk = RfcErrorInfo::new();
@@ -696,30 +1131,138 @@ impl<'conn, 'strct: 'conn> RfcParameter<'conn, 'strct> {
             }
             Ok(())
         } else {
-            Err(RfcErrorInfo::custom(
+            Err(RfcErrorInfo::conversion(
                 "Not a date datatype, cannot use set_date",
             ))
         }
     }
 
+    pub fn set_time(&mut self, value: &str) -> Result<(), RfcErrorInfo> {
+        if !self.direction.can_write() {
+            return Err(RfcErrorInfo::invalid_parameter("Read-only parameter"));
+        }
+        if self.field_type == RfcType::Time {
+            let v = U16CString::from_str(value)
+                .map_err(|e| RfcErrorInfo::conversion(&e.to_string()))?
+                .into_vec_with_nul();
+            let mut err_trunk = RfcErrorInfo::new();
+            let res = unsafe {
+                self.rfc_api
+                    .RfcSetTimeByIndex(self.fun, self.index, v.as_ptr(), &mut err_trunk)
+            };
+            if !res.is_ok() {
+                return Err(err_trunk);
+            }
+            Ok(())
+        } else {
+            Err(RfcErrorInfo::conversion(
+                "Not a time datatype, cannot use set_time",
+            ))
+        }
+    }
+
+    /// Read a DATS value. The initial date 00000000 is None.
+    pub fn get_date(&self) -> Result<Option<NaiveDate>, RfcErrorInfo> {
+        if !self.direction.can_read() {
+            return Err(RfcErrorInfo::invalid_parameter("Read-only parameter"));
+        }
+        if self.field_type != RfcType::Date {
+            return Err(RfcErrorInfo::conversion(
+                "Not a date datatype, cannot use get_date",
+            ));
+        }
+        // RFC_DATE has no terminating zero
+        let mut buf = [0_u16; 8];
+        let mut err_trunk = RfcErrorInfo::new();
+        let res = unsafe {
+            self.rfc_api
+                .RfcGetDateByIndex(self.fun, self.index, buf.as_mut_ptr(), &mut err_trunk)
+        };
+        if !res.is_ok() {
+            return Err(err_trunk);
+        }
+        datetime::parse_date(&String::from_utf16_lossy(&buf), &self.name)
+    }
+
+    /// Read a TIMS value. The initial time 000000 is None, although it is
+    /// also midnight.
+    pub fn get_time(&self) -> Result<Option<NaiveTime>, RfcErrorInfo> {
+        if !self.direction.can_read() {
+            return Err(RfcErrorInfo::invalid_parameter("Read-only parameter"));
+        }
+        if self.field_type != RfcType::Time {
+            return Err(RfcErrorInfo::conversion(
+                "Not a time datatype, cannot use get_time",
+            ));
+        }
+        let mut buf = [0_u16; 6];
+        let mut err_trunk = RfcErrorInfo::new();
+        let res = unsafe {
+            self.rfc_api
+                .RfcGetTimeByIndex(self.fun, self.index, buf.as_mut_ptr(), &mut err_trunk)
+        };
+        if !res.is_ok() {
+            return Err(err_trunk);
+        }
+        datetime::parse_time(&String::from_utf16_lossy(&buf), &self.name)
+    }
+
+    /// Whether the parameter is a UTCLONG or a packed TIMESTAMP (DEC 15)
+    /// or TIMESTAMPL (DEC 21,7)
+    pub fn is_timestamp(&self) -> bool {
+        datetime::is_timestamp(self.field_type, self.len, self.decimals)
+    }
+
+    /// Set a UTCLONG, TIMESTAMP or TIMESTAMPL. A TIMESTAMP drops the
+    /// fractions of a second, the others keep 100 nanoseconds.
+    pub fn set_timestamp(&mut self, value: DateTime<Utc>) -> Result<(), RfcErrorInfo> {
+        if !self.is_timestamp() {
+            return Err(RfcErrorInfo::conversion(
+                "Not a timestamp datatype, cannot use set_timestamp",
+            ));
+        }
+        self.set_chars(&datetime::format_timestamp(
+            &value,
+            self.field_type,
+            self.decimals,
+        ))
+    }
+
+    /// Read a UTCLONG, TIMESTAMP or TIMESTAMPL. The initial value is None.
+    pub fn get_timestamp(&self) -> Result<Option<DateTime<Utc>>, RfcErrorInfo> {
+        if !self.is_timestamp() {
+            return Err(RfcErrorInfo::conversion(
+                "Not a timestamp datatype, cannot use get_timestamp",
+            ));
+        }
+        datetime::parse_timestamp(&self.get_string()?, &self.name)
+    }
+
     pub fn get_chars(&self) -> Result<String, RfcErrorInfo> {
         if !self.direction.can_read() {
-            return Err(RfcErrorInfo::custom("Read-only parameter"));
+            return Err(RfcErrorInfo::invalid_parameter("Read-only parameter"));
+        }
+        // A STRING has no fixed length to size the buffer with
+        if self.field_type == RfcType::String {
+            return self.get_string();
         }
 
         /*
         if &self.field_type != &RfcType::String && &self.field_type != &RfcType::XString {
-            return Err(RfcErrorInfo::custom(
+            return Err(RfcErrorInfo::conversion(
                 "Not of type STRING or XSTRING; cannot use get_string",
             ));
         }
         */
 
         let mut err_trunk = RfcErrorInfo::new();
-        let mut buf = Vec::new();
-        let mut reserve_len = self.len;
-        reserve_len += 1;
-        buf.reserve_exact(reserve_len as usize * 2);
+        // The SDK pads the buffer with blanks, so fields of fixed length
+        // get a buffer of their length: two bytes of `len` per character
+        let reserve_len = match self.field_type {
+            RfcType::Char | RfcType::Num | RfcType::Date | RfcType::Time => self.len / 2,
+            _ => self.len + 1,
+        };
+        let mut buf = vec![0_u16; reserve_len as usize];
         {
             let res = unsafe {
                 self.rfc_api.RfcGetCharsByIndex(
@@ -734,33 +1277,24 @@ impl<'conn, 'strct: 'conn> RfcParameter<'conn, 'strct> {
                 return Err(err_trunk);
             }
         }
-        let s = unsafe { U16CString::from_ptr(buf.as_ptr() as *const u16, reserve_len as usize) };
-        if let Err(e) = s {
-            return Err(RfcErrorInfo::custom(&e.to_string()));
-        }
-        let s = s.unwrap().to_string();
-        if let Err(e) = s {
-            return Err(RfcErrorInfo::custom(&e.to_string()));
-        }
-        let s = s.unwrap();
-        Ok(s)
+        let len = buf.iter().position(|c| *c == 0).unwrap_or(buf.len());
+        String::from_utf16(&buf[..len]).map_err(|e| RfcErrorInfo::conversion(&e.to_string()))
     }
 
     pub fn get_string(&self) -> Result<String, RfcErrorInfo> {
         if !self.direction.can_read() {
-            return Err(RfcErrorInfo::custom("Read-only parameter"));
+            return Err(RfcErrorInfo::invalid_parameter("Read-only parameter"));
         }
 
         /*
         if &self.field_type != &RfcType::String && &self.field_type != &RfcType::XString {
-            return Err(RfcErrorInfo::custom(
+            return Err(RfcErrorInfo::conversion(
                 "Not of type STRING or XSTRING; cannot use get_string",
             ));
         }
         */
 
         let mut err_trunk = RfcErrorInfo::new();
-        let mut buf = Vec::new();
         let mut reserve_len = 0;
         {
             let res = unsafe {
@@ -778,7 +1312,7 @@ impl<'conn, 'strct: 'conn> RfcParameter<'conn, 'strct> {
         // This is an ungenau wissenschaft; utterly undocumented how to properly
         // use this API.
         reserve_len += 1;
-        buf.reserve_exact(reserve_len as usize * 2);
+        let mut buf = vec![0_u16; reserve_len as usize];
         let mut len = 0;
         {
             let res = unsafe {
@@ -795,24 +1329,16 @@ impl<'conn, 'strct: 'conn> RfcParameter<'conn, 'strct> {
                 return Err(err_trunk);
             }
         }
-        let s = unsafe { U16CString::from_ptr(buf.as_ptr() as *const u16, len as usize) };
-        if let Err(e) = s {
-            return Err(RfcErrorInfo::custom(&e.to_string()));
-        }
-        let s = s.unwrap().to_string();
-        if let Err(e) = s {
-            return Err(RfcErrorInfo::custom(&e.to_string()));
-        }
-        let s = s.unwrap();
-        Ok(s)
+        let len = min(len, reserve_len) as usize;
+        String::from_utf16(&buf[..len]).map_err(|e| RfcErrorInfo::conversion(&e.to_string()))
     }
 
     pub fn set_xstring(&mut self, v: &[u8]) -> Result<(), RfcErrorInfo> {
         if !self.direction.can_write() {
-            return Err(RfcErrorInfo::custom("Read-only parameter"));
+            return Err(RfcErrorInfo::invalid_parameter("Read-only parameter"));
         }
-        if &self.field_type != &RfcType::XString {
-            return Err(RfcErrorInfo::custom(
+        if self.field_type != RfcType::XString {
+            return Err(RfcErrorInfo::conversion(
                 "Not of type XSTRING; cannot use get_string",
             ));
         }
@@ -834,12 +1360,186 @@ impl<'conn, 'strct: 'conn> RfcParameter<'conn, 'strct> {
         }
     }
 
+    /// Set a BCD or DECFLOAT value without going through f64. A BCD value
+    /// must fit into the length and decimals of the field, extra digits
+    /// are an error rather than rounded away.
+    #[cfg(feature = "rust_decimal")]
+    pub fn set_decimal(&mut self, value: rust_decimal::Decimal) -> Result<(), RfcErrorInfo> {
+        if !self.direction.can_write() {
+            return Err(RfcErrorInfo::invalid_parameter("Read-only parameter"));
+        }
+        let mut err_trunk = RfcErrorInfo::new();
+        let res = match self.field_type {
+            RfcType::Bcd => return self.set_chars(&self.bcd_chars(value)?),
+            RfcType::Decf16 => {
+                let bits = decfloat::DECFLOAT16.encode_decimal(value, &self.name)?;
+                let v = RfcDecF16 {
+                    bytes: (bits as u64).to_ne_bytes(),
+                };
+                unsafe {
+                    self.rfc_api
+                        .RfcSetDecF16ByIndex(self.fun, self.index, v, &mut err_trunk)
+                }
+            }
+            RfcType::Decf34 => {
+                let bits = decfloat::DECFLOAT34.encode_decimal(value, &self.name)?;
+                let v = RfcDecF34 {
+                    bytes: bits.to_ne_bytes(),
+                };
+                unsafe {
+                    self.rfc_api
+                        .RfcSetDecF34ByIndex(self.fun, self.index, v, &mut err_trunk)
+                }
+            }
+            _ => {
+                return Err(RfcErrorInfo::conversion(
+                    "Not a decimal datatype, cannot use set_decimal",
+                ))
+            }
+        };
+        if res.is_ok() {
+            Ok(())
+        } else {
+            Err(err_trunk)
+        }
+    }
+
+    /// Format a value for a BCD field, with exactly as many decimals as
+    /// the field has
+    #[cfg(feature = "rust_decimal")]
+    fn bcd_chars(&self, value: rust_decimal::Decimal) -> Result<String, RfcErrorInfo> {
+        // Two digits per byte, except for the last one, which holds the sign
+        let digits = 2 * self.len - 1;
+        let value = value.normalize();
+        if value.scale() > self.decimals {
+            return Err(RfcErrorInfo::conversion(&format!(
+                "{} has more than {} decimals, the maximum of parameter {}",
+                value, self.decimals, self.name
+            )));
+        }
+        let int_digits = value
+            .trunc()
+            .abs()
+            .to_string()
+            .trim_start_matches('0')
+            .len() as u32;
+        if int_digits > digits - self.decimals {
+            return Err(RfcErrorInfo::conversion(&format!(
+                "{} overflows parameter {} (P {} DECIMALS {})",
+                value, self.name, digits, self.decimals
+            )));
+        }
+        Ok(format!("{:.*}", self.decimals as usize, value))
+    }
+
+    /// Read a BCD or DECFLOAT value without going through f64
+    #[cfg(feature = "rust_decimal")]
+    pub fn get_decimal(&self) -> Result<rust_decimal::Decimal, RfcErrorInfo> {
+        if !self.direction.can_read() {
+            return Err(RfcErrorInfo::invalid_parameter("Read-only parameter"));
+        }
+        let mut err_trunk = RfcErrorInfo::new();
+        match self.field_type {
+            RfcType::Bcd => {
+                let s = self.get_string()?;
+                s.trim().parse().map_err(|_| {
+                    RfcErrorInfo::conversion(&format!(
+                        "Value '{}' of parameter {} is not a decimal number",
+                        s, self.name
+                    ))
+                })
+            }
+            RfcType::Decf16 => {
+                let mut v = RfcDecF16 { bytes: [0; 8] };
+                let res = unsafe {
+                    self.rfc_api
+                        .RfcGetDecF16ByIndex(self.fun, self.index, &mut v, &mut err_trunk)
+                };
+                if !res.is_ok() {
+                    return Err(err_trunk);
+                }
+                let bits = u64::from_ne_bytes(unsafe { v.bytes });
+                decfloat::DECFLOAT16.decode_decimal(bits as u128, &self.name)
+            }
+            RfcType::Decf34 => {
+                let mut v = RfcDecF34 { bytes: [0; 16] };
+                let res = unsafe {
+                    self.rfc_api
+                        .RfcGetDecF34ByIndex(self.fun, self.index, &mut v, &mut err_trunk)
+                };
+                if !res.is_ok() {
+                    return Err(err_trunk);
+                }
+                let bits = u128::from_ne_bytes(unsafe { v.bytes });
+                decfloat::DECFLOAT34.decode_decimal(bits, &self.name)
+            }
+            _ => Err(RfcErrorInfo::conversion(
+                "Not a decimal datatype, cannot use get_decimal",
+            )),
+        }
+    }
+
+    pub fn set_bytes(&mut self, v: &[u8]) -> Result<(), RfcErrorInfo> {
+        if !self.direction.can_write() {
+            return Err(RfcErrorInfo::invalid_parameter("Read-only parameter"));
+        }
+        if self.field_type != RfcType::Byte {
+            return Err(RfcErrorInfo::conversion(
+                "Not of type RAW; cannot use set_bytes",
+            ));
+        }
+
+        let mut err_trunk = RfcErrorInfo::new();
+        let res = unsafe {
+            self.rfc_api.RfcSetBytesByIndex(
+                self.fun,
+                self.index,
+                v.as_ptr(),
+                v.len() as u32,
+                &mut err_trunk,
+            )
+        };
+        if res.is_ok() {
+            Ok(())
+        } else {
+            Err(err_trunk)
+        }
+    }
+
+    /// Read a RAW value. It is always as long as the field.
+    pub fn get_bytes(&self) -> Result<Vec<u8>, RfcErrorInfo> {
+        if !self.direction.can_read() {
+            return Err(RfcErrorInfo::invalid_parameter("Read-only parameter"));
+        }
+        if self.field_type != RfcType::Byte {
+            return Err(RfcErrorInfo::conversion(
+                "Not of type RAW; cannot use get_bytes",
+            ));
+        }
+        let mut err_trunk = RfcErrorInfo::new();
+        let mut out_buf = vec![0_u8; self.len as usize];
+        let res = unsafe {
+            self.rfc_api.RfcGetBytesByIndex(
+                self.fun,
+                self.index,
+                out_buf.as_mut_ptr(),
+                self.len,
+                &mut err_trunk,
+            )
+        };
+        if res.is_ok() {
+            Ok(out_buf)
+        } else {
+            Err(err_trunk)
+        }
+    }
+
     pub fn get_xstring(&self) -> Result<Vec<u8>, RfcErrorInfo> {
         if !self.direction.can_read() {
-            return Err(RfcErrorInfo::custom("Read-only parameter"));
+            return Err(RfcErrorInfo::invalid_parameter("Read-only parameter"));
         }
-        if &self.field_type != &RfcType::XString {
-            return Err(RfcErrorInfo::custom(
+        if self.field_type != RfcType::XString {
+            return Err(RfcErrorInfo::conversion(
                 "Not of type XSTRING; cannot use get_string",
             ));
         }
@@ -882,8 +1582,15 @@ impl<'conn, 'strct: 'conn> RfcParameter<'conn, 'strct> {
     }
 }
 
+/// The functions every supported SDK exports
 #[derive(WrapperApi)]
-pub struct RfcApi {
+pub struct RfcCoreApi {
+    RfcGetVersion: unsafe extern "C" fn(
+        major_version: *mut u32,
+        minor_version: *mut u32,
+        patch_level: *mut u32,
+    ) -> *const u16,
+
     RfcOpenConnection: unsafe extern "C" fn(
         parameters: *const RfcConnectionParameter,
         param_count: u32,
@@ -920,7 +1627,42 @@ pub struct RfcApi {
     RfcSetIntByIndex: unsafe extern "C" fn(
         handle: *mut RfcDataContainerHandle,
         index: u32,
-        value: i64,
+        value: i32,
+        error: *mut RfcErrorInfo,
+    ) -> RfcRc,
+
+    RfcSetInt1ByIndex: unsafe extern "C" fn(
+        handle: *mut RfcDataContainerHandle,
+        index: u32,
+        value: u8,
+        error: *mut RfcErrorInfo,
+    ) -> RfcRc,
+
+    RfcSetInt2ByIndex: unsafe extern "C" fn(
+        handle: *mut RfcDataContainerHandle,
+        index: u32,
+        value: i16,
+        error: *mut RfcErrorInfo,
+    ) -> RfcRc,
+
+    RfcGetIntByIndex: unsafe extern "C" fn(
+        handle: *mut RfcDataContainerHandle,
+        index: u32,
+        value: *mut i32,
+        error: *mut RfcErrorInfo,
+    ) -> RfcRc,
+
+    RfcGetInt1ByIndex: unsafe extern "C" fn(
+        handle: *mut RfcDataContainerHandle,
+        index: u32,
+        value: *mut u8,
+        error: *mut RfcErrorInfo,
+    ) -> RfcRc,
+
+    RfcGetInt2ByIndex: unsafe extern "C" fn(
+        handle: *mut RfcDataContainerHandle,
+        index: u32,
+        value: *mut i16,
         error: *mut RfcErrorInfo,
     ) -> RfcRc,
 
@@ -931,6 +1673,13 @@ pub struct RfcApi {
         error: *mut RfcErrorInfo,
     ) -> RfcRc,
 
+    RfcGetFloatByIndex: unsafe extern "C" fn(
+        handle: *mut RfcDataContainerHandle,
+        index: u32,
+        value: *mut f64,
+        error: *mut RfcErrorInfo,
+    ) -> RfcRc,
+
     RfcSetDateByIndex: unsafe extern "C" fn(
         handle: *mut RfcDataContainerHandle,
         index: u32,
@@ -938,6 +1687,27 @@ pub struct RfcApi {
         error: *mut RfcErrorInfo,
     ) -> RfcRc,
 
+    RfcGetDateByIndex: unsafe extern "C" fn(
+        handle: *mut RfcDataContainerHandle,
+        index: u32,
+        value: *mut u16,
+        error: *mut RfcErrorInfo,
+    ) -> RfcRc,
+
+    RfcSetTimeByIndex: unsafe extern "C" fn(
+        handle: *mut RfcDataContainerHandle,
+        index: u32,
+        value: *const u16,
+        error: *mut RfcErrorInfo,
+    ) -> RfcRc,
+
+    RfcGetTimeByIndex: unsafe extern "C" fn(
+        handle: *mut RfcDataContainerHandle,
+        index: u32,
+        value: *mut u16,
+        error: *mut RfcErrorInfo,
+    ) -> RfcRc,
+
     RfcInvoke: unsafe extern "C" fn(
         handle: *mut RfcConnectionHandle,
         fun: *mut RfcDataContainerHandle,
@@ -958,10 +1728,54 @@ pub struct RfcApi {
         error: *mut RfcErrorInfo,
     ) -> RfcRc,
 
+    RfcGetBytesByIndex: unsafe extern "C" fn(
+        fun: *const RfcDataContainerHandle,
+        index: u32,
+        value: *mut u8,
+        buflen: u32,
+        error: *mut RfcErrorInfo,
+    ) -> RfcRc,
+
+    RfcSetBytesByIndex: unsafe extern "C" fn(
+        fun: *const RfcDataContainerHandle,
+        index: u32,
+        value: *const u8,
+        len: u32,
+        error: *mut RfcErrorInfo,
+    ) -> RfcRc,
+
+    RfcGetDecF16ByIndex: unsafe extern "C" fn(
+        fun: *const RfcDataContainerHandle,
+        index: u32,
+        value: *mut RfcDecF16,
+        error: *mut RfcErrorInfo,
+    ) -> RfcRc,
+
+    RfcSetDecF16ByIndex: unsafe extern "C" fn(
+        fun: *const RfcDataContainerHandle,
+        index: u32,
+        value: RfcDecF16,
+        error: *mut RfcErrorInfo,
+    ) -> RfcRc,
+
+    RfcGetDecF34ByIndex: unsafe extern "C" fn(
+        fun: *const RfcDataContainerHandle,
+        index: u32,
+        value: *mut RfcDecF34,
+        error: *mut RfcErrorInfo,
+    ) -> RfcRc,
+
+    RfcSetDecF34ByIndex: unsafe extern "C" fn(
+        fun: *const RfcDataContainerHandle,
+        index: u32,
+        value: RfcDecF34,
+        error: *mut RfcErrorInfo,
+    ) -> RfcRc,
+
     RfcGetStringByIndex: unsafe extern "C" fn(
         fun: *const RfcDataContainerHandle,
         index: u32,
-        buf: *mut u8,
+        buf: *mut u16,
         len: u32,
         out_len: *mut u32,
         error: *mut RfcErrorInfo,
@@ -994,10 +1808,10 @@ pub struct RfcApi {
     RfcDescribeType: unsafe extern "C" fn(
         fun: *const RfcDataContainerHandle,
         error: *mut RfcErrorInfo,
-    ) -> *mut RfcDataContainerHandle,
+    ) -> *mut RfcTypeDescHandle,
 
     RfcGetFieldCount: unsafe extern "C" fn(
-        tdh: *const RfcDataContainerHandle,
+        tdh: *const RfcTypeDescHandle,
         count: *mut u32,
         error: *mut RfcErrorInfo,
     ) -> RfcRc,
@@ -1009,7 +1823,7 @@ pub struct RfcApi {
     ) -> RfcRc,
 
     RfcGetFieldDescByIndex: unsafe extern "C" fn(
-        tdh: *const RfcDataContainerHandle,
+        tdh: *const RfcTypeDescHandle,
         index: u32,
         field_desc: *mut RfcFieldDesc,
         error: *mut RfcErrorInfo,
@@ -1065,6 +1879,52 @@ pub struct RfcApi {
         error: *mut RfcErrorInfo,
     ) -> RfcRc,
 
+    RfcAppendNewRow: unsafe extern "C" fn(
+        handle: *mut RfcDataContainerHandle,
+        error: *mut RfcErrorInfo,
+    ) -> *mut RfcDataContainerHandle,
+
     RfcCloseConnection:
         unsafe extern "C" fn(handle: *mut RfcConnectionHandle, error: *mut RfcErrorInfo) -> RfcRc,
+
+    RfcPing:
+        unsafe extern "C" fn(handle: *mut RfcConnectionHandle, error: *mut RfcErrorInfo) -> RfcRc,
+
+    RfcCancel:
+        unsafe extern "C" fn(handle: *mut RfcConnectionHandle, error: *mut RfcErrorInfo) -> RfcRc,
+}
+
+/// INT8 accessors, added with SDK 7.50
+#[derive(WrapperApi)]
+pub struct RfcInt8Api {
+    RfcGetInt8ByIndex: unsafe extern "C" fn(
+        handle: *mut RfcDataContainerHandle,
+        index: u32,
+        value: *mut i64,
+        error: *mut RfcErrorInfo,
+    ) -> RfcRc,
+
+    RfcSetInt8ByIndex: unsafe extern "C" fn(
+        handle: *mut RfcDataContainerHandle,
+        index: u32,
+        value: i64,
+        error: *mut RfcErrorInfo,
+    ) -> RfcRc,
+}
+
+/// The complete binding. Groups of functions that older SDKs lack are
+/// optional, so that loading such an SDK still succeeds; using them then
+/// fails with RfcNotSupported.
+#[derive(WrapperMultiApi)]
+pub struct RfcApi {
+    pub core: RfcCoreApi,
+    pub int8: Option<RfcInt8Api>,
+}
+
+impl Deref for RfcApi {
+    type Target = RfcCoreApi;
+
+    fn deref(&self) -> &RfcCoreApi {
+        &self.core
+    }
 }
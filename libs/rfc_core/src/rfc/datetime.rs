@@ -0,0 +1,110 @@
+//! The character representations of SAP dates, times and timestamps.
+//!
+//! DATS and TIMS are YYYYMMDD and HHMMSS, with the initial values 00000000
+//! and 000000. TIMESTAMP is a DEC 15 of the form YYYYMMDDhhmmss in UTC and
+//! TIMESTAMPL a DEC 21,7 with fractions of a second. UTCLONG values read
+//! as YYYY-MM-DDThh:mm:ss.fffffff.
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc};
+
+use super::RfcType;
+use crate::error::*;
+
+pub(crate) const DATE_FORMAT: &str = "%Y%m%d";
+pub(crate) const TIME_FORMAT: &str = "%H%M%S";
+const TIMESTAMP_FORMAT: &str = "%Y%m%d%H%M%S";
+const UTCLONG_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+fn parse_error(value: &str, name: &str, kind: &str) -> RfcErrorInfo {
+    RfcErrorInfo::conversion(&format!(
+        "Value '{}' of parameter {} is not a valid {}",
+        value, name, kind
+    ))
+}
+
+/// Zeros and blanks, optionally with separators, e.g. 00000000, 0.0000000
+/// or an empty UTCLONG
+fn is_initial(value: &str) -> bool {
+    value
+        .chars()
+        .all(|c| matches!(c, '0' | ' ' | '-' | ':' | '.' | ',' | 'T'))
+}
+
+/// Whether a parameter of this type and size holds a timestamp: UTCLONG,
+/// TIMESTAMP (8 bytes, no decimals) or TIMESTAMPL (11 bytes, 7 decimals)
+pub(crate) fn is_timestamp(field_type: RfcType, len: u32, decimals: u32) -> bool {
+    match field_type {
+        RfcType::UtcLong => true,
+        RfcType::Bcd => matches!((len, decimals), (8, 0) | (11, 7)),
+        _ => false,
+    }
+}
+
+// Fails like RfcGetDate, with an RfcErrorInfo
+#[allow(clippy::result_large_err)]
+pub(crate) fn parse_date(value: &str, name: &str) -> Result<Option<NaiveDate>, RfcErrorInfo> {
+    let value = value.trim();
+    if is_initial(value) {
+        return Ok(None);
+    }
+    NaiveDate::parse_from_str(value, DATE_FORMAT)
+        .map(Some)
+        .map_err(|_| parse_error(value, name, "date"))
+}
+
+// Fails like RfcGetTime, with an RfcErrorInfo
+#[allow(clippy::result_large_err)]
+pub(crate) fn parse_time(value: &str, name: &str) -> Result<Option<NaiveTime>, RfcErrorInfo> {
+    let value = value.trim();
+    if is_initial(value) {
+        return Ok(None);
+    }
+    NaiveTime::parse_from_str(value, TIME_FORMAT)
+        .map(Some)
+        .map_err(|_| parse_error(value, name, "time"))
+}
+
+/// Parse a packed timestamp or a UTCLONG, both in UTC
+// Fails like the RfcGet* call that read the value, with an RfcErrorInfo
+#[allow(clippy::result_large_err)]
+pub(crate) fn parse_timestamp(
+    value: &str,
+    name: &str,
+) -> Result<Option<DateTime<Utc>>, RfcErrorInfo> {
+    let value = value.trim();
+    if is_initial(value) {
+        return Ok(None);
+    }
+    let invalid = || parse_error(value, name, "timestamp");
+    let (seconds, fraction) = value.split_once(['.', ',']).unwrap_or((value, ""));
+    let datetime = if seconds.contains('T') {
+        NaiveDateTime::parse_from_str(seconds, UTCLONG_FORMAT)
+    } else {
+        // The packed number loses the leading zeros of years before 1000
+        NaiveDateTime::parse_from_str(&format!("{:0>14}", seconds), TIMESTAMP_FORMAT)
+    }
+    .map_err(|_| invalid())?;
+    if fraction.len() > 9 || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let nanos = format!("{:0<9}", fraction).parse::<u32>().unwrap();
+    datetime
+        .with_nanosecond(nanos)
+        .map(|v| Some(v.and_utc()))
+        .ok_or_else(invalid)
+}
+
+/// Format a timestamp for a parameter; 7 digits of fractions for a
+/// TIMESTAMPL or UTCLONG, whole seconds for a TIMESTAMP
+pub(crate) fn format_timestamp(
+    value: &DateTime<Utc>,
+    field_type: RfcType,
+    decimals: u32,
+) -> String {
+    // A leap second is represented as more than 10^9 nanoseconds
+    let fraction = value.nanosecond().min(999_999_999) / 100;
+    match (field_type, decimals) {
+        (RfcType::UtcLong, _) => format!("{}.{:07}", value.format(UTCLONG_FORMAT), fraction),
+        (_, 0) => value.format(TIMESTAMP_FORMAT).to_string(),
+        _ => format!("{}.{:07}", value.format(TIMESTAMP_FORMAT), fraction),
+    }
+}
@@ -0,0 +1,196 @@
+//! Conversion between rust_decimal and the decimal floating point types of
+//! the SDK. RFC_DECF16 and RFC_DECF34 are IEEE 754 decimal64 and
+//! decimal128 values in densely packed decimal (DPD) encoding, stored in
+//! native byte order.
+//!
+//! Public, but hidden from the documentation, so that the tests can check
+//! the encoding against known bit patterns.
+use std::sync::OnceLock;
+
+use rust_decimal::Decimal;
+
+use crate::error::*;
+
+pub struct DecFloatFormat {
+    name: &'static str,
+    /// Number of declets, each holding three digits of the coefficient
+    declets: u32,
+    /// Bits of the exponent continuation field
+    exponent_bits: u32,
+    bias: i32,
+}
+
+pub const DECFLOAT16: DecFloatFormat = DecFloatFormat {
+    name: "DECFLOAT16",
+    declets: 5,
+    exponent_bits: 8,
+    bias: 398,
+};
+
+pub const DECFLOAT34: DecFloatFormat = DecFloatFormat {
+    name: "DECFLOAT34",
+    declets: 11,
+    exponent_bits: 12,
+    bias: 6176,
+};
+
+// Fails like RfcGetDecF16/RfcGetDecF34, with an RfcErrorInfo
+#[allow(clippy::result_large_err)]
+impl DecFloatFormat {
+    fn digits(&self) -> u32 {
+        3 * self.declets + 1
+    }
+
+    fn width(&self) -> u32 {
+        6 + self.exponent_bits + 10 * self.declets
+    }
+
+    /// Encode sign, coefficient and exponent. None if they are out of the
+    /// range of the format.
+    pub fn encode(&self, negative: bool, coefficient: u128, exponent: i32) -> Option<u128> {
+        let biased = exponent + self.bias;
+        if coefficient >= 10_u128.pow(self.digits())
+            || biased < 0
+            || biased >= 3 << self.exponent_bits
+        {
+            return None;
+        }
+        let biased = biased as u128;
+        let trailing = 10_u128.pow(3 * self.declets);
+        let leading = coefficient / trailing;
+        let mut rest = coefficient % trailing;
+
+        let mut bits = 0_u128;
+        for i in 0..self.declets {
+            bits |= (encode_declet((rest % 1000) as u32) as u128) << (10 * i);
+            rest /= 1000;
+        }
+        let coefficient_bits = 10 * self.declets;
+        bits |= (biased & ((1 << self.exponent_bits) - 1)) << coefficient_bits;
+
+        let exponent_msb = biased >> self.exponent_bits;
+        let combination = if leading < 8 {
+            (exponent_msb << 3) | leading
+        } else {
+            0b11000 | (exponent_msb << 1) | (leading - 8)
+        };
+        bits |= combination << (coefficient_bits + self.exponent_bits);
+        if negative {
+            bits |= 1 << (self.width() - 1);
+        }
+        Some(bits)
+    }
+
+    /// Decode into sign, coefficient and exponent. Fails for infinities
+    /// and NaNs.
+    pub fn decode(&self, bits: u128) -> Option<(bool, u128, i32)> {
+        let coefficient_bits = 10 * self.declets;
+        let negative = bits >> (self.width() - 1) & 1 == 1;
+        let combination = (bits >> (coefficient_bits + self.exponent_bits)) & 0b11111;
+        let (exponent_msb, leading) = match combination >> 3 {
+            0b11 if combination >> 1 & 0b11 == 0b11 => return None,
+            0b11 => (combination >> 1 & 0b11, 8 + (combination & 1)),
+            msb => (msb, combination & 0b111),
+        };
+        let continuation = (bits >> coefficient_bits) & ((1 << self.exponent_bits) - 1);
+        let exponent = ((exponent_msb << self.exponent_bits) | continuation) as i32 - self.bias;
+
+        let mut coefficient = leading;
+        for i in (0..self.declets).rev() {
+            let declet = (bits >> (10 * i)) & 0x3ff;
+            coefficient = coefficient * 1000 + decode_declet(declet as usize) as u128;
+        }
+        Some((negative, coefficient, exponent))
+    }
+
+    /// Encode a decimal. Fails if it has more significant digits than the
+    /// format, rounding is left to the caller.
+    pub fn encode_decimal(&self, value: Decimal, name: &str) -> Result<u128, RfcErrorInfo> {
+        let mut coefficient = value.mantissa().unsigned_abs();
+        let mut exponent = -(value.scale() as i32);
+        while coefficient >= 10_u128.pow(self.digits()) && coefficient.is_multiple_of(10) {
+            coefficient /= 10;
+            exponent += 1;
+        }
+        self.encode(value.is_sign_negative(), coefficient, exponent)
+            .ok_or_else(|| {
+                RfcErrorInfo::conversion(&format!(
+                    "{} has more than {} significant digits and does not fit into {} parameter {}",
+                    value,
+                    self.digits(),
+                    self.name,
+                    name
+                ))
+            })
+    }
+
+    /// Decode into a decimal. Fails for values that Decimal cannot hold
+    /// exactly.
+    pub fn decode_decimal(&self, bits: u128, name: &str) -> Result<Decimal, RfcErrorInfo> {
+        let unrepresentable = || {
+            RfcErrorInfo::conversion(&format!(
+                "The {} value of parameter {} cannot be represented as a Decimal",
+                self.name, name
+            ))
+        };
+        let (negative, mut coefficient, mut exponent) =
+            self.decode(bits).ok_or_else(unrepresentable)?;
+        while exponent > 0 {
+            coefficient = coefficient.checked_mul(10).ok_or_else(unrepresentable)?;
+            exponent -= 1;
+        }
+        while exponent < -(Decimal::MAX_SCALE as i32)
+            && coefficient.is_multiple_of(10)
+            && coefficient > 0
+        {
+            coefficient /= 10;
+            exponent += 1;
+        }
+        if coefficient == 0 {
+            exponent = exponent.max(-(Decimal::MAX_SCALE as i32));
+        }
+        let mantissa = i128::try_from(coefficient).map_err(|_| unrepresentable())?;
+        let mantissa = if negative { -mantissa } else { mantissa };
+        Decimal::try_from_i128_with_scale(mantissa, (-exponent) as u32)
+            .map_err(|_| unrepresentable())
+    }
+}
+
+/// Encode three decimal digits into a declet, following the table of
+/// IEEE 754-2008, 3.5.2
+fn encode_declet(value: u32) -> u32 {
+    let (d2, d1, d0) = (value / 100, value / 10 % 10, value % 10);
+    let (b, c, d) = (d2 >> 2 & 1, d2 >> 1 & 1, d2 & 1);
+    let (f, g, h) = (d1 >> 2 & 1, d1 >> 1 & 1, d1 & 1);
+    let (j, k, m) = (d0 >> 2 & 1, d0 >> 1 & 1, d0 & 1);
+    let bits = |b: [u32; 10]| b.iter().fold(0, |acc, bit| acc << 1 | bit);
+    match (d2 > 7, d1 > 7, d0 > 7) {
+        (false, false, false) => bits([b, c, d, f, g, h, 0, j, k, m]),
+        (false, false, true) => bits([b, c, d, f, g, h, 1, 0, 0, m]),
+        (false, true, false) => bits([b, c, d, j, k, h, 1, 0, 1, m]),
+        (true, false, false) => bits([j, k, d, f, g, h, 1, 1, 0, m]),
+        (true, true, false) => bits([j, k, d, 0, 0, h, 1, 1, 1, m]),
+        (true, false, true) => bits([f, g, d, 0, 1, h, 1, 1, 1, m]),
+        (false, true, true) => bits([b, c, d, 1, 0, h, 1, 1, 1, m]),
+        (true, true, true) => bits([0, 0, d, 1, 1, h, 1, 1, 1, m]),
+    }
+}
+
+fn decode_declet(declet: usize) -> u16 {
+    static TABLE: OnceLock<[u16; 1024]> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut table = [0; 1024];
+        for value in 0..1000 {
+            let declet = encode_declet(value) as usize;
+            table[declet] = value as u16;
+            // The two leading bits are ignored when all digits are large,
+            // so there are three more, non-canonical encodings
+            if declet & 0x6e == 0x6e {
+                for ignored in 1..4 {
+                    table[declet | ignored << 8] = value as u16;
+                }
+            }
+        }
+        table
+    })[declet]
+}
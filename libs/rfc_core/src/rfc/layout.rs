@@ -0,0 +1,69 @@
+//! Compile time checks of the structs the SDK reads and writes through
+//! pointers. The expected sizes and offsets were recorded with `sizeof`
+//! and `offsetof` from sapnwrfc.h (SDK 7.50, 64 bit). Any mismatch fails
+//! the build, so no SDK is needed to run these checks.
+use std::mem::{align_of, offset_of, size_of};
+
+use super::{RfcConnectionParameter, RfcDecF16, RfcDecF34, RfcFieldDesc, RfcParameterDesc};
+use crate::error::RfcErrorInfo;
+
+macro_rules! assert_layout {
+    ($ty:ty, size = $size:expr, { $($field:ident: $offset:expr,)* }) => {
+        const _: () = assert!(size_of::<$ty>() == $size);
+        $(const _: () = assert!(offset_of!($ty, $field) == $offset);)*
+    };
+}
+
+// RFC_CONNECTION_PARAMETER
+assert_layout!(RfcConnectionParameter, size = 16, {
+    name: 0,
+    value: 8,
+});
+
+// RFC_ERROR_INFO
+assert_layout!(RfcErrorInfo, size = 1752, {
+    code: 0,
+    group: 4,
+    key: 8,
+    message: 264,
+    abap_msg_class: 1288,
+    abap_msg_type: 1330,
+    abap_msg_number: 1334,
+    abap_msg_v1: 1342,
+    abap_msg_v2: 1444,
+    abap_msg_v3: 1546,
+    abap_msg_v4: 1648,
+});
+
+// RFC_FIELD_DESC
+assert_layout!(RfcFieldDesc, size = 104, {
+    name: 0,
+    field_type: 64,
+    nuc_length: 68,
+    nuc_offset: 72,
+    uc_length: 76,
+    uc_offset: 80,
+    decimals: 84,
+    type_desc_handle: 88,
+    extended_description: 96,
+});
+
+// RFC_PARAMETER_DESC
+assert_layout!(RfcParameterDesc, size = 328, {
+    name: 0,
+    field_type: 64,
+    direction: 68,
+    nuc_length: 72,
+    uc_length: 76,
+    decimals: 80,
+    type_desc_handle: 88,
+    default_value: 96,
+    parameter_text: 158,
+    optional: 318,
+    extended_description: 320,
+});
+
+// RFC_DECF16 and RFC_DECF34, passed by value
+assert_layout!(RfcDecF16, size = 8, { bytes: 0, });
+assert_layout!(RfcDecF34, size = 16, { bytes: 0, });
+const _: () = assert!(align_of::<RfcDecF16>() == 8 && align_of::<RfcDecF34>() == 8);
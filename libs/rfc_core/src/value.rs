@@ -0,0 +1,441 @@
+//! Conversion between Rust values and RFC parameters, so that one
+//! [`RfcParameter::set`] and [`RfcParameter::get`] cover all types.
+//!
+//! The implementations dispatch on the type of the parameter: an i32 is
+//! written with RfcSetInt to an INT field, with RfcSetFloat to a FLOAT
+//! field and as characters to a NUMC or CHAR field, for instance.
+use chrono::{DateTime, NaiveDate, NaiveTime, TimeZone, Utc};
+
+use crate::error::*;
+use crate::rfc::datetime::{self, DATE_FORMAT, TIME_FORMAT};
+use crate::rfc::RfcType;
+use crate::RfcParameter;
+
+/// A Rust value that can be written to an RFC parameter
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` cannot be written to an RFC parameter or field",
+    label = "neither a scalar nor a structure",
+    note = "structures must derive `RfcRow`; other types implement `ToRfcValue`, derive `RfcEnum` or are written with `#[sap(with = \"...\")]`"
+)]
+// Writes with the SDK's RfcSet* functions, which fail with RfcErrorInfo
+#[allow(clippy::result_large_err)]
+pub trait ToRfcValue {
+    fn to_rfc(&self, param: &mut RfcParameter) -> Result<(), RfcErrorInfo>;
+
+    /// Add what does not fit the fields of `param` to `mismatches`, for
+    /// structures, see [`RfcClient::check`](crate::RfcClient::check)
+    fn check_fields(_param: &mut RfcParameter, _mismatches: &mut Vec<String>)
+    where
+        Self: Sized,
+    {
+    }
+}
+
+/// A Rust value that can be read from an RFC parameter
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` cannot be read from an RFC parameter or field",
+    label = "not a scalar",
+    note = "a structure cannot be read into an `Option`; use `#[sap(default)]` or `#[sap(optional)]` on the field instead",
+    note = "other types implement `FromRfcValue`, derive `RfcEnum` or are read with `#[sap(with = \"...\")]`"
+)]
+// Reads with the SDK's RfcGet* functions, which fail with RfcErrorInfo
+#[allow(clippy::result_large_err)]
+pub trait FromRfcValue: Sized {
+    fn from_rfc(param: &RfcParameter) -> Result<Self, RfcErrorInfo>;
+
+    /// Read the value for an `Option<Self>`. Types that cannot represent
+    /// the initial value of a field, like dates, return None for it.
+    fn from_rfc_opt(param: &RfcParameter) -> Result<Option<Self>, RfcErrorInfo> {
+        Self::from_rfc(param).map(Some)
+    }
+}
+
+fn type_mismatch(param: &RfcParameter, rust_type: &str) -> RfcErrorInfo {
+    RfcErrorInfo::conversion(&format!(
+        "Parameter {} of type {:?} cannot hold a {}",
+        param.name, param.field_type, rust_type
+    ))
+}
+
+fn parse_error(param: &RfcParameter, value: &str, rust_type: &str) -> RfcErrorInfo {
+    RfcErrorInfo::conversion(&format!(
+        "Value '{}' of parameter {} is not a valid {}",
+        value, param.name, rust_type
+    ))
+}
+
+/// Whether the parameter holds a scalar the SDK can convert from and to
+/// characters
+fn is_convertible(param: &RfcParameter) -> bool {
+    !matches!(
+        param.field_type,
+        RfcType::Structure | RfcType::Table | RfcType::XString | RfcType::Byte
+    )
+}
+
+/// The value as text, e.g. "12.50" for a BCD and "1.5E+00" for a FLOAT
+// Passes on the RfcErrorInfo of RfcGetChars and RfcGetString
+#[allow(clippy::result_large_err)]
+fn read_text(param: &RfcParameter, rust_type: &str) -> Result<String, RfcErrorInfo> {
+    match param.field_type {
+        RfcType::Char => Ok(param.get_chars()?.trim_end().to_string()),
+        _ if is_convertible(param) => Ok(param.get_string()?.trim().to_string()),
+        _ => Err(type_mismatch(param, rust_type)),
+    }
+}
+
+// Passes on the RfcErrorInfo of the RfcSet* call for the type
+#[allow(clippy::result_large_err)]
+fn write_integer(
+    param: &mut RfcParameter,
+    value: i64,
+    rust_type: &str,
+) -> Result<(), RfcErrorInfo> {
+    match param.field_type {
+        RfcType::Int | RfcType::Int1 | RfcType::Int2 | RfcType::Int8 => param.set_int(value),
+        RfcType::Num => match u64::try_from(value) {
+            Ok(v) => param.set_num(v),
+            Err(_) => Err(RfcErrorInfo::conversion(&format!(
+                "Parameter {} of type NUMC cannot hold the negative value {}",
+                param.name, value
+            ))),
+        },
+        RfcType::Float => param.set_float(value as f64),
+        _ if is_convertible(param) => param.set_chars(&value.to_string()),
+        _ => Err(type_mismatch(param, rust_type)),
+    }
+}
+
+// Passes on the RfcErrorInfo of the RfcGet* call for the type
+#[allow(clippy::result_large_err)]
+fn read_integer(param: &RfcParameter, rust_type: &str) -> Result<i64, RfcErrorInfo> {
+    if let RfcType::Int | RfcType::Int1 | RfcType::Int2 | RfcType::Int8 | RfcType::Num =
+        param.field_type
+    {
+        return param.get_int();
+    }
+    let text = read_text(param, rust_type)?;
+    if text.is_empty() {
+        return Ok(0);
+    }
+    if let Ok(v) = text.parse::<i64>() {
+        return Ok(v);
+    }
+    // BCD and FLOAT values without a fractional part, e.g. 12.00
+    match text.parse::<f64>() {
+        Ok(v) if v.fract() == 0.0 && v.abs() < i64::MAX as f64 => Ok(v as i64),
+        _ => Err(parse_error(param, &text, rust_type)),
+    }
+}
+
+macro_rules! integer_value {
+    ($($t:ty),*) => {$(
+        impl ToRfcValue for $t {
+            fn to_rfc(&self, param: &mut RfcParameter) -> Result<(), RfcErrorInfo> {
+                let value = i64::try_from(*self).map_err(|_| {
+                    RfcErrorInfo::conversion(&format!(
+                        "{} is too large for parameter {}",
+                        self, param.name
+                    ))
+                })?;
+                write_integer(param, value, stringify!($t))
+            }
+        }
+
+        impl FromRfcValue for $t {
+            fn from_rfc(param: &RfcParameter) -> Result<Self, RfcErrorInfo> {
+                let value = read_integer(param, stringify!($t))?;
+                <$t>::try_from(value).map_err(|_| {
+                    RfcErrorInfo::conversion(&format!(
+                        "Value {} of parameter {} does not fit into {}",
+                        value, param.name, stringify!($t)
+                    ))
+                })
+            }
+        }
+    )*};
+}
+
+integer_value!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl ToRfcValue for f64 {
+    fn to_rfc(&self, param: &mut RfcParameter) -> Result<(), RfcErrorInfo> {
+        match param.field_type {
+            RfcType::Float | RfcType::Bcd | RfcType::Decf16 | RfcType::Decf34 => {
+                param.set_float(*self)
+            }
+            RfcType::Int | RfcType::Int1 | RfcType::Int2 | RfcType::Int8 => {
+                Err(type_mismatch(param, "f64"))
+            }
+            _ if is_convertible(param) => param.set_chars(&self.to_string()),
+            _ => Err(type_mismatch(param, "f64")),
+        }
+    }
+}
+
+impl FromRfcValue for f64 {
+    fn from_rfc(param: &RfcParameter) -> Result<Self, RfcErrorInfo> {
+        if param.field_type == RfcType::Float {
+            return param.get_float();
+        }
+        let text = read_text(param, "f64")?;
+        if text.is_empty() {
+            return Ok(0.0);
+        }
+        text.parse().map_err(|_| parse_error(param, &text, "f64"))
+    }
+}
+
+impl ToRfcValue for f32 {
+    fn to_rfc(&self, param: &mut RfcParameter) -> Result<(), RfcErrorInfo> {
+        (*self as f64).to_rfc(param)
+    }
+}
+
+impl FromRfcValue for f32 {
+    fn from_rfc(param: &RfcParameter) -> Result<Self, RfcErrorInfo> {
+        f64::from_rfc(param).map(|v| v as f32)
+    }
+}
+
+/// The ABAP convention for flags: 'X' is true and a blank is false
+impl ToRfcValue for bool {
+    fn to_rfc(&self, param: &mut RfcParameter) -> Result<(), RfcErrorInfo> {
+        match param.field_type {
+            RfcType::Char | RfcType::String => param.set_chars(if *self { "X" } else { " " }),
+            _ => Err(type_mismatch(param, "bool")),
+        }
+    }
+}
+
+impl FromRfcValue for bool {
+    fn from_rfc(param: &RfcParameter) -> Result<Self, RfcErrorInfo> {
+        let text = match param.field_type {
+            RfcType::Char | RfcType::String => read_text(param, "bool")?,
+            _ => return Err(type_mismatch(param, "bool")),
+        };
+        match text.trim() {
+            "X" => Ok(true),
+            "" => Ok(false),
+            _ => Err(parse_error(param, &text, "bool")),
+        }
+    }
+}
+
+impl ToRfcValue for str {
+    fn to_rfc(&self, param: &mut RfcParameter) -> Result<(), RfcErrorInfo> {
+        if is_convertible(param) {
+            param.set_chars(self)
+        } else {
+            Err(type_mismatch(param, "string"))
+        }
+    }
+}
+
+impl ToRfcValue for String {
+    fn to_rfc(&self, param: &mut RfcParameter) -> Result<(), RfcErrorInfo> {
+        self.as_str().to_rfc(param)
+    }
+}
+
+/// CHAR values are returned without the trailing blanks that pad them to
+/// the length of the field. An `Option<String>` is None for blank values.
+impl FromRfcValue for String {
+    fn from_rfc(param: &RfcParameter) -> Result<Self, RfcErrorInfo> {
+        match param.field_type {
+            RfcType::String => param.get_string(),
+            _ => read_text(param, "string"),
+        }
+    }
+
+    fn from_rfc_opt(param: &RfcParameter) -> Result<Option<Self>, RfcErrorInfo> {
+        let value = Self::from_rfc(param)?;
+        Ok(if value.trim().is_empty() {
+            None
+        } else {
+            Some(value)
+        })
+    }
+}
+
+impl ToRfcValue for [u8] {
+    fn to_rfc(&self, param: &mut RfcParameter) -> Result<(), RfcErrorInfo> {
+        match param.field_type {
+            RfcType::XString => param.set_xstring(self),
+            RfcType::Byte => param.set_bytes(self),
+            _ => Err(type_mismatch(param, "byte vector")),
+        }
+    }
+}
+
+impl ToRfcValue for Vec<u8> {
+    fn to_rfc(&self, param: &mut RfcParameter) -> Result<(), RfcErrorInfo> {
+        self.as_slice().to_rfc(param)
+    }
+}
+
+impl FromRfcValue for Vec<u8> {
+    fn from_rfc(param: &RfcParameter) -> Result<Self, RfcErrorInfo> {
+        match param.field_type {
+            RfcType::XString => param.get_xstring(),
+            RfcType::Byte => param.get_bytes(),
+            _ => Err(type_mismatch(param, "byte vector")),
+        }
+    }
+}
+
+impl ToRfcValue for NaiveDate {
+    fn to_rfc(&self, param: &mut RfcParameter) -> Result<(), RfcErrorInfo> {
+        let value = self.format(DATE_FORMAT).to_string();
+        match param.field_type {
+            RfcType::Date => param.set_date(&value),
+            RfcType::Char | RfcType::Num | RfcType::String => param.set_chars(&value),
+            _ => Err(type_mismatch(param, "date")),
+        }
+    }
+}
+
+/// The initial value 00000000 is not a valid date, read it into an
+/// `Option<NaiveDate>`.
+impl FromRfcValue for NaiveDate {
+    fn from_rfc(param: &RfcParameter) -> Result<Self, RfcErrorInfo> {
+        Self::from_rfc_opt(param)?.ok_or_else(|| {
+            RfcErrorInfo::conversion(&format!(
+                "Parameter {} holds the initial date 00000000",
+                param.name
+            ))
+        })
+    }
+
+    fn from_rfc_opt(param: &RfcParameter) -> Result<Option<Self>, RfcErrorInfo> {
+        match param.field_type {
+            RfcType::Date => param.get_date(),
+            RfcType::Char | RfcType::Num | RfcType::String => {
+                datetime::parse_date(&read_text(param, "date")?, &param.name)
+            }
+            _ => Err(type_mismatch(param, "date")),
+        }
+    }
+}
+
+impl ToRfcValue for NaiveTime {
+    fn to_rfc(&self, param: &mut RfcParameter) -> Result<(), RfcErrorInfo> {
+        let value = self.format(TIME_FORMAT).to_string();
+        match param.field_type {
+            RfcType::Time => param.set_time(&value),
+            RfcType::Char | RfcType::Num | RfcType::String => param.set_chars(&value),
+            _ => Err(type_mismatch(param, "time")),
+        }
+    }
+}
+
+/// The initial value 000000 reads as midnight, or as None into an
+/// `Option<NaiveTime>`.
+impl FromRfcValue for NaiveTime {
+    fn from_rfc(param: &RfcParameter) -> Result<Self, RfcErrorInfo> {
+        Ok(Self::from_rfc_opt(param)?.unwrap_or(NaiveTime::MIN))
+    }
+
+    fn from_rfc_opt(param: &RfcParameter) -> Result<Option<Self>, RfcErrorInfo> {
+        match param.field_type {
+            RfcType::Time => param.get_time(),
+            RfcType::Char | RfcType::Num | RfcType::String => {
+                datetime::parse_time(&read_text(param, "time")?, &param.name)
+            }
+            _ => Err(type_mismatch(param, "time")),
+        }
+    }
+}
+
+/// Timestamps are converted to UTC, DATS and TIMS take the date and time
+/// in the time zone of the value.
+impl<Tz: TimeZone> ToRfcValue for DateTime<Tz> {
+    fn to_rfc(&self, param: &mut RfcParameter) -> Result<(), RfcErrorInfo> {
+        match param.field_type {
+            _ if param.is_timestamp() => param.set_timestamp(self.with_timezone(&Utc)),
+            RfcType::Date => self.date_naive().to_rfc(param),
+            RfcType::Time => self.time().to_rfc(param),
+            _ => Err(type_mismatch(param, "timestamp")),
+        }
+    }
+}
+
+/// The initial timestamp is not a valid point in time, read it into an
+/// `Option<DateTime<Utc>>`.
+impl FromRfcValue for DateTime<Utc> {
+    fn from_rfc(param: &RfcParameter) -> Result<Self, RfcErrorInfo> {
+        Self::from_rfc_opt(param)?.ok_or_else(|| {
+            RfcErrorInfo::conversion(&format!(
+                "Parameter {} holds the initial timestamp",
+                param.name
+            ))
+        })
+    }
+
+    fn from_rfc_opt(param: &RfcParameter) -> Result<Option<Self>, RfcErrorInfo> {
+        if !param.is_timestamp() {
+            return Err(type_mismatch(param, "timestamp"));
+        }
+        param.get_timestamp()
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+impl ToRfcValue for rust_decimal::Decimal {
+    fn to_rfc(&self, param: &mut RfcParameter) -> Result<(), RfcErrorInfo> {
+        use rust_decimal::prelude::ToPrimitive;
+
+        match param.field_type {
+            RfcType::Bcd | RfcType::Decf16 | RfcType::Decf34 => param.set_decimal(*self),
+            RfcType::Float => param.set_float(self.to_f64().unwrap_or_default()),
+            RfcType::Int | RfcType::Int1 | RfcType::Int2 | RfcType::Int8 => {
+                match self.to_i64().filter(|_| self.fract().is_zero()) {
+                    Some(v) => write_integer(param, v, "decimal"),
+                    None => Err(type_mismatch(param, "decimal with fractional digits")),
+                }
+            }
+            _ if is_convertible(param) => param.set_chars(&self.to_string()),
+            _ => Err(type_mismatch(param, "decimal")),
+        }
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+impl FromRfcValue for rust_decimal::Decimal {
+    fn from_rfc(param: &RfcParameter) -> Result<Self, RfcErrorInfo> {
+        use std::str::FromStr;
+
+        if let RfcType::Bcd | RfcType::Decf16 | RfcType::Decf34 = param.field_type {
+            return param.get_decimal();
+        }
+        let text = read_text(param, "decimal")?;
+        if text.is_empty() {
+            return Ok(Self::ZERO);
+        }
+        Self::from_str(&text)
+            .or_else(|_| Self::from_scientific(&text))
+            .map_err(|_| parse_error(param, &text, "decimal"))
+    }
+}
+
+impl<T: ToRfcValue + ?Sized> ToRfcValue for &T {
+    fn to_rfc(&self, param: &mut RfcParameter) -> Result<(), RfcErrorInfo> {
+        (*self).to_rfc(param)
+    }
+}
+
+/// None leaves the parameter untouched
+impl<T: ToRfcValue> ToRfcValue for Option<T> {
+    fn to_rfc(&self, param: &mut RfcParameter) -> Result<(), RfcErrorInfo> {
+        match self {
+            Some(v) => v.to_rfc(param),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<T: FromRfcValue> FromRfcValue for Option<T> {
+    fn from_rfc(param: &RfcParameter) -> Result<Self, RfcErrorInfo> {
+        T::from_rfc_opt(param)
+    }
+}
@@ -0,0 +1,129 @@
+#![cfg(feature = "async")]
+mod common;
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use common::*;
+use rsrfc::{error::*, *};
+
+#[derive(Debug, RfcResult)]
+struct Echo {
+    #[sap(alias = "EV_TEXT")]
+    text: String,
+}
+
+#[derive(RfcParams)]
+struct EchoRequest {
+    #[sap(alias = "IV_TEXT")]
+    text: String,
+}
+
+fn install_echo(mock: &MockLib, name: &str, delay: Duration) {
+    mock.install(
+        &FunctionModule::new(name)
+            .importing("IV_TEXT", DataType::char(10))
+            .exporting("EV_TEXT", DataType::char(10))
+            .respond(Response::new().copy("EV_TEXT", "IV_TEXT").delay(delay)),
+    );
+}
+
+fn params(text: &'static str) -> HashMap<&'static str, ParamType<'static>> {
+    HashMap::from([("IV_TEXT", ParamType::Value(ParamValue::Str(text)))])
+}
+
+#[tokio::test]
+async fn execute_runs_off_the_executor() {
+    let mock = mock();
+    install_echo(&mock, "Z_ASYNC_ECHO", Duration::ZERO);
+    let client = AsyncRfcClient::new().unwrap();
+    let echo: Echo = client
+        .execute("Z_ASYNC_ECHO", params("HELLO"))
+        .await
+        .unwrap();
+    assert_eq!(echo.text, "HELLO");
+}
+
+#[tokio::test]
+async fn call_writes_typed_params() {
+    let mock = mock();
+    install_echo(&mock, "Z_ASYNC_CALL", Duration::ZERO);
+    let client = AsyncRfcClient::new().unwrap();
+    let request = EchoRequest {
+        text: "TYPED".into(),
+    };
+    let echo: Echo = client.call("Z_ASYNC_CALL", request).await.unwrap();
+    assert_eq!(echo.text, "TYPED");
+}
+
+#[tokio::test]
+async fn timeout_cancels_the_call() {
+    let mock = mock();
+    install_echo(&mock, "Z_ASYNC_SLOW", Duration::from_secs(10));
+    let client = AsyncRfcClient::new()
+        .unwrap()
+        .with_timeout(Duration::from_millis(100));
+    let err = client
+        .execute::<Echo>("Z_ASYNC_SLOW", params("SLOW"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), RfcRc::RfcTimeout);
+
+    // The canceled connection is replaced and the client keeps working
+    install_echo(&mock, "Z_ASYNC_SLOW", Duration::ZERO);
+    let echo: Echo = client
+        .execute("Z_ASYNC_SLOW", params("FAST"))
+        .await
+        .unwrap();
+    assert_eq!(echo.text, "FAST");
+}
+
+#[tokio::test]
+async fn dropping_the_future_cancels_the_call() {
+    let mock = mock();
+    install_echo(&mock, "Z_ASYNC_DROP", Duration::from_secs(10));
+    let client = AsyncRfcClient::new().unwrap();
+    let call = client.execute::<Echo>("Z_ASYNC_DROP", params("DROP"));
+    assert!(tokio::time::timeout(Duration::from_millis(100), call)
+        .await
+        .is_err());
+
+    // The worker closes the connection long before the call would have
+    // finished
+    let started = Instant::now();
+    while client.client().pool().size() > 0 {
+        assert!(started.elapsed() < Duration::from_secs(5));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
+
+#[tokio::test]
+async fn canceled_connection_is_closed_without_health_check() {
+    let mock = mock();
+    install_echo(&mock, "Z_ASYNC_NOCHECK", Duration::from_secs(10));
+    let client = RfcClient::new().unwrap().with_pool_config(RfcPoolConfig {
+        health_check: false,
+        ..Default::default()
+    });
+    let client = AsyncRfcClient::from_client(client).with_timeout(Duration::from_millis(100));
+    let err = client
+        .execute::<Echo>("Z_ASYNC_NOCHECK", params("SLOW"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), RfcRc::RfcTimeout);
+
+    // The worker closes the canceled connection rather than giving it back
+    let started = Instant::now();
+    while client.client().pool().size() > 0 {
+        assert!(started.elapsed() < Duration::from_secs(5));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(client.client().pool().idle(), 0);
+
+    install_echo(&mock, "Z_ASYNC_NOCHECK", Duration::ZERO);
+    let echo: Echo = client
+        .execute("Z_ASYNC_NOCHECK", params("FAST"))
+        .await
+        .unwrap();
+    assert_eq!(echo.text, "FAST");
+}
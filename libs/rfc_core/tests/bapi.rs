@@ -0,0 +1,195 @@
+#![allow(clippy::result_large_err)]
+mod common;
+
+use std::collections::HashMap;
+
+use common::*;
+use rsrfc::{error::*, *};
+
+fn bapiret2() -> Vec<Field> {
+    vec![
+        Field::new("TYPE", DataType::char(1)),
+        Field::new("ID", DataType::char(20)),
+        Field::new("NUMBER", DataType::num(3)),
+        Field::new("MESSAGE", DataType::char(220)),
+        Field::new("LOG_NO", DataType::char(20)),
+        Field::new("LOG_MSG_NO", DataType::num(6)),
+        Field::new("MESSAGE_V1", DataType::char(50)),
+        Field::new("MESSAGE_V2", DataType::char(50)),
+        Field::new("MESSAGE_V3", DataType::char(50)),
+        Field::new("MESSAGE_V4", DataType::char(50)),
+        Field::new("PARAMETER", DataType::char(32)),
+        Field::new("ROW", DataType::Int),
+        Field::new("FIELD", DataType::char(30)),
+        Field::new("SYSTEM", DataType::char(10)),
+    ]
+}
+
+/// BAPIRET1 lacks the fields BAPIRET2 adds at the end
+fn bapiret1() -> Vec<Field> {
+    let mut fields = bapiret2();
+    fields.truncate(10);
+    fields
+}
+
+fn install_bapi(mock: &MockLib, name: &str, messages: serde_json::Value) {
+    mock.install(
+        &FunctionModule::new(name)
+            .exporting("EV_EBELN", DataType::char(10))
+            .tables("RETURN", DataType::table(bapiret2()))
+            .respond(
+                Response::new()
+                    .export("EV_EBELN", json!("4500000001"))
+                    .export("RETURN", messages),
+            ),
+    );
+}
+
+#[derive(Debug, RfcResult)]
+#[sap(rename_all = "UPPERCASE")]
+struct CreateResult {
+    ev_ebeln: String,
+    #[sap(alias = "RETURN")]
+    messages: Vec<BapiReturn>,
+}
+
+#[test]
+fn bapi_errors_fail_the_call() {
+    let mock = mock();
+    install_bapi(
+        &mock,
+        "Z_BAPI_ERRORS",
+        json!([
+            {"TYPE": "W", "ID": "ZMM", "NUMBER": "010", "MESSAGE": "Delivery date is in the past"},
+            {"TYPE": "E", "ID": "ZMM", "NUMBER": "042", "MESSAGE": "Vendor 1000 is blocked",
+             "MESSAGE_V1": "1000", "PARAMETER": "POHEADER", "ROW": 1, "FIELD": "VENDOR"},
+            {"TYPE": "S", "ID": "ZMM", "NUMBER": "001", "MESSAGE": "Checked"},
+            {"TYPE": "A", "ID": "ZMM", "NUMBER": "099", "MESSAGE": "Processing aborted"},
+        ]),
+    );
+
+    let client = RfcClient::new().unwrap().with_bapi_errors(true);
+    let err = client
+        .execute::<CreateResult>("Z_BAPI_ERRORS", HashMap::new())
+        .unwrap_err();
+    assert_eq!(err.code(), RfcRc::RfcAbapMessage);
+    assert!(!err.is_retryable());
+    assert_eq!(
+        err.to_string(),
+        "Z_BAPI_ERRORS failed: Processing aborted (A099(ZMM)); \
+         Vendor 1000 is blocked (E042(ZMM))"
+    );
+    assert_eq!(
+        err.message(),
+        "Z_BAPI_ERRORS failed: Processing aborted; Vendor 1000 is blocked"
+    );
+
+    let RfcError::BapiError { function, messages } = err else {
+        panic!("unexpected error {err:?}");
+    };
+    assert_eq!(function, "Z_BAPI_ERRORS");
+    let types: Vec<_> = messages.iter().map(|m| m.r#type.clone()).collect();
+    assert_eq!(
+        types,
+        [
+            BapiSeverity::Abort,
+            BapiSeverity::Error,
+            BapiSeverity::Warning,
+            BapiSeverity::Success
+        ]
+    );
+    let vendor = &messages[1];
+    assert_eq!(vendor.message_v1, "1000");
+    assert_eq!(vendor.parameter, "POHEADER");
+    assert_eq!(vendor.row, 1);
+    assert_eq!(vendor.field, "VENDOR");
+    assert_eq!(vendor.abap_message().to_string(), "E042(ZMM)");
+    assert_eq!(vendor.abap_message().v1, "1000");
+}
+
+#[test]
+fn warnings_leave_the_messages_to_the_result() {
+    let mock = mock();
+    install_bapi(
+        &mock,
+        "Z_BAPI_WARNINGS",
+        json!([
+            {"TYPE": "S", "ID": "06", "NUMBER": "017", "MESSAGE": "Purchase order 4500000001 created"},
+            {"TYPE": "W", "ID": "ZMM", "NUMBER": "010", "MESSAGE": "Delivery date is in the past"},
+        ]),
+    );
+
+    let client = RfcClient::new().unwrap().with_bapi_errors(true);
+    let result: CreateResult = client.execute("Z_BAPI_WARNINGS", HashMap::new()).unwrap();
+    assert_eq!(result.ev_ebeln, "4500000001");
+    assert_eq!(result.messages.len(), 2);
+    assert_eq!(result.messages[0].r#type, BapiSeverity::Success);
+    assert_eq!(result.messages[0].id, "06");
+    assert_eq!(
+        result.messages[1].to_string(),
+        "Delivery date is in the past (W010(ZMM))"
+    );
+    assert!(RfcError::from_bapi_return("Z_BAPI_WARNINGS", &result.messages).is_none());
+}
+
+#[test]
+fn bapi_errors_are_off_by_default() {
+    let mock = mock();
+    install_bapi(
+        &mock,
+        "Z_BAPI_UNCHECKED",
+        json!([{"TYPE": "E", "ID": "ZMM", "NUMBER": "042", "MESSAGE": "Vendor 1000 is blocked"}]),
+    );
+
+    let client = RfcClient::new().unwrap();
+    let result: CreateResult = client.execute("Z_BAPI_UNCHECKED", HashMap::new()).unwrap();
+    assert!(result.messages[0].r#type.is_error());
+}
+
+#[test]
+fn return_structures_are_checked() {
+    let mock = mock();
+    mock.install(
+        &FunctionModule::new("Z_BAPI_STRUCTURE")
+            .exporting("RETURN", DataType::structure(bapiret1()))
+            .respond(Response::new())
+            .respond(Response::new().export(
+                "RETURN",
+                json!({"TYPE": "E", "ID": "ZMM", "NUMBER": "042",
+                       "MESSAGE_V1": "1000", "MESSAGE_V2": "0001"}),
+            )),
+    );
+
+    let client = RfcClient::new().unwrap().with_bapi_errors(true);
+    // An initial structure is no message
+    client
+        .execute::<()>("Z_BAPI_STRUCTURE", HashMap::new())
+        .unwrap();
+
+    let err = client
+        .execute::<()>("Z_BAPI_STRUCTURE", HashMap::new())
+        .unwrap_err();
+    let RfcError::BapiError { messages, .. } = &err else {
+        panic!("unexpected error {err:?}");
+    };
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].parameter, "");
+    // Without a text, the T100 key and variables stand in for it
+    assert_eq!(messages[0].text(), "E042(ZMM) 1000 0001");
+    assert_eq!(
+        err.to_string(),
+        "Z_BAPI_STRUCTURE failed: E042(ZMM) 1000 0001"
+    );
+}
+
+#[test]
+fn severities_are_ordered() {
+    assert!(BapiSeverity::Success < BapiSeverity::Info);
+    assert!(BapiSeverity::Info < BapiSeverity::Warning);
+    assert!(BapiSeverity::Warning < BapiSeverity::Error);
+    assert!(BapiSeverity::Error < BapiSeverity::Abort);
+    assert!(BapiSeverity::Other(String::new()) < BapiSeverity::Success);
+    assert!(!BapiSeverity::Warning.is_error());
+    assert!(BapiSeverity::Abort.is_error());
+    assert!(BapiSeverity::Exit.is_error());
+}
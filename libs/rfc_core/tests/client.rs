@@ -0,0 +1,1316 @@
+#![allow(clippy::result_large_err)]
+
+mod common;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::NaiveDate;
+
+use common::*;
+use rsrfc::{error::*, *};
+
+#[derive(Debug, RfcTable)]
+struct Item {
+    #[sap(alias = "EBELN")]
+    order_code: String,
+    #[sap(alias = "TYPE")]
+    r#type: String,
+    #[sap(alias = "MSG")]
+    msg: String,
+}
+
+#[derive(Debug, RfcResult)]
+struct CreateResult {
+    #[sap(alias = "EV_EBELN")]
+    ev_ebeln: String,
+    #[sap(alias = "EV_TYPE")]
+    ev_type: String,
+    #[sap(alias = "CT_DATA")]
+    ct_data: Vec<Item>,
+}
+
+#[derive(Debug, RfcTable)]
+struct Position {
+    #[sap(alias = "POSNR")]
+    posnr: u64,
+    #[sap(alias = "MENGE")]
+    menge: f64,
+    #[sap(alias = "PRICE")]
+    price: f32,
+}
+
+#[derive(Debug, RfcResult)]
+struct Totals {
+    #[sap(alias = "EV_COUNT")]
+    count: i32,
+    #[sap(alias = "EV_RATE")]
+    rate: f64,
+    #[sap(alias = "EV_POSNR")]
+    posnr: u32,
+    #[sap(alias = "ET_ITEMS")]
+    items: Vec<Position>,
+}
+
+#[derive(Debug, RfcStructure)]
+struct Address {
+    #[sap(alias = "CITY")]
+    city: String,
+    #[sap(alias = "POSTCODE")]
+    postcode: u32,
+}
+
+#[derive(Debug, RfcTable)]
+struct Line {
+    #[sap(alias = "POSNR")]
+    posnr: u32,
+}
+
+#[derive(Debug, RfcStructure)]
+struct Header {
+    #[sap(alias = "EBELN")]
+    ebeln: String,
+    #[sap(alias = "ADDRESS")]
+    address: Address,
+    #[sap(alias = "LINES")]
+    lines: Vec<Line>,
+}
+
+#[derive(Debug, RfcStructure)]
+struct Message {
+    #[sap(alias = "TYPE")]
+    r#type: String,
+    #[sap(alias = "MESSAGE")]
+    message: String,
+}
+
+#[derive(Debug, RfcTable)]
+struct Order {
+    #[sap(alias = "EBELN")]
+    ebeln: String,
+    #[sap(alias = "RETURN")]
+    ret: Message,
+    #[sap(alias = "LINES")]
+    lines: Vec<Line>,
+}
+
+#[derive(Debug, RfcResult)]
+struct Deep {
+    #[sap(alias = "ES_HEADER")]
+    header: Header,
+    #[sap(alias = "ET_ORDERS")]
+    orders: Vec<Order>,
+}
+
+#[derive(Debug, RfcTable)]
+struct Flagged {
+    #[sap(alias = "POSNR")]
+    posnr: i64,
+    #[sap(alias = "DELETED")]
+    deleted: bool,
+    #[sap(alias = "EEIND")]
+    delivery: Option<NaiveDate>,
+}
+
+#[derive(Debug, RfcResult)]
+struct Typed {
+    #[sap(alias = "EV_COUNT")]
+    count: i64,
+    #[sap(alias = "EV_FLAG")]
+    flag: bool,
+    #[sap(alias = "EV_DATE")]
+    date: NaiveDate,
+    #[sap(alias = "EV_HASH")]
+    hash: Vec<u8>,
+    #[sap(alias = "EV_NOTE")]
+    note: Option<String>,
+    #[sap(alias = "ET_ITEMS")]
+    items: Vec<Flagged>,
+}
+
+/// Plants are shown with a prefix, but SAP knows them without
+mod plant_code {
+    use rsrfc::{error::RfcErrorInfo, RfcParameter};
+
+    pub fn read(param: &RfcParameter) -> Result<String, RfcErrorInfo> {
+        Ok(format!("P-{}", param.get::<String>()?))
+    }
+
+    pub fn write(value: &str, param: &mut RfcParameter) -> Result<(), RfcErrorInfo> {
+        param.set(value.trim_start_matches("P-"))
+    }
+}
+
+mod uppercase {
+    use rsrfc::{error::RfcErrorInfo, RfcParameter};
+
+    pub fn write(value: &str, param: &mut RfcParameter) -> Result<(), RfcErrorInfo> {
+        param.set(value.to_uppercase())
+    }
+}
+
+#[derive(Debug, RfcTable)]
+#[sap(rename_all = "UPPERCASE")]
+struct Stock {
+    matnr: String,
+    #[sap(alias = "WERKS", with = "plant_code")]
+    plant: String,
+    #[sap(trim = false)]
+    lgort: String,
+    #[sap(trim = false, default)]
+    lgpbe: String,
+    #[sap(default)]
+    eindt: NaiveDate,
+    #[sap(optional)]
+    charg: Option<String>,
+    #[sap(skip)]
+    notes: Vec<String>,
+}
+
+/// Read and written, so `with` needs both functions of its module
+#[derive(Debug, PartialEq, RfcTable, RfcRow)]
+#[sap(rename_all = "UPPERCASE")]
+struct Plant {
+    #[sap(alias = "WERKS", with = "plant_code")]
+    plant: String,
+    name1: String,
+}
+
+#[derive(RfcParams)]
+#[sap(rename_all = "UPPERCASE")]
+struct PlantUpdate {
+    it_plants: Vec<Plant>,
+}
+
+#[derive(Debug, RfcResult)]
+#[sap(rename_all = "UPPERCASE")]
+struct PlantResult {
+    et_plants: Vec<Plant>,
+}
+
+#[derive(Debug, RfcResult)]
+#[sap(rename_all = "UPPERCASE")]
+struct StockResult {
+    et_stock: Vec<Stock>,
+    #[sap(optional)]
+    ev_missing: Option<i32>,
+    #[sap(default)]
+    ev_count: i32,
+}
+
+#[derive(RfcParams)]
+#[sap(rename_all = "UPPERCASE")]
+struct StockQuery {
+    #[sap(with = "uppercase")]
+    iv_matnr: String,
+    #[sap(optional)]
+    iv_not_there: String,
+    #[sap(skip)]
+    #[allow(dead_code)]
+    cache_key: String,
+}
+
+#[derive(Debug, PartialEq, RfcEnum)]
+enum MessageType {
+    #[sap(value = "S")]
+    Success,
+    #[sap(value = "E")]
+    Error,
+    #[sap(value = "W")]
+    Warning,
+    Other(String),
+}
+
+#[derive(Debug, PartialEq, RfcEnum)]
+enum Release {
+    #[sap(value = "R")]
+    Released,
+    #[sap(value = "B")]
+    Blocked,
+}
+
+#[derive(Debug, RfcTable)]
+#[sap(rename_all = "UPPERCASE")]
+struct Return {
+    r#type: MessageType,
+    message: String,
+}
+
+#[derive(RfcParams)]
+#[sap(rename_all = "UPPERCASE")]
+struct ReleaseRequest {
+    iv_type: MessageType,
+    iv_release: Option<Release>,
+}
+
+#[derive(Debug, RfcResult)]
+#[sap(rename_all = "UPPERCASE")]
+struct ReleaseResult {
+    ev_type: MessageType,
+    ev_release: Option<Release>,
+    et_return: Vec<Return>,
+}
+
+#[derive(Debug, RfcResult)]
+#[sap(rename_all = "UPPERCASE")]
+#[allow(dead_code)]
+struct StrictRelease {
+    ev_code: Release,
+}
+
+#[derive(RfcRow)]
+struct Vendor {
+    #[sap(alias = "LIFNR")]
+    lifnr: String,
+    #[sap(alias = "ADDRESS")]
+    address: PostalAddress,
+}
+
+#[derive(RfcRow)]
+struct PostalAddress {
+    #[sap(alias = "CITY")]
+    city: String,
+    #[sap(alias = "POSTCODE")]
+    postcode: u32,
+}
+
+#[derive(RfcRow)]
+struct Requisition {
+    #[sap(alias = "BANFN")]
+    banfn: String,
+    #[sap(alias = "MENGE")]
+    menge: f64,
+    #[sap(alias = "EEIND")]
+    delivery: NaiveDate,
+    #[sap(alias = "VENDOR")]
+    vendor: Vendor,
+}
+
+#[derive(RfcParams)]
+struct CreateRequest {
+    #[sap(alias = "IV_ZSQDH")]
+    zsqdh: String,
+    #[sap(alias = "IV_COUNT")]
+    count: i32,
+    #[sap(alias = "IV_NOTE")]
+    note: Option<String>,
+    #[sap(alias = "CS_VENDOR")]
+    vendor: Vendor,
+    #[sap(alias = "CT_DATA")]
+    items: Vec<Requisition>,
+}
+
+#[derive(Debug, RfcResult)]
+struct Created {
+    #[sap(alias = "EV_EBELN")]
+    ev_ebeln: String,
+}
+
+#[derive(RfcParams)]
+struct WritesExport {
+    #[sap(alias = "EV_EBELN")]
+    ebeln: String,
+}
+
+#[derive(RfcParams)]
+struct BulkRequest {
+    #[sap(alias = "CT_DATA")]
+    items: Vec<Requisition>,
+}
+
+fn item_type() -> DataType {
+    DataType::table(vec![
+        Field::new("BANFN", DataType::char(10)),
+        Field::new("MENGE", DataType::bcd(7, 3)),
+        Field::new("EBELN", DataType::char(10)),
+        Field::new("TYPE", DataType::char(1)),
+        Field::new("MSG", DataType::char(50)),
+    ])
+}
+
+#[test]
+fn execute_maps_exports_and_tables() {
+    let mock = mock();
+    mock.install(
+        &FunctionModule::new("Z_TEST_EXECUTE")
+            .importing("IV_ZSQDH", DataType::char(10))
+            .exporting("EV_EBELN", DataType::char(10))
+            .exporting("EV_TYPE", DataType::char(1))
+            .tables("CT_DATA", item_type())
+            .respond(
+                Response::new()
+                    .export("EV_EBELN", json!("4500000001"))
+                    .export("EV_TYPE", json!("S"))
+                    .export(
+                        "CT_DATA",
+                        json!([
+                            {"EBELN": "4500000001", "TYPE": "S", "MSG": "created"},
+                            {"EBELN": "4500000002", "TYPE": "W", "MSG": "check price"},
+                        ]),
+                    ),
+            ),
+    );
+
+    let client = RfcClient::new().unwrap();
+    let mut params = HashMap::new();
+    params.insert("IV_ZSQDH", ParamType::Value(ParamValue::Str("REQ1")));
+    params.insert(
+        "CT_DATA",
+        ParamType::Table(vec![vec![
+            ("BANFN", ParamValue::Str("0010000001")),
+            ("MENGE", ParamValue::Dec(1.5)),
+        ]]),
+    );
+    let result: CreateResult = client.execute("Z_TEST_EXECUTE", params).unwrap();
+
+    assert_eq!(result.ev_ebeln, "4500000001");
+    assert_eq!(result.ev_type, "S");
+    assert_eq!(result.ct_data.len(), 2);
+    assert_eq!(result.ct_data[1].order_code, "4500000002");
+    assert_eq!(result.ct_data[1].r#type, "W");
+    assert_eq!(result.ct_data[1].msg, "check price");
+
+    let calls = mock.calls("Z_TEST_EXECUTE");
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0]["IV_ZSQDH"], "REQ1");
+    assert_eq!(calls[0]["CT_DATA"][0]["BANFN"], "0010000001");
+    assert_eq!(calls[0]["CT_DATA"][0]["MENGE"], "1.500");
+}
+
+#[test]
+fn execute_writes_every_table_row() {
+    let mock = mock();
+    mock.install(
+        &FunctionModule::new("Z_TEST_EXECUTE_ROWS")
+            .tables("CT_DATA", item_type())
+            .respond(Response::new()),
+    );
+
+    let client = RfcClient::new().unwrap();
+    let mut params = HashMap::new();
+    params.insert(
+        "CT_DATA",
+        ParamType::Table(
+            ["0010000001", "0010000002", "0010000003"]
+                .into_iter()
+                .map(|banfn| vec![("BANFN", ParamValue::Str(banfn))])
+                .collect(),
+        ),
+    );
+    client.execute::<()>("Z_TEST_EXECUTE_ROWS", params).unwrap();
+
+    let calls = mock.calls("Z_TEST_EXECUTE_ROWS");
+    let rows = calls[0]["CT_DATA"].as_array().unwrap();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[0]["BANFN"], "0010000001");
+    assert_eq!(rows[1]["BANFN"], "0010000002");
+    assert_eq!(rows[2]["BANFN"], "0010000003");
+}
+
+#[test]
+fn execute_reports_every_input_problem() {
+    let mock = mock();
+    install_create(&mock, "Z_TEST_CREATE_BAD_INPUT");
+
+    let params = || {
+        let mut params = HashMap::new();
+        params.insert("IV_ZSQDH", ParamType::Value(ParamValue::Str("REQ1")));
+        params.insert("IV_COUNT", ParamType::Value(ParamValue::Str("two")));
+        params.insert("IV_UNKNOWN", ParamType::Value(ParamValue::Int(1)));
+        params.insert("EV_EBELN", ParamType::Value(ParamValue::Str("4500000001")));
+        params.insert(
+            "CS_VENDOR",
+            ParamType::Struct(vec![("LIFNR", ParamValue::Str("1000"))]),
+        );
+        params.insert(
+            "CT_DATA",
+            ParamType::Table(vec![
+                vec![("BANFN", ParamValue::Str("0010000001"))],
+                vec![("BANFM", ParamValue::Str("0010000002"))],
+            ]),
+        );
+        params
+    };
+
+    let client = RfcClient::new().unwrap();
+    let err = client
+        .execute::<()>("Z_TEST_CREATE_BAD_INPUT", params())
+        .unwrap_err();
+    assert_eq!(err.code(), RfcRc::RfcInvalidParameter);
+    assert!(err
+        .to_string()
+        .starts_with("Invalid input for Z_TEST_CREATE_BAD_INPUT: "));
+    let RfcError::InvalidInput { function, problems } = err else {
+        panic!("unexpected error {err:?}");
+    };
+    assert_eq!(function, "Z_TEST_CREATE_BAD_INPUT");
+    assert_eq!(problems.len(), 4, "{problems:?}");
+    assert_eq!(problems[0], "CT_DATA[1] has no field BANFM");
+    assert!(problems[1].starts_with("Parameter EV_EBELN of Z_TEST_CREATE_BAD_INPUT is EXPORTING"));
+    assert!(problems[2].starts_with("IV_COUNT: "));
+    assert!(problems[3].ends_with("has no parameter IV_UNKNOWN"));
+    assert!(mock.calls("Z_TEST_CREATE_BAD_INPUT").is_empty());
+
+    let ignored = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&ignored);
+    let client = client
+        .with_lenient_input(true)
+        .with_ignored_input(move |function, problems| {
+            seen.lock()
+                .unwrap()
+                .push((function.to_string(), problems.to_vec()));
+        });
+    client
+        .execute::<()>("Z_TEST_CREATE_BAD_INPUT", params())
+        .unwrap();
+    assert_eq!(
+        *ignored.lock().unwrap(),
+        [("Z_TEST_CREATE_BAD_INPUT".to_string(), problems)]
+    );
+    let calls = mock.calls("Z_TEST_CREATE_BAD_INPUT");
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0]["IV_ZSQDH"], "REQ1");
+    assert_eq!(calls[0]["CS_VENDOR"]["LIFNR"], "1000");
+    assert_eq!(calls[0]["CT_DATA"][0]["BANFN"], "0010000001");
+}
+
+#[test]
+fn derive_reads_numbers() {
+    let mock = mock();
+    mock.install(
+        &FunctionModule::new("Z_TEST_NUMBERS")
+            .exporting("EV_COUNT", DataType::Int)
+            .exporting("EV_RATE", DataType::Float)
+            .exporting("EV_POSNR", DataType::num(6))
+            .tables(
+                "ET_ITEMS",
+                DataType::table(vec![
+                    Field::new("POSNR", DataType::num(6)),
+                    Field::new("MENGE", DataType::bcd(7, 3)),
+                    Field::new("PRICE", DataType::Float),
+                ]),
+            )
+            .respond(
+                Response::new()
+                    .export("EV_COUNT", json!(-2))
+                    .export("EV_RATE", json!(0.25))
+                    .export("EV_POSNR", json!("000020"))
+                    .export(
+                        "ET_ITEMS",
+                        json!([
+                            {"POSNR": "000010", "MENGE": "1.500", "PRICE": 9.99},
+                            {"POSNR": "000020", "MENGE": "2", "PRICE": 0.5},
+                        ]),
+                    ),
+            ),
+    );
+
+    let client = RfcClient::new().unwrap();
+    let totals: Totals = client.execute("Z_TEST_NUMBERS", HashMap::new()).unwrap();
+    assert_eq!(totals.count, -2);
+    assert_eq!(totals.rate, 0.25);
+    assert_eq!(totals.posnr, 20);
+    assert_eq!(totals.items.len(), 2);
+    assert_eq!(totals.items[0].posnr, 10);
+    assert_eq!(totals.items[0].menge, 1.5);
+    assert_eq!(totals.items[0].price, 9.99_f32);
+    assert_eq!(totals.items[1].menge, 2.0);
+}
+
+#[test]
+fn derive_reads_nested_structures() {
+    let lines = DataType::table(vec![Field::new("POSNR", DataType::num(6))]);
+    let mock = mock();
+    mock.install(
+        &FunctionModule::new("Z_TEST_DEEP")
+            .exporting(
+                "ES_HEADER",
+                DataType::structure(vec![
+                    Field::new("EBELN", DataType::char(10)),
+                    Field::new(
+                        "ADDRESS",
+                        DataType::structure(vec![
+                            Field::new("CITY", DataType::char(20)),
+                            Field::new("POSTCODE", DataType::num(5)),
+                        ]),
+                    ),
+                    Field::new("LINES", lines.clone()),
+                ]),
+            )
+            .tables(
+                "ET_ORDERS",
+                DataType::table(vec![
+                    Field::new("EBELN", DataType::char(10)),
+                    Field::new(
+                        "RETURN",
+                        DataType::structure(vec![
+                            Field::new("TYPE", DataType::char(1)),
+                            Field::new("MESSAGE", DataType::char(50)),
+                        ]),
+                    ),
+                    Field::new("LINES", lines),
+                ]),
+            )
+            .respond(
+                Response::new()
+                    .export(
+                        "ES_HEADER",
+                        json!({
+                            "EBELN": "4500000001",
+                            "ADDRESS": {"CITY": "Walldorf", "POSTCODE": "69190"},
+                            "LINES": [{"POSNR": "10"}, {"POSNR": "20"}],
+                        }),
+                    )
+                    .export(
+                        "ET_ORDERS",
+                        json!([
+                            {
+                                "EBELN": "4500000001",
+                                "RETURN": {"TYPE": "S", "MESSAGE": "created"},
+                                "LINES": [{"POSNR": "10"}],
+                            },
+                            {
+                                "EBELN": "4500000002",
+                                "RETURN": {"TYPE": "E", "MESSAGE": "no vendor"},
+                                "LINES": [],
+                            },
+                        ]),
+                    ),
+            ),
+    );
+
+    let client = RfcClient::new().unwrap();
+    let deep: Deep = client.execute("Z_TEST_DEEP", HashMap::new()).unwrap();
+    assert_eq!(deep.header.ebeln, "4500000001");
+    assert_eq!(deep.header.address.city, "Walldorf");
+    assert_eq!(deep.header.address.postcode, 69190);
+    assert_eq!(deep.header.lines.len(), 2);
+    assert_eq!(deep.header.lines[1].posnr, 20);
+
+    assert_eq!(deep.orders.len(), 2);
+    assert_eq!(deep.orders[0].ret.r#type, "S");
+    assert_eq!(deep.orders[0].lines.len(), 1);
+    assert_eq!(deep.orders[0].lines[0].posnr, 10);
+    assert_eq!(deep.orders[1].ebeln, "4500000002");
+    assert_eq!(deep.orders[1].ret.r#type, "E");
+    assert_eq!(deep.orders[1].ret.message, "no vendor");
+    assert!(deep.orders[1].lines.is_empty());
+}
+
+#[test]
+fn derive_reads_typed_fields() {
+    let mock = mock();
+    mock.install(
+        &FunctionModule::new("Z_TEST_TYPED")
+            .exporting("EV_COUNT", DataType::Int8)
+            .exporting("EV_FLAG", DataType::char(1))
+            .exporting("EV_DATE", DataType::Date)
+            .exporting("EV_HASH", DataType::Xstring)
+            .exporting("EV_NOTE", DataType::char(20))
+            .tables(
+                "ET_ITEMS",
+                DataType::table(vec![
+                    Field::new("POSNR", DataType::num(6)),
+                    Field::new("DELETED", DataType::char(1)),
+                    Field::new("EEIND", DataType::Date),
+                ]),
+            )
+            .respond(
+                Response::new()
+                    .export("EV_COUNT", json!(-5_000_000_000_i64))
+                    .export("EV_FLAG", json!("X"))
+                    .export("EV_DATE", json!("20240229"))
+                    .export("EV_HASH", json!("CAFE"))
+                    .export(
+                        "ET_ITEMS",
+                        json!([
+                            {"POSNR": "000010", "DELETED": "X", "EEIND": "20240131"},
+                            {"POSNR": "000020", "DELETED": " ", "EEIND": "00000000"},
+                        ]),
+                    ),
+            ),
+    );
+
+    let client = RfcClient::new().unwrap();
+    let typed: Typed = client.execute("Z_TEST_TYPED", HashMap::new()).unwrap();
+    assert_eq!(typed.count, -5_000_000_000);
+    assert!(typed.flag);
+    assert_eq!(typed.date, NaiveDate::from_ymd_opt(2024, 2, 29).unwrap());
+    assert_eq!(typed.hash, vec![0xca, 0xfe]);
+    assert_eq!(typed.note, None);
+    assert_eq!(typed.items.len(), 2);
+    assert_eq!(typed.items[0].posnr, 10);
+    assert!(typed.items[0].deleted);
+    assert_eq!(
+        typed.items[0].delivery,
+        NaiveDate::from_ymd_opt(2024, 1, 31)
+    );
+    assert!(!typed.items[1].deleted);
+    assert_eq!(typed.items[1].delivery, None);
+}
+
+#[test]
+fn derive_attributes() {
+    let mock = mock();
+    mock.install(
+        &FunctionModule::new("Z_TEST_STOCK")
+            .importing("IV_MATNR", DataType::char(18))
+            .tables(
+                "ET_STOCK",
+                DataType::table(vec![
+                    Field::new("MATNR", DataType::char(18)),
+                    Field::new("WERKS", DataType::char(4)),
+                    Field::new("LGORT", DataType::char(4)),
+                    Field::new("LGPBE", DataType::char(10)),
+                    Field::new("EINDT", DataType::Date),
+                ]),
+            )
+            .respond(Response::new().export(
+                "ET_STOCK",
+                json!([
+                    {"MATNR": "M1", "WERKS": "1000", "LGORT": "A1", "LGPBE": "", "EINDT": "00000000"},
+                    {"MATNR": "M2", "WERKS": "2000", "LGORT": "B200", "LGPBE": "BIN-1", "EINDT": "20240131"},
+                ]),
+            )),
+    );
+
+    let client = RfcClient::new().unwrap();
+    let query = StockQuery {
+        iv_matnr: "m1".into(),
+        iv_not_there: "ignored".into(),
+        cache_key: "ignored".into(),
+    };
+    let result: StockResult = client.call("Z_TEST_STOCK", &query).unwrap();
+    assert_eq!(mock.calls("Z_TEST_STOCK")[0]["IV_MATNR"], "M1");
+
+    assert_eq!(result.ev_missing, None);
+    assert_eq!(result.ev_count, 0);
+    let stock = &result.et_stock;
+    assert_eq!(stock.len(), 2);
+    assert_eq!(stock[0].matnr, "M1");
+    assert_eq!(stock[0].plant, "P-1000");
+    assert_eq!(stock[0].lgort, "A1  ");
+    assert_eq!(stock[0].lgpbe, "");
+    assert_eq!(stock[1].lgpbe, "BIN-1     ");
+    assert_eq!(stock[0].eindt, NaiveDate::default());
+    assert_eq!(stock[0].charg, None);
+    assert!(stock[0].notes.is_empty());
+    assert_eq!(stock[1].lgort, "B200");
+    assert_eq!(
+        stock[1].eindt,
+        NaiveDate::from_ymd_opt(2024, 1, 31).unwrap()
+    );
+}
+
+#[test]
+fn with_module_reads_and_writes() {
+    let mock = mock();
+    let plant = || {
+        DataType::table(vec![
+            Field::new("WERKS", DataType::char(4)),
+            Field::new("NAME1", DataType::char(30)),
+        ])
+    };
+    mock.install(
+        &FunctionModule::new("Z_TEST_PLANTS")
+            .tables("IT_PLANTS", plant())
+            .tables("ET_PLANTS", plant())
+            .respond(Response::new().copy("ET_PLANTS", "IT_PLANTS")),
+    );
+
+    let client = RfcClient::new().unwrap();
+    let update = PlantUpdate {
+        it_plants: vec![Plant {
+            plant: "P-1000".into(),
+            name1: "Hamburg".into(),
+        }],
+    };
+    let result: PlantResult = client.call("Z_TEST_PLANTS", &update).unwrap();
+    assert_eq!(
+        mock.calls("Z_TEST_PLANTS")[0]["IT_PLANTS"][0]["WERKS"],
+        "1000"
+    );
+    assert_eq!(result.et_plants, update.it_plants);
+}
+
+#[test]
+fn derive_maps_enums() {
+    let mock = mock();
+    mock.install(
+        &FunctionModule::new("Z_TEST_RELEASE")
+            .importing("IV_TYPE", DataType::char(1))
+            .importing("IV_RELEASE", DataType::char(1))
+            .exporting("EV_TYPE", DataType::char(1))
+            .exporting("EV_RELEASE", DataType::char(1))
+            .exporting("EV_CODE", DataType::char(1))
+            .tables(
+                "ET_RETURN",
+                DataType::table(vec![
+                    Field::new("TYPE", DataType::char(1)),
+                    Field::new("MESSAGE", DataType::char(50)),
+                ]),
+            )
+            .respond(
+                Response::new()
+                    .copy("EV_TYPE", "IV_TYPE")
+                    .export("EV_CODE", json!("Q"))
+                    .export(
+                        "ET_RETURN",
+                        json!([
+                            {"TYPE": "S", "MESSAGE": "released"},
+                            {"TYPE": "I", "MESSAGE": "see long text"},
+                        ]),
+                    ),
+            ),
+    );
+
+    let client = RfcClient::new().unwrap();
+    let request = ReleaseRequest {
+        iv_type: MessageType::Warning,
+        iv_release: Some(Release::Blocked),
+    };
+    let result: ReleaseResult = client.call("Z_TEST_RELEASE", &request).unwrap();
+    let calls = mock.calls("Z_TEST_RELEASE");
+    assert_eq!(calls[0]["IV_TYPE"], "W");
+    assert_eq!(calls[0]["IV_RELEASE"], "B");
+
+    assert_eq!(result.ev_type, MessageType::Warning);
+    assert_eq!(result.ev_release, None);
+    assert_eq!(result.et_return[0].r#type, MessageType::Success);
+    assert_eq!(result.et_return[0].message, "released");
+    assert_eq!(result.et_return[1].r#type, MessageType::Other("I".into()));
+
+    let err = client
+        .call::<_, StrictRelease>("Z_TEST_RELEASE", &request)
+        .unwrap_err();
+    assert!(err.to_string().contains("'Q'"));
+    assert!(err.to_string().contains("Release"));
+}
+
+fn install_create(mock: &MockLib, name: &str) {
+    let address = DataType::structure(vec![
+        Field::new("CITY", DataType::char(20)),
+        Field::new("POSTCODE", DataType::num(5)),
+    ]);
+    let vendor = DataType::structure(vec![
+        Field::new("LIFNR", DataType::char(10)),
+        Field::new("ADDRESS", address),
+    ]);
+    mock.install(
+        &FunctionModule::new(name)
+            .importing("IV_ZSQDH", DataType::char(10))
+            .importing("IV_COUNT", DataType::Int)
+            .importing("IV_NOTE", DataType::char(20))
+            .changing("CS_VENDOR", vendor.clone())
+            .exporting("EV_EBELN", DataType::char(10))
+            .tables(
+                "CT_DATA",
+                DataType::table(vec![
+                    Field::new("BANFN", DataType::char(10)),
+                    Field::new("MENGE", DataType::bcd(7, 3)),
+                    Field::new("EEIND", DataType::Date),
+                    Field::new("VENDOR", vendor),
+                ]),
+            )
+            .respond(Response::new().export("EV_EBELN", json!("4500000001"))),
+    );
+}
+
+#[test]
+fn derive_writes_params() {
+    let mock = mock();
+    install_create(&mock, "Z_TEST_CREATE");
+
+    let vendor = |lifnr: &str, city: &str| Vendor {
+        lifnr: lifnr.into(),
+        address: PostalAddress {
+            city: city.into(),
+            postcode: 69190,
+        },
+    };
+    let request = CreateRequest {
+        zsqdh: "REQ1".into(),
+        count: 2,
+        note: None,
+        vendor: vendor("1000", "Walldorf"),
+        items: vec![
+            Requisition {
+                banfn: "0010000001".into(),
+                menge: 1.5,
+                delivery: NaiveDate::from_ymd_opt(2024, 1, 31).unwrap(),
+                vendor: vendor("1000", "Walldorf"),
+            },
+            Requisition {
+                banfn: "0010000002".into(),
+                menge: 3.0,
+                delivery: NaiveDate::from_ymd_opt(2024, 2, 29).unwrap(),
+                vendor: vendor("2000", "Berlin"),
+            },
+        ],
+    };
+
+    let client = RfcClient::new().unwrap();
+    let created: Created = client.call("Z_TEST_CREATE", &request).unwrap();
+    assert_eq!(created.ev_ebeln, "4500000001");
+
+    let calls = mock.calls("Z_TEST_CREATE");
+    assert_eq!(calls.len(), 1);
+    let call = &calls[0];
+    assert_eq!(call["IV_ZSQDH"], "REQ1");
+    assert_eq!(call["IV_COUNT"], 2);
+    assert_eq!(call["IV_NOTE"], "");
+    assert_eq!(call["CS_VENDOR"]["LIFNR"], "1000");
+    assert_eq!(call["CS_VENDOR"]["ADDRESS"]["CITY"], "Walldorf");
+    assert_eq!(call["CS_VENDOR"]["ADDRESS"]["POSTCODE"], "69190");
+    assert_eq!(call["CT_DATA"].as_array().unwrap().len(), 2);
+    assert_eq!(call["CT_DATA"][0]["BANFN"], "0010000001");
+    assert_eq!(call["CT_DATA"][0]["MENGE"], "1.500");
+    assert_eq!(call["CT_DATA"][0]["EEIND"], "20240131");
+    assert_eq!(call["CT_DATA"][1]["BANFN"], "0010000002");
+    assert_eq!(call["CT_DATA"][1]["EEIND"], "20240229");
+    assert_eq!(call["CT_DATA"][1]["VENDOR"]["ADDRESS"]["CITY"], "Berlin");
+}
+
+#[test]
+fn append_rows_from_fills_large_tables() {
+    let mock = mock();
+    install_create(&mock, "Z_TEST_CREATE_BULK");
+
+    let request = BulkRequest {
+        items: (0..20_000)
+            .map(|i| Requisition {
+                banfn: format!("{:010}", i),
+                menge: 1.0,
+                delivery: NaiveDate::from_ymd_opt(2024, 1, 31).unwrap(),
+                vendor: Vendor {
+                    lifnr: format!("{}", i % 7),
+                    address: PostalAddress {
+                        city: "Walldorf".into(),
+                        postcode: 69190,
+                    },
+                },
+            })
+            .collect(),
+    };
+
+    let client = RfcClient::new().unwrap();
+    let _: () = client.call("Z_TEST_CREATE_BULK", &request).unwrap();
+
+    let calls = mock.calls("Z_TEST_CREATE_BULK");
+    let rows = calls[0]["CT_DATA"].as_array().unwrap();
+    assert_eq!(rows.len(), 20_000);
+    assert_eq!(rows[0]["BANFN"], "0000000000");
+    assert_eq!(rows[19_999]["BANFN"], "0000019999");
+    assert_eq!(rows[19_998]["VENDOR"]["LIFNR"], "6");
+    assert_eq!(rows[19_999]["VENDOR"]["ADDRESS"]["CITY"], "Walldorf");
+}
+
+#[test]
+fn invalid_params_fail_before_the_call() {
+    let mock = mock();
+    install_create(&mock, "Z_TEST_CREATE_RO");
+
+    let client = RfcClient::new().unwrap();
+    let err = client
+        .call::<_, Created>(
+            "Z_TEST_CREATE_RO",
+            &WritesExport {
+                ebeln: "4500000001".into(),
+            },
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("Read-only parameter"));
+    assert!(mock.calls("Z_TEST_CREATE_RO").is_empty());
+}
+
+#[test]
+fn check_reports_every_mismatch() {
+    let mock = mock();
+    install_create(&mock, "Z_TEST_CREATE_CHECKED");
+    let address = DataType::structure(vec![Field::new("POSTCODE", DataType::num(5))]);
+    mock.install(
+        &FunctionModule::new("Z_TEST_CREATE_OLD")
+            .importing("IV_COUNT", DataType::Int)
+            .importing("EV_EBELN", DataType::char(10))
+            .tables(
+                "CS_VENDOR",
+                DataType::table(vec![Field::new("LIFNR", DataType::char(10))]),
+            )
+            .tables(
+                "CT_DATA",
+                DataType::table(vec![
+                    Field::new("BANFN", DataType::char(10)),
+                    Field::new("MENGE", DataType::bcd(7, 3)),
+                    Field::new(
+                        "VENDOR",
+                        DataType::structure(vec![
+                            Field::new("LIFNR", DataType::char(10)),
+                            Field::new("ADDRESS", address),
+                        ]),
+                    ),
+                ]),
+            ),
+    );
+
+    let client = RfcClient::new().unwrap();
+    let mismatches = client
+        .check::<CreateRequest, Created>("Z_TEST_CREATE_CHECKED")
+        .unwrap();
+    assert!(mismatches.is_empty(), "{mismatches:?}");
+
+    let mismatches = client
+        .check::<CreateRequest, Created>("Z_TEST_CREATE_OLD")
+        .unwrap();
+    assert_eq!(
+        mismatches,
+        [
+            "Function module Z_TEST_CREATE_OLD has no parameter IV_ZSQDH",
+            "Function module Z_TEST_CREATE_OLD has no parameter IV_NOTE",
+            "CS_VENDOR is of type Table, not a structure",
+            "CT_DATA has no field EEIND",
+            "ADDRESS has no field CITY",
+            "Parameter EV_EBELN of Z_TEST_CREATE_OLD is IMPORTING and cannot be read",
+        ]
+    );
+    assert!(mock.calls("Z_TEST_CREATE_OLD").is_empty());
+
+    let mismatches = client
+        .check::<(), CreateResult>("Z_TEST_CREATE_OLD")
+        .unwrap();
+    assert_eq!(
+        mismatches,
+        [
+            "Parameter EV_EBELN of Z_TEST_CREATE_OLD is IMPORTING and cannot be read",
+            "Function module Z_TEST_CREATE_OLD has no parameter EV_TYPE",
+            "CT_DATA has no field EBELN",
+            "CT_DATA has no field TYPE",
+            "CT_DATA has no field MSG",
+        ]
+    );
+}
+
+#[test]
+fn missing_fields_are_named() {
+    let mock = mock();
+    mock.install(
+        &FunctionModule::new("Z_TEST_MISSING_FIELD")
+            .exporting("EV_EBELN", DataType::char(10))
+            .exporting("EV_TYPE", DataType::char(1))
+            .tables(
+                "CT_DATA",
+                DataType::table(vec![
+                    Field::new("EBELN", DataType::char(10)),
+                    Field::new("TYPE", DataType::char(1)),
+                ]),
+            )
+            .respond(Response::new().export("CT_DATA", json!([{"EBELN": "1", "TYPE": "S"}]))),
+    );
+    mock.install(
+        &FunctionModule::new("Z_TEST_MISSING_PARAM")
+            .exporting("EV_EBELN", DataType::char(10))
+            .respond(Response::new()),
+    );
+
+    let client = RfcClient::new().unwrap();
+    let err = client
+        .execute::<CreateResult>("Z_TEST_MISSING_FIELD", HashMap::new())
+        .unwrap_err();
+    assert_eq!(err.to_string(), "CT_DATA has no field MSG");
+
+    let err = client
+        .execute::<CreateResult>("Z_TEST_MISSING_PARAM", HashMap::new())
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Function module Z_TEST_MISSING_PARAM has no parameter EV_TYPE"
+    );
+}
+
+#[test]
+fn unknown_function_is_reported() {
+    mock();
+    let client = RfcClient::new().unwrap();
+    let err = client
+        .execute::<CreateResult>("Z_TEST_DOES_NOT_EXIST", HashMap::new())
+        .unwrap_err();
+    assert!(matches!(err, RfcError::NotFound { .. }), "{err:?}");
+    assert_eq!(err.code(), RfcRc::RfcNotFound);
+    assert!(err.to_string().contains("Z_TEST_DOES_NOT_EXIST"));
+}
+
+#[test]
+fn abap_exceptions_are_reported() {
+    let mock = mock();
+    mock.install(
+        &FunctionModule::new("Z_TEST_RAISE")
+            .exporting("EV_EBELN", DataType::char(10))
+            .respond(Response::new().error(ScriptedError::new(
+                MockRc::AbapException,
+                "NOT_FOUND",
+                "Purchase order not found",
+            ))),
+    );
+
+    let client = RfcClient::new().unwrap();
+    let err = client
+        .execute::<CreateResult>("Z_TEST_RAISE", HashMap::new())
+        .unwrap_err();
+    assert_eq!(err.code(), RfcRc::RfcAbapException);
+    assert_eq!(err.group(), RfcErrorGroup::AbapApplicationFailure);
+    assert_eq!(
+        err,
+        RfcError::AbapException {
+            code: RfcRc::RfcAbapException,
+            key: "NOT_FOUND".into(),
+            message: "Purchase order not found".into(),
+            abap_message: None,
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "Purchase order not found (exception NOT_FOUND)"
+    );
+}
+
+#[test]
+fn abap_messages_carry_t100_fields() {
+    let mock = mock();
+    mock.install(
+        &FunctionModule::new("Z_TEST_MESSAGE")
+            .exporting("EV_EBELN", DataType::char(10))
+            .respond(Response::new().error(ScriptedError {
+                abap_msg_class: "ZMM".into(),
+                abap_msg_type: "E".into(),
+                abap_msg_number: "042".into(),
+                abap_msg_v1: "4500000001".into(),
+                abap_msg_v2: "1000".into(),
+                ..ScriptedError::new(
+                    MockRc::AbapMessage,
+                    "",
+                    "Purchase order 4500000001 is locked by 1000",
+                )
+            })),
+    );
+
+    let client = RfcClient::new().unwrap();
+    let err = client
+        .execute::<()>("Z_TEST_MESSAGE", HashMap::new())
+        .unwrap_err();
+    let RfcError::AbapMessage {
+        message,
+        abap_message,
+    } = &err
+    else {
+        panic!("unexpected error {err:?}");
+    };
+    assert_eq!(message, "Purchase order 4500000001 is locked by 1000");
+    assert_eq!(abap_message.class, "ZMM");
+    assert_eq!(abap_message.r#type, "E");
+    assert_eq!(abap_message.number, "042");
+    assert_eq!(abap_message.v1, "4500000001");
+    assert_eq!(abap_message.v2, "1000");
+    assert_eq!(abap_message.v3, "");
+    assert_eq!(
+        err.to_string(),
+        "Purchase order 4500000001 is locked by 1000 (message E042(ZMM))"
+    );
+
+    fn is_error<E: std::error::Error + Send + Sync + 'static>(_: &E) {}
+    is_error(&err);
+}
+
+#[test]
+fn library_errors_convert_by_code() {
+    let err = RfcError::from(RfcErrorInfo::conversion("too long"));
+    assert_eq!(
+        err,
+        RfcError::Conversion {
+            code: RfcRc::RfcConversionFailure,
+            message: "too long".into(),
+        }
+    );
+    assert_eq!(err.group(), RfcErrorGroup::ExternalRuntimeFailure);
+
+    let err = RfcError::from(RfcErrorInfo::with_code(RfcRc::RfcClosed, "closed"));
+    assert!(matches!(err, RfcError::Communication { .. }));
+    assert_eq!(err.code(), RfcRc::RfcClosed);
+
+    let err = RfcError::from(RfcErrorInfo::custom("logic error"));
+    assert_eq!(err.code(), RfcRc::RfcUnknownError);
+    assert_eq!(err.to_string(), "logic error");
+
+    // The codes and groups as the library reports them
+    let mock = mock();
+    let scripted = [
+        MockRc::AuthorizationFailure,
+        MockRc::AuthenticationFailure,
+        MockRc::CryptolibFailure,
+        MockRc::LockingFailure,
+        MockRc::Timeout,
+        MockRc::AbapMessage,
+    ];
+    let module = scripted.iter().fold(
+        FunctionModule::new("Z_TEST_LIBRARY_ERRORS"),
+        |module, &code| {
+            module.respond(Response::new().error(ScriptedError::new(code, "KEY", "failed")))
+        },
+    );
+    mock.install(&module);
+    let client = RfcClient::new().unwrap();
+    let errors: Vec<RfcError> = scripted
+        .iter()
+        .map(|_| {
+            client
+                .execute::<()>("Z_TEST_LIBRARY_ERRORS", HashMap::new())
+                .unwrap_err()
+        })
+        .collect();
+    let failed = || "failed".to_string();
+    assert_eq!(
+        errors[0],
+        RfcError::Authorization {
+            code: RfcRc::RfcAuthorizationFailure,
+            message: failed(),
+        }
+    );
+    assert_eq!(
+        errors[1],
+        RfcError::Authorization {
+            code: RfcRc::RfcAuthenticationFailure,
+            message: failed(),
+        }
+    );
+    assert_eq!(
+        errors[2],
+        RfcError::Other {
+            code: RfcRc::RfcCryptolibFailure,
+            group: RfcErrorGroup::CryptolibFailure,
+            key: "KEY".into(),
+            message: failed(),
+        }
+    );
+    assert_eq!(
+        errors[3],
+        RfcError::Other {
+            code: RfcRc::RfcLockingFailure,
+            group: RfcErrorGroup::LockingFailure,
+            key: "KEY".into(),
+            message: failed(),
+        }
+    );
+    assert_eq!(
+        errors[4],
+        RfcError::Communication {
+            code: RfcRc::RfcTimeout,
+            message: failed(),
+        }
+    );
+    assert!(matches!(errors[5], RfcError::AbapMessage { .. }));
+    let groups: Vec<_> = errors.iter().map(RfcError::group).collect();
+    assert_eq!(
+        groups,
+        [
+            RfcErrorGroup::ExternalAuthorizationFailure,
+            RfcErrorGroup::ExternalAuthenticationFailure,
+            RfcErrorGroup::CryptolibFailure,
+            RfcErrorGroup::LockingFailure,
+            RfcErrorGroup::CommunicationFailure,
+            RfcErrorGroup::AbapRuntimeFailure,
+        ]
+    );
+}
+
+#[test]
+fn codes_are_grouped_like_the_library_does() {
+    use RfcErrorGroup::*;
+    let groups = [
+        (RfcRc::RfcOk, Ok),
+        (RfcRc::RfcCommunicationFailure, CommunicationFailure),
+        (RfcRc::RfcLogonFailure, LogonFailure),
+        (RfcRc::RfcAbapRuntimeFailure, AbapRuntimeFailure),
+        (RfcRc::RfcAbapMessage, AbapRuntimeFailure),
+        (RfcRc::RfcAbapException, AbapApplicationFailure),
+        (RfcRc::RfcClosed, CommunicationFailure),
+        (RfcRc::RfcCanceled, ExternalRuntimeFailure),
+        (RfcRc::RfcTimeout, CommunicationFailure),
+        (RfcRc::RfcMemoryInsufficient, ExternalRuntimeFailure),
+        (RfcRc::RfcVersionMismatcH, ExternalRuntimeFailure),
+        (RfcRc::RfcInvalidProtocol, ExternalRuntimeFailure),
+        (RfcRc::RfcSerializationFailure, ExternalRuntimeFailure),
+        (RfcRc::RfcInvalidHandle, ExternalRuntimeFailure),
+        (RfcRc::RfcRetry, ExternalRuntimeFailure),
+        (RfcRc::RfcExternalFailure, ExternalRuntimeFailure),
+        (RfcRc::RfcExecuted, ExternalRuntimeFailure),
+        (RfcRc::RfcNotFound, ExternalRuntimeFailure),
+        (RfcRc::RfcNotSupported, ExternalRuntimeFailure),
+        (RfcRc::RfcIllegalState, ExternalRuntimeFailure),
+        (RfcRc::RfcInvalidParameter, ExternalRuntimeFailure),
+        (RfcRc::RfcCodepageConversionFailure, ExternalRuntimeFailure),
+        (RfcRc::RfcConversionFailure, ExternalRuntimeFailure),
+        (RfcRc::RfcBufferTooSmall, ExternalRuntimeFailure),
+        (RfcRc::RfcTableMoveBof, ExternalRuntimeFailure),
+        (RfcRc::RfcTableMoveEof, ExternalRuntimeFailure),
+        (RfcRc::RfcStartSapguiFailure, ExternalRuntimeFailure),
+        (RfcRc::RfcAbapClassException, AbapApplicationFailure),
+        (RfcRc::RfcUnknownError, ExternalRuntimeFailure),
+        (RfcRc::RfcAuthorizationFailure, ExternalAuthorizationFailure),
+        (
+            RfcRc::RfcAuthenticationFailure,
+            ExternalAuthenticationFailure,
+        ),
+        (RfcRc::RfcCryptolibFailure, CryptolibFailure),
+        (RfcRc::RfcIoFailure, ExternalRuntimeFailure),
+        (RfcRc::RfcLockingFailure, LockingFailure),
+    ];
+    for (code, group) in groups {
+        assert_eq!(code.group(), group, "{code:?}");
+    }
+}
+
+/// The derives only use what they name with its full path, so they
+/// compile without any imports and next to a `Result` of its own
+#[allow(dead_code)]
+mod hygiene {
+    #![deny(unused_mut)]
+
+    type Result<T> = std::result::Result<T, String>;
+
+    #[derive(rsrfc::RfcParams)]
+    struct Params {
+        #[sap(alias = "IV_TEXT")]
+        text: String,
+        #[sap(alias = "IT_ROWS")]
+        rows: Vec<Row>,
+    }
+
+    #[derive(rsrfc::RfcRow, rsrfc::RfcTable)]
+    struct Row {
+        #[sap(alias = "TEXT")]
+        text: String,
+        #[sap(alias = "HEADER")]
+        header: Header,
+    }
+
+    #[derive(rsrfc::RfcRow, rsrfc::RfcStructure)]
+    struct Header {
+        #[sap(alias = "ID")]
+        id: String,
+        #[sap(alias = "STATUS")]
+        status: Status,
+    }
+
+    #[derive(rsrfc::RfcResult)]
+    struct Response {
+        #[sap(alias = "EV_STATUS")]
+        status: Status,
+        #[sap(alias = "ET_ROWS")]
+        rows: Vec<Row>,
+    }
+
+    #[derive(rsrfc::RfcEnum)]
+    enum Status {
+        #[sap(value = "A")]
+        Active,
+        Other(String),
+    }
+}
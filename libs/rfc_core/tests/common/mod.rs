@@ -0,0 +1,26 @@
+//! Shared setup for the tests that run against the libsapnwrfc stand-in.
+#![allow(dead_code, unused_imports)]
+
+use std::sync::Once;
+
+pub use sapnwrfc::RfcRc as MockRc;
+pub use sapnwrfc::{DataType, Field, FunctionModule, MockLib, Response, ScriptedError};
+pub use serde_json::json;
+
+/// User the default connection parameters log on with
+pub const USER: &str = "TESTER";
+
+/// Load the stand-in library and point the default connection parameters
+/// at it.
+pub fn mock() -> MockLib {
+    static ENV: Once = Once::new();
+    ENV.call_once(|| {
+        std::env::set_var("SAP_ASHOST", "mock");
+        std::env::set_var("SAP_SYSNR", "00");
+        std::env::set_var("SAP_CLIENT", "100");
+        std::env::set_var("SAP_USER", USER);
+        std::env::set_var("SAP_PASSWD", "secret");
+        std::env::set_var("SAP_LANG", "EN");
+    });
+    MockLib::load().expect("libsapnwrfc stand-in not found, is rfc_mock a dev-dependency?")
+}
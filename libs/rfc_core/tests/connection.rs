@@ -0,0 +1,46 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use common::*;
+use rsrfc::*;
+
+fn connect(lib: &RfcLib, user: &str) -> RfcConnection {
+    let params = RfcConnectionParameters {
+        ashost: Some("mock".into()),
+        sysnr: Some("00".into()),
+        client: Some("100".into()),
+        user: Some(user.into()),
+        passwd: Some("secret".into()),
+        ..Default::default()
+    };
+    RfcConnection::new(&params, lib).unwrap()
+}
+
+#[test]
+fn connection_outlives_lib_handle_and_moves_to_thread() {
+    let mock = mock();
+    let conn = {
+        let lib = RfcLib::new().unwrap();
+        connect(&lib, "MOVER")
+    };
+    thread::spawn(move || conn.ping().unwrap()).join().unwrap();
+    assert_eq!(mock.logons("MOVER").open, 0);
+}
+
+#[test]
+fn connection_shared_through_mutex() {
+    let _mock = mock();
+    let lib = RfcLib::new().unwrap();
+    let conn = Arc::new(Mutex::new(connect(&lib, "SHARER")));
+    let workers: Vec<_> = (0..4)
+        .map(|_| {
+            let conn = Arc::clone(&conn);
+            thread::spawn(move || conn.lock().unwrap().ping().unwrap())
+        })
+        .collect();
+    for w in workers {
+        w.join().unwrap();
+    }
+}
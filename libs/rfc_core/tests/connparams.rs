@@ -0,0 +1,81 @@
+mod common;
+
+use common::*;
+use rsrfc::*;
+
+fn load_balancing() -> RfcConnectionParameters {
+    RfcConnectionParameters::default()
+        .with_mshost("ms.example.com")
+        .with_sysid("PRD")
+        .with_group("SPACE")
+        .with_client("100")
+        .with_user("LB_USER")
+        .with_passwd("secret")
+        .with_trace(2)
+        .with_snc_mode(false)
+}
+
+#[test]
+fn parameters_reach_the_library() {
+    let mock = mock();
+    let rfc_lib = RfcLib::new().unwrap();
+    let conn = RfcConnection::new(&load_balancing(), &rfc_lib).unwrap();
+    drop(conn);
+
+    let logons = mock.logons("LB_USER");
+    assert_eq!(logons.total, 1);
+    let sent = &logons.last_parameters;
+    assert_eq!(sent["mshost"], json!("ms.example.com"));
+    assert_eq!(sent["sysid"], json!("PRD"));
+    assert_eq!(sent["group"], json!("SPACE"));
+    assert_eq!(sent["trace"], json!("2"));
+    assert_eq!(sent["snc_mode"], json!("0"));
+    assert!(!sent.contains_key("ashost"));
+}
+
+#[test]
+fn contradicting_parameters_are_rejected() {
+    let both = RfcConnectionParameters {
+        ashost: Some("app.example.com".into()),
+        sysnr: Some("00".into()),
+        ..load_balancing()
+    };
+    let err = both.validate().unwrap_err();
+    assert!(err.to_string().contains("ashost and mshost"), "{}", err);
+
+    let sso_and_password = RfcConnectionParameters {
+        mysapsso2: Some("AjQxMDMBABhKAE...".into()),
+        ..load_balancing()
+    };
+    assert!(sso_and_password.validate().is_err());
+
+    let group_without_mshost = RfcConnectionParameters {
+        mshost: None,
+        ashost: Some("app.example.com".into()),
+        sysnr: Some("00".into()),
+        ..load_balancing()
+    };
+    assert!(group_without_mshost.validate().is_err());
+
+    let destination = RfcConnectionParameters {
+        dest: Some("PRD".into()),
+        ..Default::default()
+    };
+    assert!(destination.validate().is_ok());
+}
+
+#[test]
+fn new_takes_the_former_fields() {
+    let params =
+        RfcConnectionParameters::new("app.example.com", "00", "100", "USER", "secret", "EN")
+            .with_saprouter("/H/router.example.com/S/3299/H/");
+    assert!(params.validate().is_ok());
+    assert_eq!(params.ashost.as_deref(), Some("app.example.com"));
+    assert_eq!(params.sysnr.as_deref(), Some("00"));
+    assert_eq!(params.client.as_deref(), Some("100"));
+    assert_eq!(params.user.as_deref(), Some("USER"));
+    assert_eq!(params.passwd.as_deref(), Some("secret"));
+    assert_eq!(params.lang.as_deref(), Some("EN"));
+    assert!(params.saprouter.is_some());
+    assert!(params.mshost.is_none());
+}
@@ -0,0 +1,102 @@
+#![cfg(feature = "rust_decimal")]
+#![allow(clippy::result_large_err)]
+
+use rsrfc::decfloat::{DECFLOAT16, DECFLOAT34};
+use rust_decimal::Decimal;
+
+// Bit patterns of IEEE 754-2008 decimal64 and decimal128 in DPD encoding
+const D64_ZERO: u128 = 0x2238_0000_0000_0000;
+const D64_ONE: u128 = 0x2238_0000_0000_0001;
+const D64_MINUS_ONE: u128 = 0xA238_0000_0000_0001;
+const D64_ONE_TENTH: u128 = 0x2234_0000_0000_0001;
+/// 9.999999999999999E+384, every declet 0x0FF
+const D64_MAX: u128 = 0x77FC_FF3F_CFF3_FCFF;
+const D64_INFINITY: u128 = 0x7800_0000_0000_0000;
+const D64_NAN: u128 = 0x7C00_0000_0000_0000;
+
+const D128_ZERO: u128 = 0x2208_0000_0000_0000_0000_0000_0000_0000;
+const D128_ONE: u128 = 0x2208_0000_0000_0000_0000_0000_0000_0001;
+const D128_MINUS_ONE: u128 = 0xA208_0000_0000_0000_0000_0000_0000_0001;
+/// 9.999999999999999999999999999999999E+6144
+const D128_MAX: u128 = 0x77FF_CFF3_FCFF_3FCF_F3FC_FF3F_CFF3_FCFF;
+
+#[test]
+fn decimal64_known_values() {
+    let cases = [
+        (Decimal::ZERO, D64_ZERO),
+        (Decimal::ONE, D64_ONE),
+        (Decimal::NEGATIVE_ONE, D64_MINUS_ONE),
+        (Decimal::new(1, 1), D64_ONE_TENTH),
+    ];
+    for (value, bits) in cases {
+        assert_eq!(
+            DECFLOAT16.encode_decimal(value, "P").unwrap(),
+            bits,
+            "{value}"
+        );
+        assert_eq!(DECFLOAT16.decode_decimal(bits, "P").unwrap(), value);
+    }
+
+    let max = 9_999_999_999_999_999;
+    assert_eq!(DECFLOAT16.encode(false, max, 369), Some(D64_MAX));
+    assert_eq!(DECFLOAT16.decode(D64_MAX), Some((false, max, 369)));
+    // Beyond the digits or the exponent range of the format
+    assert_eq!(DECFLOAT16.encode(false, max + 1, 0), None);
+    assert_eq!(DECFLOAT16.encode(false, 1, 370), None);
+    // Too large for a Decimal
+    assert!(DECFLOAT16.decode_decimal(D64_MAX, "P").is_err());
+}
+
+#[test]
+fn decimal128_known_values() {
+    let cases = [
+        (Decimal::ZERO, D128_ZERO),
+        (Decimal::ONE, D128_ONE),
+        (Decimal::NEGATIVE_ONE, D128_MINUS_ONE),
+    ];
+    for (value, bits) in cases {
+        assert_eq!(
+            DECFLOAT34.encode_decimal(value, "P").unwrap(),
+            bits,
+            "{value}"
+        );
+        assert_eq!(DECFLOAT34.decode_decimal(bits, "P").unwrap(), value);
+    }
+
+    let max = 10_u128.pow(34) - 1;
+    assert_eq!(DECFLOAT34.encode(false, max, 6111), Some(D128_MAX));
+    assert_eq!(DECFLOAT34.decode(D128_MAX), Some((false, max, 6111)));
+}
+
+#[test]
+fn every_declet_round_trips() {
+    for digits in 0..1000 {
+        let bits = DECFLOAT16.encode(false, digits, 0).unwrap();
+        assert_eq!(bits & !0x3ff, D64_ZERO, "{digits}");
+        assert_eq!(DECFLOAT16.decode(bits), Some((false, digits, 0)));
+    }
+    // 999 is the only declet of 0xFF
+    assert_eq!(DECFLOAT16.encode(false, 999, 0), Some(D64_ZERO | 0x0ff));
+}
+
+#[test]
+fn non_canonical_declets_are_read() {
+    // With all three digits 8 or 9 the two leading bits of a declet are
+    // ignored, so 0x1FF, 0x2FF and 0x3FF are 999 as well
+    for declet in [0x1ff, 0x2ff, 0x3ff] {
+        assert_eq!(
+            DECFLOAT16.decode(D64_ZERO | declet),
+            Some((false, 999, 0)),
+            "{declet:#x}"
+        );
+    }
+    // 0x16E is the non-canonical form of 888
+    assert_eq!(DECFLOAT16.decode(D64_ZERO | 0x16e), Some((false, 888, 0)));
+}
+
+#[test]
+fn special_values_are_rejected() {
+    assert_eq!(DECFLOAT16.decode(D64_INFINITY), None);
+    assert_eq!(DECFLOAT16.decode(D64_NAN), None);
+    assert!(DECFLOAT16.decode_decimal(D64_NAN, "P").is_err());
+}
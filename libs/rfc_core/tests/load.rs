@@ -0,0 +1,106 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use rsrfc::error::{RfcError, RfcLoadError, RfcRc};
+use rsrfc::{RfcLib, RfcVersion, RFC_LIB_NAME, RFC_LIB_PATH_ENV};
+
+/// The stand-in library is built next to the test binaries
+fn mock_dir() -> PathBuf {
+    std::env::current_exe()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .to_path_buf()
+}
+
+#[test]
+fn from_path_accepts_file_and_directory() {
+    assert!(RfcLib::from_path(mock_dir()).is_ok());
+    assert!(RfcLib::from_path(mock_dir().join(RFC_LIB_NAME)).is_ok());
+}
+
+#[test]
+fn load_error_lists_every_attempt() {
+    let missing = PathBuf::from("/nonexistent/sdk").join(RFC_LIB_NAME);
+    let err = RfcLib::from_path(&missing).err().unwrap();
+    let RfcLoadError::NotLoaded(attempts) = &err else {
+        panic!("unexpected error {:?}", err);
+    };
+    assert_eq!(attempts.len(), 1);
+    assert_eq!(attempts[0].path, missing);
+    assert!(err.to_string().contains("/nonexistent/sdk"));
+
+    // The client error keeps the attempts
+    let message = err.to_string();
+    let err = RfcError::from(err);
+    assert_eq!(err.code(), RfcRc::RfcNotFound);
+    assert_eq!(err.to_string(), message);
+    let RfcError::LibraryLoad { error } = &err else {
+        panic!("unexpected error {:?}", err);
+    };
+    let RfcLoadError::NotLoaded(attempts) = error.as_ref() else {
+        panic!("unexpected error {:?}", error);
+    };
+    assert_eq!(attempts[0].path, missing);
+}
+
+#[test]
+fn search_path_order() {
+    let var = |name: &str| match name {
+        RFC_LIB_PATH_ENV => Some(OsString::from("/opt/sdk/7.50")),
+        "SAPNWRFC_HOME" => Some(OsString::from("/usr/sap/nwrfcsdk")),
+        _ => None,
+    };
+    let paths = RfcLib::search_path(Some(&mock_dir()), var);
+    assert_eq!(
+        paths,
+        [
+            mock_dir().join(RFC_LIB_NAME),
+            PathBuf::from("/opt/sdk/7.50"),
+            PathBuf::from(RFC_LIB_NAME),
+            Path::new("/usr/sap/nwrfcsdk/lib").join(RFC_LIB_NAME),
+            mock_dir().join(RFC_LIB_NAME),
+        ]
+    );
+
+    // Unset and empty variables are skipped
+    let paths = RfcLib::search_path(None, |name| (name == RFC_LIB_PATH_ENV).then(OsString::new));
+    assert_eq!(
+        paths,
+        [PathBuf::from(RFC_LIB_NAME), mock_dir().join(RFC_LIB_NAME)]
+    );
+}
+
+#[test]
+fn version_requirement_is_enforced() {
+    let rfc_lib = RfcLib::from_path(mock_dir()).unwrap();
+    let version = rfc_lib.version();
+    assert_eq!(version, RfcVersion::new(7500, 0, 12));
+
+    let rfc_lib = rfc_lib
+        .require_version(RfcVersion::new(7500, 0, 5))
+        .unwrap();
+    match rfc_lib.require_version(RfcVersion::new(7500, 0, 13)) {
+        Err(RfcLoadError::VersionTooOld { found, required }) => {
+            assert_eq!(found, version);
+            assert_eq!(required, RfcVersion::new(7500, 0, 13));
+        }
+        _ => panic!("7500.0.12 must not satisfy 7500.0.13"),
+    }
+}
+
+#[test]
+fn connections_outlive_the_library_handle() {
+    let params = rsrfc::RfcConnectionParameters {
+        ashost: Some("mock".into()),
+        sysnr: Some("00".into()),
+        client: Some("100".into()),
+        user: Some("RELOAD".into()),
+        passwd: Some("secret".into()),
+        ..Default::default()
+    };
+    let rfc_lib = RfcLib::from_path(mock_dir()).unwrap();
+    let conn = rsrfc::RfcConnection::new(&params, &rfc_lib).unwrap();
+    drop(rfc_lib);
+    conn.ping().unwrap();
+}
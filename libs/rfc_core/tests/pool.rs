@@ -0,0 +1,126 @@
+mod common;
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use common::*;
+use rsrfc::{error::*, *};
+
+#[derive(Debug, RfcResult)]
+struct Pong {
+    #[sap(alias = "EV_SYSID")]
+    sysid: String,
+}
+
+fn install_ping(mock: &MockLib) {
+    mock.install(
+        &FunctionModule::new("Z_POOL_PING")
+            .exporting("EV_SYSID", DataType::char(8))
+            .respond(Response::new().export("EV_SYSID", json!("DEV"))),
+    );
+}
+
+fn pool(user: &str, config: RfcPoolConfig) -> RfcPool {
+    let params = RfcConnectionParameters {
+        ashost: Some("mock".into()),
+        sysnr: Some("00".into()),
+        client: Some("100".into()),
+        user: Some(user.into()),
+        passwd: Some("secret".into()),
+        ..Default::default()
+    };
+    RfcPool::new(RfcLib::new().unwrap(), params, config)
+}
+
+#[test]
+fn client_reuses_connections() {
+    let mock = mock();
+    install_ping(&mock);
+    let before = mock.logons(USER).total;
+
+    let client = RfcClient::new().unwrap();
+    for _ in 0..3 {
+        let pong: Pong = client.execute("Z_POOL_PING", HashMap::new()).unwrap();
+        assert_eq!(pong.sysid, "DEV");
+    }
+    assert_eq!(client.pool().size(), 1);
+    assert_eq!(mock.logons(USER).total - before, 1);
+}
+
+#[test]
+fn broken_connections_are_replaced() {
+    let mock = mock();
+    mock.install(
+        &FunctionModule::new("Z_POOL_CLOSE")
+            .respond(Response::new().close())
+            .respond(Response::new()),
+    );
+    let pool = pool("POOL_BROKEN", RfcPoolConfig::default());
+    {
+        let conn = pool.checkout().unwrap();
+        conn.get_function("Z_POOL_CLOSE").unwrap().call().unwrap();
+    }
+    {
+        let conn = pool.checkout().unwrap();
+        conn.get_function("Z_POOL_CLOSE").unwrap().call().unwrap();
+    }
+    assert_eq!(mock.logons("POOL_BROKEN").total, 2);
+    assert_eq!(pool.size(), 1);
+}
+
+#[test]
+fn idle_connections_expire() {
+    let mock = mock();
+    let pool = pool(
+        "POOL_IDLE",
+        RfcPoolConfig {
+            idle_timeout: Duration::ZERO,
+            ..Default::default()
+        },
+    );
+    drop(pool.checkout().unwrap());
+    assert_eq!(pool.idle(), 0);
+    drop(pool.checkout().unwrap());
+    let logons = mock.logons("POOL_IDLE");
+    assert_eq!(logons.total, 2);
+    assert_eq!(logons.open, 0);
+}
+
+#[test]
+fn pool_is_shared_between_threads() {
+    let mock = mock();
+    install_ping(&mock);
+    let pool = pool(
+        "POOL_THREADS",
+        RfcPoolConfig {
+            max_size: 2,
+            ..Default::default()
+        },
+    );
+    std::thread::scope(|s| {
+        for _ in 0..8 {
+            s.spawn(|| {
+                let conn = pool.checkout().unwrap();
+                conn.get_function("Z_POOL_PING").unwrap().call().unwrap();
+            });
+        }
+    });
+    assert!(pool.size() <= 2);
+    assert!(mock.logons("POOL_THREADS").total <= 2);
+}
+
+#[test]
+fn checkout_times_out_when_pool_is_full() {
+    mock();
+    let pool = pool(
+        "POOL_FULL",
+        RfcPoolConfig {
+            max_size: 1,
+            checkout_timeout: Duration::from_millis(50),
+            ..Default::default()
+        },
+    );
+    let _busy = pool.checkout().unwrap();
+    let err = pool.checkout().err().unwrap();
+    assert_eq!(err.code, RfcRc::RfcTimeout);
+}
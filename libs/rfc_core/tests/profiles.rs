@@ -0,0 +1,125 @@
+mod common;
+
+use std::collections::HashMap;
+
+use common::*;
+use rsrfc::*;
+
+#[derive(Debug, RfcResult)]
+struct Pong {
+    #[sap(alias = "EV_SYSID")]
+    sysid: String,
+}
+
+/// QAS inherits client and language from ERP and has its own host and
+/// user
+fn configure() -> MockLib {
+    let mock = mock();
+    for (key, value) in [
+        ("SAP_ERP_CLIENT", "200"),
+        ("SAP_ERP_LANG", "DE"),
+        ("SAP_ERP_USER", "ERP_USER"),
+        ("SAP_QAS_INHERITS", "erp"),
+        ("SAP_QAS_ASHOST", "qas.example.com"),
+        ("SAP_QAS_SYSNR", "01"),
+        ("SAP_QAS_USER", "QAS_USER"),
+        ("SAP_QAS_PASSWD", "secret"),
+        ("SAP_LOOP_INHERITS", "loop"),
+    ] {
+        std::env::set_var(key, value);
+    }
+    mock.install(
+        &FunctionModule::new("Z_TEST_PING")
+            .exporting("EV_SYSID", DataType::char(8))
+            .respond(Response::new().export("EV_SYSID", json!("QAS"))),
+    );
+    mock
+}
+
+#[test]
+fn named_system_with_inheritance() {
+    let mock = configure();
+    let client = RfcClient::for_system("qas").unwrap();
+    let pong: Pong = client.execute("Z_TEST_PING", HashMap::new()).unwrap();
+    assert_eq!(pong.sysid, "QAS");
+
+    let sent = mock.logons("QAS_USER").last_parameters;
+    assert_eq!(sent["ashost"], json!("qas.example.com"));
+    assert_eq!(sent["sysnr"], json!("01"));
+    assert_eq!(sent["client"], json!("200"));
+    assert_eq!(sent["lang"], json!("DE"));
+}
+
+#[test]
+fn unknown_and_cyclic_systems_are_rejected() {
+    configure();
+    let err = RfcClient::for_system("nope")
+        .unwrap()
+        .execute::<Pong>("Z_TEST_PING", HashMap::new())
+        .unwrap_err();
+    assert!(err.to_string().contains("nope not configured"), "{}", err);
+
+    let err = RfcClient::for_system("loop")
+        .unwrap()
+        .execute::<Pong>("Z_TEST_PING", HashMap::new())
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("loop is inherited in a cycle: loop -> loop"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn named_systems_fall_back_to_the_base_section() {
+    let mock = configure();
+    let file = std::env::temp_dir().join(format!("rsrfc-profiles-{}.toml", std::process::id()));
+    std::fs::write(
+        &file,
+        r#"
+[SAP]
+saprouter = "/H/router.example.com/S/3299"
+
+[SAP.prd]
+ashost = "prd.example.com"
+user = "PRD_USER"
+passwd = "secret"
+
+[SAP.cya]
+inherits = "cyb"
+
+[SAP.cyb]
+inherits = "cya"
+
+[SAP.cyc]
+inherits = "cya"
+"#,
+    )
+    .unwrap();
+
+    let client = RfcClient::for_system("prd")
+        .unwrap()
+        .with_config_file(&file);
+    let pong = client.execute::<Pong>("Z_TEST_PING", HashMap::new());
+    let cycle = RfcClient::for_system("cyc")
+        .unwrap()
+        .with_config_file(&file)
+        .execute::<Pong>("Z_TEST_PING", HashMap::new());
+    std::fs::remove_file(&file).unwrap();
+
+    assert_eq!(pong.unwrap().sysid, "QAS");
+    let sent = mock.logons("PRD_USER").last_parameters;
+    assert_eq!(sent["ashost"], json!("prd.example.com"));
+    assert_eq!(sent["saprouter"], json!("/H/router.example.com/S/3299"));
+    // Set with SAP_CLIENT, the environment variable of [SAP]
+    assert_eq!(sent["client"], json!("100"));
+
+    let err = cycle.unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("cya is inherited in a cycle: cyc -> cya -> cyb -> cya"),
+        "{}",
+        err
+    );
+}
@@ -0,0 +1,221 @@
+#![allow(clippy::result_large_err)]
+mod common;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use common::*;
+use rsrfc::{error::*, *};
+
+#[derive(Debug, RfcResult)]
+struct Pong {
+    #[sap(alias = "EV_SYSID")]
+    sysid: String,
+}
+
+fn connection_reset() -> ScriptedError {
+    ScriptedError::new(
+        MockRc::CommunicationFailure,
+        "RFC_COMMUNICATION_FAILURE",
+        "Connection reset by peer",
+    )
+}
+
+/// Fails once with a broken connection, then answers
+fn install_flaky(mock: &MockLib, name: &str) {
+    mock.install(
+        &FunctionModule::new(name)
+            .exporting("EV_SYSID", DataType::char(8))
+            .respond(Response::new().close().error(connection_reset()))
+            .respond(Response::new().export("EV_SYSID", json!("DEV"))),
+    );
+}
+
+fn fast_retries() -> RetryPolicy {
+    RetryPolicy {
+        initial_backoff: Duration::from_millis(1),
+        jitter: false,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn read_only_calls_are_retried_on_a_new_connection() {
+    let mock = mock();
+    install_flaky(&mock, "Z_RETRY_READ");
+
+    // Without the health check, only closing the broken connection keeps
+    // the retry from getting it again
+    let client = RfcClient::new()
+        .unwrap()
+        .with_pool_config(RfcPoolConfig {
+            health_check: false,
+            ..Default::default()
+        })
+        .with_retry_policy(fast_retries())
+        .with_read_only("z_retry_read");
+    assert!(client.retries("Z_RETRY_READ"));
+
+    let pong: Pong = client.execute("Z_RETRY_READ", HashMap::new()).unwrap();
+    assert_eq!(pong.sysid, "DEV");
+    assert_eq!(mock.calls("Z_RETRY_READ").len(), 2);
+    assert_eq!(client.pool().size(), 1);
+}
+
+#[test]
+fn other_calls_are_not_retried_by_default() {
+    let mock = mock();
+    install_flaky(&mock, "Z_RETRY_WRITE");
+
+    let client = RfcClient::new().unwrap().with_retry_policy(fast_retries());
+    assert!(!client.retries("Z_RETRY_WRITE"));
+
+    let err = client
+        .execute::<Pong>("Z_RETRY_WRITE", HashMap::new())
+        .unwrap_err();
+    assert!(matches!(err, RfcError::Communication { .. }), "{err:?}");
+    assert!(err.is_retryable());
+    assert_eq!(mock.calls("Z_RETRY_WRITE").len(), 1);
+    // The broken connection is not returned to the pool
+    assert_eq!(client.pool().size(), 0);
+}
+
+#[test]
+fn all_functions_retries_every_call() {
+    let mock = mock();
+    install_flaky(&mock, "Z_RETRY_ANY");
+
+    let client = RfcClient::new().unwrap().with_retry_policy(RetryPolicy {
+        all_functions: true,
+        ..fast_retries()
+    });
+
+    let pong: Pong = client.execute("Z_RETRY_ANY", HashMap::new()).unwrap();
+    assert_eq!(pong.sysid, "DEV");
+    assert_eq!(mock.calls("Z_RETRY_ANY").len(), 2);
+}
+
+#[test]
+fn on_retry_is_told_about_each_retry() {
+    let mock = mock();
+    install_flaky(&mock, "Z_RETRY_HOOK");
+
+    let retries = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&retries);
+    let policy = fast_retries().on_retry(move |name, retry, backoff, err| {
+        seen.lock()
+            .unwrap()
+            .push((name.to_string(), retry, backoff, err.code()));
+    });
+    let client = RfcClient::new()
+        .unwrap()
+        .with_retry_policy(policy)
+        .with_read_only("Z_RETRY_HOOK");
+
+    let _: Pong = client.execute("Z_RETRY_HOOK", HashMap::new()).unwrap();
+    assert_eq!(
+        *retries.lock().unwrap(),
+        [(
+            "Z_RETRY_HOOK".to_string(),
+            1,
+            Duration::from_millis(1),
+            RfcRc::RfcCommunicationFailure
+        )]
+    );
+}
+
+#[test]
+fn retries_stop_after_max_attempts() {
+    let mock = mock();
+    mock.install(
+        &FunctionModule::new("Z_RETRY_TIMEOUT").respond(Response::new().error(ScriptedError::new(
+            MockRc::Timeout,
+            "RFC_TIMEOUT",
+            "Timeout",
+        ))),
+    );
+
+    let client = RfcClient::new()
+        .unwrap()
+        .with_retry_policy(RetryPolicy {
+            max_attempts: 4,
+            ..fast_retries()
+        })
+        .with_read_only("Z_RETRY_TIMEOUT");
+
+    let err = client
+        .execute::<()>("Z_RETRY_TIMEOUT", HashMap::new())
+        .unwrap_err();
+    assert_eq!(err.code(), RfcRc::RfcTimeout);
+    assert_eq!(mock.calls("Z_RETRY_TIMEOUT").len(), 4);
+}
+
+#[test]
+fn permanent_errors_are_not_retried() {
+    let mock = mock();
+    mock.install(
+        &FunctionModule::new("Z_RETRY_EXCEPTION")
+            .respond(Response::new().error(ScriptedError::new(
+                MockRc::AbapException,
+                "NOT_FOUND",
+                "Purchase order not found",
+            )))
+            .respond(Response::new()),
+    );
+
+    let client = RfcClient::new()
+        .unwrap()
+        .with_retry_policy(fast_retries())
+        .with_read_only("Z_RETRY_EXCEPTION");
+
+    let err = client
+        .execute::<()>("Z_RETRY_EXCEPTION", HashMap::new())
+        .unwrap_err();
+    assert!(!err.is_retryable());
+    assert_eq!(mock.calls("Z_RETRY_EXCEPTION").len(), 1);
+}
+
+#[test]
+fn errors_are_classified_by_code() {
+    let retryable = |code| RfcError::from(RfcErrorInfo::with_code(code, "")).is_retryable();
+    assert!(retryable(RfcRc::RfcCommunicationFailure));
+    assert!(retryable(RfcRc::RfcClosed));
+    assert!(retryable(RfcRc::RfcTimeout));
+    assert!(!retryable(RfcRc::RfcLogonFailure));
+    assert!(!retryable(RfcRc::RfcAbapException));
+    assert!(!retryable(RfcRc::RfcAbapMessage));
+    assert!(!retryable(RfcRc::RfcCanceled));
+    assert!(!retryable(RfcRc::RfcInvalidParameter));
+}
+
+#[test]
+fn backoff_grows_up_to_the_maximum() {
+    let policy = RetryPolicy {
+        initial_backoff: Duration::from_millis(100),
+        max_backoff: Duration::from_millis(500),
+        jitter: false,
+        ..Default::default()
+    };
+    assert_eq!(policy.backoff(1), Duration::from_millis(100));
+    assert_eq!(policy.backoff(2), Duration::from_millis(200));
+    assert_eq!(policy.backoff(3), Duration::from_millis(400));
+    assert_eq!(policy.backoff(4), Duration::from_millis(500));
+    assert_eq!(policy.backoff(1000), Duration::from_millis(500));
+
+    let jittered = RetryPolicy {
+        jitter: true,
+        ..policy
+    };
+    for _ in 0..100 {
+        let backoff = jittered.backoff(2);
+        assert!(backoff >= Duration::from_millis(100), "{backoff:?}");
+        assert!(backoff <= Duration::from_millis(200), "{backoff:?}");
+    }
+
+    assert!(!RfcClient::new()
+        .unwrap()
+        .with_retry_policy(RetryPolicy::none())
+        .with_read_only("Z_RETRY_NONE")
+        .retries("Z_RETRY_NONE"));
+}
@@ -0,0 +1,328 @@
+#![allow(clippy::result_large_err)]
+
+mod common;
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime, TimeZone, Timelike, Utc};
+use common::*;
+use rsrfc::{error::*, *};
+
+fn install_values(mock: &MockLib) {
+    mock.install(
+        &FunctionModule::new("Z_VALUES")
+            .changing("CV_INT", DataType::Int)
+            .changing("CV_INT1", DataType::Int1)
+            .changing("CV_INT2", DataType::Int2)
+            .changing("CV_INT8", DataType::Int8)
+            .changing("CV_NUM", DataType::num(6))
+            .changing("CV_CHAR", DataType::char(20))
+            .changing("CV_FLOAT", DataType::Float)
+            .changing("CV_BCD", DataType::bcd(7, 2))
+            .changing("CV_DECF16", DataType::Decf16)
+            .changing("CV_DECF34", DataType::Decf34)
+            .changing("CV_STRING", DataType::String)
+            .changing("CV_XSTRING", DataType::Xstring)
+            .changing("CV_RAW", DataType::byte(4))
+            .changing("CV_DATE", DataType::Date)
+            .changing("CV_TIME", DataType::Time)
+            .changing("CV_TSTMP", DataType::bcd(8, 0))
+            .changing("CV_TSTMPL", DataType::bcd(11, 7))
+            .changing("CV_UTCLONG", DataType::Utclong)
+            .respond(Response::new()),
+    );
+}
+
+fn connect() -> RfcConnection {
+    let params = RfcConnectionParameters {
+        ashost: Some("mock".into()),
+        sysnr: Some("00".into()),
+        client: Some("100".into()),
+        user: Some("VALUES".into()),
+        passwd: Some("secret".into()),
+        ..Default::default()
+    };
+    RfcConnection::new(&params, &RfcLib::new().unwrap()).unwrap()
+}
+
+fn set<T: ToRfcValue>(f: &mut RfcFunction, name: &str, value: T) -> Result<(), RfcErrorInfo> {
+    f.get_mut_parameter(name).unwrap().set(value)
+}
+
+fn get<T: FromRfcValue>(f: &RfcFunction, name: &str) -> Result<T, RfcErrorInfo> {
+    f.get_parameter(name).unwrap().get()
+}
+
+#[test]
+fn values_round_trip() {
+    let mock = mock();
+    install_values(&mock);
+    let conn = connect();
+    let mut f = conn.get_function("Z_VALUES").unwrap();
+
+    set(&mut f, "CV_INT", -42_i32).unwrap();
+    set(&mut f, "CV_INT1", 200_u8).unwrap();
+    set(&mut f, "CV_NUM", 123_u32).unwrap();
+    set(&mut f, "CV_CHAR", "hello").unwrap();
+    set(&mut f, "CV_FLOAT", 1.5_f64).unwrap();
+    set(&mut f, "CV_BCD", "12.5").unwrap();
+    set(&mut f, "CV_STRING", String::from("a longer text")).unwrap();
+    set(&mut f, "CV_XSTRING", vec![0xde_u8, 0xad]).unwrap();
+    set(&mut f, "CV_RAW", &[1_u8, 2][..]).unwrap();
+    set(
+        &mut f,
+        "CV_DATE",
+        NaiveDate::from_ymd_opt(2024, 2, 29).unwrap(),
+    )
+    .unwrap();
+    set(
+        &mut f,
+        "CV_TIME",
+        NaiveTime::from_hms_opt(13, 5, 9).unwrap(),
+    )
+    .unwrap();
+    f.call().unwrap();
+
+    assert_eq!(get::<i32>(&f, "CV_INT").unwrap(), -42);
+    assert_eq!(get::<u8>(&f, "CV_INT1").unwrap(), 200);
+    assert_eq!(get::<u32>(&f, "CV_NUM").unwrap(), 123);
+    assert_eq!(get::<String>(&f, "CV_NUM").unwrap(), "000123");
+    assert_eq!(get::<String>(&f, "CV_CHAR").unwrap(), "hello");
+    assert_eq!(get::<f64>(&f, "CV_FLOAT").unwrap(), 1.5);
+    assert_eq!(get::<f64>(&f, "CV_BCD").unwrap(), 12.5);
+    assert_eq!(get::<String>(&f, "CV_BCD").unwrap(), "12.50");
+    assert_eq!(get::<String>(&f, "CV_STRING").unwrap(), "a longer text");
+    assert_eq!(get::<Vec<u8>>(&f, "CV_XSTRING").unwrap(), vec![0xde, 0xad]);
+    assert_eq!(get::<Vec<u8>>(&f, "CV_RAW").unwrap(), vec![1, 2, 0, 0]);
+    assert_eq!(
+        get::<NaiveDate>(&f, "CV_DATE").unwrap(),
+        NaiveDate::from_ymd_opt(2024, 2, 29).unwrap()
+    );
+    assert_eq!(
+        get::<NaiveTime>(&f, "CV_TIME").unwrap(),
+        NaiveTime::from_hms_opt(13, 5, 9).unwrap()
+    );
+}
+
+#[test]
+fn options_and_mismatches() {
+    let mock = mock();
+    install_values(&mock);
+    let conn = connect();
+    let mut f = conn.get_function("Z_VALUES").unwrap();
+
+    // None leaves the initial values in place
+    set(&mut f, "CV_DATE", None::<NaiveDate>).unwrap();
+    set(&mut f, "CV_INT", Some(7_i64)).unwrap();
+    assert_eq!(get::<Option<NaiveDate>>(&f, "CV_DATE").unwrap(), None);
+    assert!(get::<NaiveDate>(&f, "CV_DATE").is_err());
+    assert_eq!(get::<Option<String>>(&f, "CV_CHAR").unwrap(), None);
+    assert_eq!(get::<Option<i64>>(&f, "CV_INT").unwrap(), Some(7));
+
+    assert!(set(&mut f, "CV_INT", 1.5_f64).is_err());
+    assert!(set(&mut f, "CV_XSTRING", "text").is_err());
+    assert!(set(&mut f, "CV_INT", u64::MAX).is_err());
+    set(&mut f, "CV_INT", -1_i32).unwrap();
+    assert!(get::<u32>(&f, "CV_INT").is_err());
+
+    // Flags are 'X' or blank
+    set(&mut f, "CV_CHAR", true).unwrap();
+    assert!(get::<bool>(&f, "CV_CHAR").unwrap());
+    set(&mut f, "CV_CHAR", false).unwrap();
+    assert!(!get::<bool>(&f, "CV_CHAR").unwrap());
+    set(&mut f, "CV_CHAR", "Y").unwrap();
+    assert!(get::<bool>(&f, "CV_CHAR").is_err());
+    assert!(set(&mut f, "CV_INT", true).is_err());
+}
+
+fn get_int(f: &RfcFunction, name: &str) -> Result<i64, RfcErrorInfo> {
+    f.get_parameter(name).unwrap().get_int()
+}
+
+#[test]
+fn integers_are_range_checked() {
+    let mock = mock();
+    install_values(&mock);
+    let conn = connect();
+    let mut f = conn.get_function("Z_VALUES").unwrap();
+
+    let mut set_int = |name: &str, value: i64| f.get_mut_parameter(name).unwrap().set_int(value);
+    set_int("CV_INT1", 255).unwrap();
+    set_int("CV_INT2", -32768).unwrap();
+    set_int("CV_INT", i32::MAX as i64).unwrap();
+    set_int("CV_INT8", i64::MIN).unwrap();
+    set_int("CV_NUM", 4711).unwrap();
+    assert!(set_int("CV_INT1", 256).is_err());
+    assert!(set_int("CV_INT1", -1).is_err());
+    assert!(set_int("CV_INT2", 32768).is_err());
+    assert!(set_int("CV_INT", i32::MAX as i64 + 1).is_err());
+    f.call().unwrap();
+
+    assert_eq!(get_int(&f, "CV_INT1").unwrap(), 255);
+    assert_eq!(get_int(&f, "CV_INT2").unwrap(), -32768);
+    assert_eq!(get_int(&f, "CV_INT").unwrap(), i32::MAX as i64);
+    assert_eq!(get_int(&f, "CV_INT8").unwrap(), i64::MIN);
+    assert_eq!(get_int(&f, "CV_NUM").unwrap(), 4711);
+    assert_eq!(get::<i64>(&f, "CV_INT8").unwrap(), i64::MIN);
+
+    // RfcGetInt reads NUMC and BCD values, as long as they are integers
+    f.get_mut_parameter("CV_BCD")
+        .unwrap()
+        .set_chars("12.50")
+        .unwrap();
+    assert!(get_int(&f, "CV_BCD").is_err());
+    f.get_mut_parameter("CV_NUM")
+        .unwrap()
+        .set_chars("999999")
+        .unwrap();
+    assert_eq!(get_int(&f, "CV_NUM").unwrap(), 999_999);
+}
+
+#[test]
+fn floats_and_numc() {
+    let mock = mock();
+    install_values(&mock);
+    let conn = connect();
+    let mut f = conn.get_function("Z_VALUES").unwrap();
+
+    let num = f.get_mut_parameter("CV_NUM").unwrap();
+    num.set_num(42).unwrap();
+    assert!(num.set_num(1_000_000).is_err());
+    f.get_mut_parameter("CV_FLOAT")
+        .unwrap()
+        .set_float(-0.125)
+        .unwrap();
+    f.get_mut_parameter("CV_BCD")
+        .unwrap()
+        .set_chars("12.34")
+        .unwrap();
+    f.call().unwrap();
+
+    let num = f.get_parameter("CV_NUM").unwrap();
+    assert_eq!(get::<String>(&f, "CV_NUM").unwrap(), "000042");
+    assert_eq!(num.get_num().unwrap(), 42);
+    assert_eq!(
+        f.get_parameter("CV_FLOAT").unwrap().get_float().unwrap(),
+        -0.125
+    );
+    assert_eq!(
+        f.get_parameter("CV_BCD").unwrap().get_float().unwrap(),
+        12.34
+    );
+    assert!(f.get_parameter("CV_CHAR").unwrap().get_float().is_err());
+    assert!(f.get_parameter("CV_CHAR").unwrap().get_num().is_err());
+    assert!(set(&mut f, "CV_NUM", -1_i32).is_err());
+}
+
+#[test]
+fn dates_times_and_timestamps() {
+    let mock = mock();
+    install_values(&mock);
+    let conn = connect();
+    let mut f = conn.get_function("Z_VALUES").unwrap();
+
+    // Initial values
+    let date = f.get_parameter("CV_DATE").unwrap();
+    assert_eq!(date.get_date().unwrap(), None);
+    assert_eq!(
+        f.get_parameter("CV_TIME").unwrap().get_time().unwrap(),
+        None
+    );
+    assert_eq!(get::<NaiveTime>(&f, "CV_TIME").unwrap(), NaiveTime::MIN);
+    assert_eq!(get::<Option<DateTime<Utc>>>(&f, "CV_TSTMP").unwrap(), None);
+    assert_eq!(
+        get::<Option<DateTime<Utc>>>(&f, "CV_UTCLONG").unwrap(),
+        None
+    );
+    assert!(get::<DateTime<Utc>>(&f, "CV_TSTMPL").is_err());
+
+    let stamp = Utc
+        .with_ymd_and_hms(2024, 2, 29, 13, 5, 9)
+        .unwrap()
+        .with_nanosecond(123_456_789)
+        .unwrap();
+    let seconds = stamp.with_nanosecond(0).unwrap();
+    let ticks = stamp.with_nanosecond(123_456_700).unwrap();
+    f.get_mut_parameter("CV_TIME")
+        .unwrap()
+        .set_time("130509")
+        .unwrap();
+    set(&mut f, "CV_TSTMP", stamp).unwrap();
+    set(&mut f, "CV_TSTMPL", stamp).unwrap();
+    set(&mut f, "CV_UTCLONG", stamp).unwrap();
+    // A DateTime in another time zone is the same point in time
+    set(
+        &mut f,
+        "CV_DATE",
+        stamp.with_timezone(&FixedOffset::east_opt(3600 * 11).unwrap()),
+    )
+    .unwrap();
+    f.call().unwrap();
+
+    assert_eq!(
+        f.get_parameter("CV_TIME").unwrap().get_time().unwrap(),
+        NaiveTime::from_hms_opt(13, 5, 9)
+    );
+    assert_eq!(get::<String>(&f, "CV_TSTMP").unwrap(), "20240229130509");
+    assert_eq!(get::<DateTime<Utc>>(&f, "CV_TSTMP").unwrap(), seconds);
+    assert_eq!(
+        get::<String>(&f, "CV_TSTMPL").unwrap(),
+        "20240229130509.1234567"
+    );
+    assert_eq!(get::<DateTime<Utc>>(&f, "CV_TSTMPL").unwrap(), ticks);
+    assert_eq!(
+        get::<String>(&f, "CV_UTCLONG").unwrap(),
+        "2024-02-29T13:05:09.1234567"
+    );
+    assert_eq!(get::<DateTime<Utc>>(&f, "CV_UTCLONG").unwrap(), ticks);
+    // DATS take the date in the time zone of the value
+    assert_eq!(
+        f.get_parameter("CV_DATE").unwrap().get_date().unwrap(),
+        NaiveDate::from_ymd_opt(2024, 3, 1)
+    );
+
+    assert!(set(&mut f, "CV_BCD", stamp).is_err());
+    assert!(f.get_parameter("CV_INT").unwrap().get_date().is_err());
+}
+
+#[cfg(feature = "rust_decimal")]
+#[test]
+fn decimals_round_trip() {
+    use rust_decimal::Decimal;
+
+    let mock = mock();
+    install_values(&mock);
+    let conn = connect();
+    let mut f = conn.get_function("Z_VALUES").unwrap();
+
+    set(&mut f, "CV_BCD", Decimal::new(-1999, 2)).unwrap();
+    set(&mut f, "CV_FLOAT", Decimal::new(25, 1)).unwrap();
+    assert_eq!(
+        get::<Decimal>(&f, "CV_BCD").unwrap(),
+        Decimal::new(-1999, 2)
+    );
+    assert_eq!(get::<Decimal>(&f, "CV_FLOAT").unwrap(), Decimal::new(25, 1));
+
+    // P 13 DECIMALS 2: extra decimals and integer digits are not rounded away
+    assert!(set(&mut f, "CV_BCD", Decimal::new(1999, 3)).is_err());
+    assert!(set(&mut f, "CV_BCD", Decimal::new(10_i64.pow(11), 0)).is_err());
+    set(&mut f, "CV_BCD", Decimal::new(9_999_999_999_999, 2)).unwrap();
+
+    let pi = "3.141592653589793".parse::<Decimal>().unwrap();
+    let long = "-1234567890.1234567890123456".parse::<Decimal>().unwrap();
+    set(&mut f, "CV_DECF16", pi).unwrap();
+    set(&mut f, "CV_DECF34", long).unwrap();
+    f.call().unwrap();
+    assert_eq!(get::<Decimal>(&f, "CV_DECF16").unwrap(), pi);
+    assert_eq!(get::<Decimal>(&f, "CV_DECF34").unwrap(), long);
+    assert_eq!(
+        get::<Decimal>(&f, "CV_BCD").unwrap(),
+        Decimal::new(9_999_999_999_999, 2)
+    );
+
+    // DECFLOAT16 holds 16 significant digits
+    assert!(set(&mut f, "CV_DECF16", long).is_err());
+    set(&mut f, "CV_DECF16", Decimal::new(12_000, 0)).unwrap();
+    assert_eq!(
+        get::<Decimal>(&f, "CV_DECF16").unwrap(),
+        Decimal::new(12_000, 0)
+    );
+}
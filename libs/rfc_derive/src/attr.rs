@@ -1,7 +1,7 @@
 use proc_macro2::Span;
 use syn::{
-    parse::Parse, punctuated::Punctuated, spanned::Spanned, token::Comma, Attribute, Ident, LitStr,
-    Result,
+    parse::Parse, punctuated::Punctuated, spanned::Spanned, token::Comma, Attribute, Ident,
+    LitBool, LitStr, Result,
 };
 
 use crate::util;
@@ -13,6 +13,11 @@ pub struct AttributeSpanWrapper<T> {
 
 pub enum FieldAttr {
     Alias(Ident, LitStr),
+    Default(Ident),
+    Skip(Ident),
+    With(Ident, LitStr),
+    Trim(Ident, LitBool),
+    Optional(Ident),
 }
 
 impl Parse for FieldAttr {
@@ -25,7 +30,59 @@ impl Parse for FieldAttr {
                 name,
                 util::parse_eq(input, util::ALIAS_NOTE)?,
             )),
-            _ => todo!(),
+            "default" => Ok(FieldAttr::Default(name)),
+            "skip" => Ok(FieldAttr::Skip(name)),
+            "with" => Ok(FieldAttr::With(
+                name,
+                util::parse_eq(input, util::WITH_NOTE)?,
+            )),
+            "trim" => Ok(FieldAttr::Trim(
+                name,
+                util::parse_eq(input, util::TRIM_NOTE)?,
+            )),
+            "optional" => Ok(FieldAttr::Optional(name)),
+            _ => Err(util::unknown_attribute(
+                &name,
+                &["alias", "default", "skip", "with", "trim", "optional"],
+            )),
+        }
+    }
+}
+
+pub enum ContainerAttr {
+    RenameAll(Ident, LitStr),
+}
+
+impl Parse for ContainerAttr {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let name: Ident = input.parse()?;
+        let name_str = name.to_string();
+
+        match &*name_str {
+            "rename_all" => Ok(ContainerAttr::RenameAll(
+                name,
+                util::parse_eq(input, util::RENAME_ALL_NOTE)?,
+            )),
+            _ => Err(util::unknown_attribute(&name, &["rename_all"])),
+        }
+    }
+}
+
+pub enum VariantAttr {
+    Value(Ident, LitStr),
+}
+
+impl Parse for VariantAttr {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let name: Ident = input.parse()?;
+        let name_str = name.to_string();
+
+        match &*name_str {
+            "value" => Ok(VariantAttr::Value(
+                name,
+                util::parse_eq(input, util::VALUE_NOTE)?,
+            )),
+            _ => Err(util::unknown_attribute(&name, &["value"])),
         }
     }
 }
@@ -0,0 +1,121 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Error, Fields, LitStr, Result};
+
+use crate::attr::{self, VariantAttr};
+use crate::util;
+
+/// An enum of the fixed values of an ABAP domain. Each unit variant has a
+/// `#[sap(value = "...")]`; one variant holding a String may take the
+/// values that are not listed.
+pub fn derive(item: DeriveInput) -> Result<TokenStream> {
+    let enum_name = &item.ident;
+    let Data::Enum(data) = &item.data else {
+        return Err(Error::new_spanned(
+            &item.ident,
+            "RfcEnum can only be derived for enums",
+        ));
+    };
+
+    let mut values: Vec<String> = Vec::new();
+    let mut from_arms = quote! {};
+    let mut to_arms = quote! {};
+    let mut other = None;
+
+    for variant in &data.variants {
+        let variant_name = &variant.ident;
+        let mut value = None;
+        for attr in attr::parse_attributes(&variant.attrs)? {
+            match attr.item {
+                VariantAttr::Value(_ident, lit) => value = Some(lit),
+            }
+        }
+
+        match (value, &variant.fields) {
+            (Some(lit), Fields::Unit) => {
+                if values.iter().any(|v| v.trim() == lit.value().trim()) {
+                    return Err(Error::new_spanned(lit, "duplicate value"));
+                }
+                values.push(lit.value());
+                // Values are compared without the blanks that pad a CHAR
+                let key = LitStr::new(lit.value().trim(), lit.span());
+                from_arms = quote! { #from_arms #key => Ok(Self::#variant_name), };
+                to_arms = quote! { #to_arms Self::#variant_name => #lit, };
+            }
+            (None, Fields::Unnamed(fields)) if fields.unnamed.len() == 1 => {
+                if other.is_some() {
+                    return Err(Error::new_spanned(
+                        variant,
+                        "only one variant can take the values that are not listed",
+                    ));
+                }
+                other = Some(variant_name);
+                to_arms = quote! { #to_arms Self::#variant_name(value) => value.as_str(), };
+            }
+            (Some(lit), _) => {
+                return Err(Error::new_spanned(
+                    lit,
+                    "a variant with a value cannot have fields",
+                ))
+            }
+            (None, _) => {
+                return Err(Error::new_spanned(
+                    variant,
+                    format!(
+                        "missing value\n\
+                         help: add `#[sap({})]`, or use a variant like `Other(String)` \
+                         for the values that are not listed",
+                        util::VALUE_NOTE
+                    ),
+                ))
+            }
+        }
+    }
+
+    let fallback = match other {
+        Some(variant_name) => quote! { _ => Ok(Self::#variant_name(value)), },
+        None => quote! {
+            _ => Err(rsrfc::error::RfcErrorInfo::conversion(&format!(
+                "Value '{}' of parameter {} is not a valid {}",
+                value, param.name, stringify!(#enum_name)
+            ))),
+        },
+    };
+    // A blank field is None in an Option, unless blank is one of the values
+    let from_rfc_opt = if values.iter().any(|v| v.trim().is_empty()) {
+        quote! {}
+    } else {
+        quote! {
+            fn from_rfc_opt(param: &rsrfc::RfcParameter) -> ::std::result::Result<Option<Self>, rsrfc::error::RfcErrorInfo> {
+                let value = <String as rsrfc::FromRfcValue>::from_rfc(param)?;
+                if value.trim().is_empty() {
+                    return Ok(None);
+                }
+                <Self as rsrfc::FromRfcValue>::from_rfc(param).map(Some)
+            }
+        }
+    };
+
+    Ok(quote! {
+        impl rsrfc::FromRfcValue for #enum_name {
+            fn from_rfc(param: &rsrfc::RfcParameter) -> ::std::result::Result<Self, rsrfc::error::RfcErrorInfo> {
+                let value = <String as rsrfc::FromRfcValue>::from_rfc(param)?;
+                match value.trim() {
+                    #from_arms
+                    #fallback
+                }
+            }
+
+            #from_rfc_opt
+        }
+
+        impl rsrfc::ToRfcValue for #enum_name {
+            fn to_rfc(&self, param: &mut rsrfc::RfcParameter) -> ::std::result::Result<(), rsrfc::error::RfcErrorInfo> {
+                let value: &str = match self {
+                    #to_arms
+                };
+                <str as rsrfc::ToRfcValue>::to_rfc(value, param)
+            }
+        }
+    })
+}
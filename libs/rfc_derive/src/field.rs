@@ -1,20 +1,94 @@
-use syn::{Field as SynField, GenericArgument, Ident, PathArguments, Result, Type};
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::{
+    ext::IdentExt, spanned::Spanned, Error, Field as SynField, GenericArgument, Ident, Path,
+    PathArguments, Result, Type,
+};
 
 use crate::attr::{self, AttributeSpanWrapper};
+use crate::model::RenameRule;
+use crate::util;
+
+/// Types converted with `FromRfcValue` and `ToRfcValue`. Any other path
+/// type is taken for a structure; `FromStructure` and `ToRfcValue` name
+/// `RfcStructure` and `RfcRow` in the error for types that implement
+/// neither, at the span of the field type.
+const VALUE_TYPES: &[&str] = &[
+    "i8",
+    "i16",
+    "i32",
+    "i64",
+    "isize",
+    "u8",
+    "u16",
+    "u32",
+    "u64",
+    "usize",
+    "f32",
+    "f64",
+    "bool",
+    "String",
+    "Decimal",
+    "NaiveDate",
+    "NaiveTime",
+    "DateTime",
+    "Option",
+];
+
+/// Types that are neither values nor structures
+const UNSUPPORTED_TYPES: &[&str] = &[
+    "char", "i128", "u128", "str", "Box", "Rc", "Arc", "Cow", "Result", "HashMap", "BTreeMap",
+    "HashSet", "BTreeSet", "VecDeque",
+];
+
+/// How a field maps to a parameter
+pub enum FieldKind {
+    /// A scalar, read and written with `FromRfcValue` and `ToRfcValue`
+    Value,
+    /// A table, read row by row into a `Vec` of the row type
+    Table(Box<Type>),
+    /// A structure, read with `FromStructure`
+    Structure,
+    /// Converted by the `read` and `write` functions of the module given
+    /// with `#[sap(with = "...")]`
+    With(Box<Path>),
+    /// Left at its default with `#[sap(skip)]`
+    Skip,
+}
+
+/// Whether a derive reads the fields from the parameters or writes them
+#[derive(Clone, Copy)]
+pub enum Access {
+    Read,
+    Write,
+}
 
 pub struct Field {
     pub ty: Type,
     pub ident: Option<Ident>,
-    alias: Option<AttributeSpanWrapper<String>>,
+    pub kind: FieldKind,
+    name: String,
+    /// `#[sap(default)]`: missing parameters and initial values read as
+    /// `Default::default()`
+    default: bool,
+    /// `#[sap(optional)]`: missing parameters are tolerated
+    optional: bool,
+    /// `#[sap(trim = false)]` keeps the blanks that pad a CHAR value
+    trim: bool,
 }
 
 impl Field {
-    pub fn from_struct_field(field: &SynField, _index: usize) -> Result<Self> {
+    pub fn from_struct_field(field: &SynField, rename_all: Option<&RenameRule>) -> Result<Self> {
         let SynField {
             ident, attrs, ty, ..
         } = field;
 
         let mut alias = None;
+        let mut default = false;
+        let mut optional = false;
+        let mut skip = false;
+        let mut with = None;
+        let mut trim = None;
 
         for attr in attr::parse_attributes(attrs)? {
             let attribute_span = attr.attribute_span;
@@ -23,49 +97,227 @@ impl Field {
                 attr::FieldAttr::Alias(_ident, value) => {
                     alias = Some(AttributeSpanWrapper {
                         item: value.value(),
-                        attribute_span: attribute_span,
+                        attribute_span,
+                    })
+                }
+                attr::FieldAttr::Default(_ident) => default = true,
+                attr::FieldAttr::Optional(_ident) => optional = true,
+                attr::FieldAttr::Skip(_ident) => skip = true,
+                attr::FieldAttr::With(_ident, value) => with = Some(value.parse::<Path>()?),
+                attr::FieldAttr::Trim(ident, value) => {
+                    trim = Some(AttributeSpanWrapper {
+                        item: value.value,
+                        attribute_span: ident.span(),
                     })
                 }
             }
         }
 
+        let kind = match (skip, with) {
+            (true, _) => FieldKind::Skip,
+            (false, Some(path)) => FieldKind::With(Box::new(path)),
+            (false, None) => field_kind(ty)?,
+        };
+        let is_string = matches!(kind, FieldKind::Value) && is_ident(ty, "String");
+        if let Some(trim) = &trim {
+            if !trim.item && !is_string {
+                return Err(Error::new(
+                    trim.attribute_span,
+                    "`trim = false` only applies to String fields",
+                ));
+            }
+        }
+
+        let name = match (alias, rename_all, ident) {
+            (Some(alias), _, _) => alias.item,
+            (None, Some(rule), Some(ident)) => rule.apply(&ident.unraw().to_string()),
+            _ if skip => String::new(),
+            _ => {
+                return Err(Error::new_spanned(
+                    field,
+                    format!(
+                        "missing parameter name\n\
+                         help: add `#[sap({})]` to the field or `#[sap({})]` to the struct",
+                        util::ALIAS_NOTE,
+                        util::RENAME_ALL_NOTE
+                    ),
+                ))
+            }
+        };
+
         Ok(Self {
             ident: ident.clone(),
             ty: ty.clone(),
-            alias,
+            kind,
+            name,
+            default,
+            optional,
+            trim: trim.is_none_or(|t| t.item),
         })
     }
 
-    pub fn is_type(&self, type_name: &str) -> bool {
-        if let Type::Path(type_path) = &self.ty {
-            if let Some(segment) = type_path.path.segments.last() {
-                return segment.ident == type_name;
+    /// Whether the field is left at its default if the parameter or field
+    /// does not exist
+    pub fn tolerates_missing(&self) -> bool {
+        self.default || self.optional
+    }
+
+    /// The expression reading this field from `param`, an expression of
+    /// type `&mut RfcParameter`
+    pub fn read_field(&self, param: TokenStream) -> TokenStream {
+        let ty = &self.ty;
+        match &self.kind {
+            FieldKind::Table(row_type) => quote_spanned! {ty.span()=> {
+                let param = #param;
+                let total = param.get_row_count()?;
+                let mut data: Vec<#row_type> = Vec::with_capacity(total as usize);
+                let indices = <#row_type as rsrfc::FromTable>::field_indices(param)?;
+                for i in 0..total {
+                    param.set_row(i)?;
+                    data.push(<#row_type as rsrfc::FromTable>::from_row(param, &indices)?);
+                }
+                data
+            }},
+            FieldKind::Value if !self.trim && self.default => quote_spanned! {ty.span()=> {
+                let value = #param.get_chars()?;
+                if value.trim().is_empty() {
+                    Default::default()
+                } else {
+                    value
+                }
+            }},
+            FieldKind::Value if !self.trim => quote_spanned! {ty.span()=> #param.get_chars()? },
+            FieldKind::Value if self.default => quote_spanned! {ty.span()=>
+                <Option<#ty> as rsrfc::FromRfcValue>::from_rfc(#param)?.unwrap_or_default()
+            },
+            FieldKind::Value => {
+                quote_spanned! {ty.span()=> <#ty as rsrfc::FromRfcValue>::from_rfc(#param)? }
+            }
+            FieldKind::Structure => {
+                quote_spanned! {ty.span()=> <#ty as rsrfc::FromStructure>::from_structure(#param)? }
             }
+            FieldKind::With(path) => quote_spanned! {path.span()=> #path::read(#param)? },
+            FieldKind::Skip => quote_spanned! {ty.span()=> Default::default() },
         }
+    }
 
-        return false;
+    /// The statements writing `value` to `param`, an expression of type
+    /// `&mut RfcParameter`. Tables are appended to row by row, values and
+    /// structures go through `ToRfcValue`.
+    pub fn write_field(&self, param: TokenStream, value: TokenStream) -> TokenStream {
+        let ty = &self.ty;
+        match &self.kind {
+            FieldKind::Table(row_type) => quote_spanned! {ty.span()=>
+                #param.append_rows_from::<&Vec<#row_type>>(&#value)?;
+            },
+            FieldKind::With(path) => {
+                quote_spanned! {path.span()=> #path::write(&#value, #param)?; }
+            }
+            FieldKind::Skip => quote_spanned! {ty.span()=> },
+            _ => quote_spanned! {ty.span()=>
+                <#ty as rsrfc::ToRfcValue>::to_rfc(&#value, #param)?;
+            },
+        }
     }
 
-    pub fn inner_type(&self) -> Option<&syn::Type> {
-        if let Type::Path(type_path) = &self.ty {
-            if let Some(segment) = type_path.path.segments.last() {
-                if segment.ident == "Vec" {
-                    if let PathArguments::AngleBracketed(args) = &segment.arguments {
-                        if let Some(GenericArgument::Type(inner_type)) = args.args.first() {
-                            return Some(inner_type);
-                        }
-                    }
-                }
+    /// The statement checking this field against the metadata. `lookup`
+    /// is a call to `rsrfc::check` returning the parameter or field, given
+    /// `shape` as the expected shape; structures and tables are checked
+    /// field by field.
+    pub fn check_field(
+        &self,
+        lookup: impl Fn(TokenStream) -> TokenStream,
+        access: Access,
+    ) -> TokenStream {
+        let ty = &self.ty;
+        let shape = |shape| quote_spanned! {ty.span()=> rsrfc::check::FieldShape::#shape };
+        let (shape, nested) = match (&self.kind, access) {
+            (FieldKind::Table(row_type), Access::Read) => (
+                shape(quote! { Table }),
+                quote_spanned! {ty.span()=> <#row_type as rsrfc::FromTable>::check_fields },
+            ),
+            (FieldKind::Table(row_type), Access::Write) => (
+                shape(quote! { Table }),
+                quote_spanned! {ty.span()=> <#row_type as rsrfc::ToTable>::check_fields },
+            ),
+            (FieldKind::Structure, Access::Read) => (
+                shape(quote! { Structure }),
+                quote_spanned! {ty.span()=> <#ty as rsrfc::FromStructure>::check_fields },
+            ),
+            (FieldKind::Structure, Access::Write) => (
+                shape(quote! { Structure }),
+                quote_spanned! {ty.span()=> <#ty as rsrfc::ToRfcValue>::check_fields },
+            ),
+            (FieldKind::Value, _) => (shape(quote! { Value }), quote! {}),
+            (FieldKind::With(_), _) => (shape(quote! { Any }), quote! {}),
+            (FieldKind::Skip, _) => return quote! {},
+        };
+        let lookup = lookup(shape);
+        if nested.is_empty() {
+            return quote! { #lookup; };
+        }
+        quote! {
+            if let Some(field) = #lookup {
+                #nested(field, mismatches);
             }
         }
-        None
     }
 
-    pub fn alias_name(&self) -> String {
-        if let Some(attr) = &self.alias {
-            attr.item.clone()
-        } else {
-            "".into()
+    pub fn alias_name(&self) -> &str {
+        &self.name
+    }
+}
+
+fn field_kind(ty: &Type) -> Result<FieldKind> {
+    let unsupported = |why: &str| Error::new_spanned(ty, format!("unsupported field type: {why}"));
+    let segment = match ty {
+        Type::Path(type_path) if type_path.qself.is_none() => type_path.path.segments.last(),
+        _ => None,
+    }
+    .ok_or_else(|| unsupported("use a scalar, a struct or a Vec of structs"))?;
+    let name = segment.ident.to_string();
+
+    if UNSUPPORTED_TYPES.contains(&name.as_str()) {
+        return Err(unsupported(&format!(
+            "{name} cannot be mapped to a parameter"
+        )));
+    }
+    if name == "Option" {
+        // The derive cannot tell a struct from an enum of its own here. Both
+        // go through FromRfcValue, whose error for a struct says to use
+        // `#[sap(default)]` instead
+        return match generic_argument(segment).map(field_kind) {
+            Some(Ok(FieldKind::Value)) | Some(Ok(FieldKind::Structure)) => Ok(FieldKind::Value),
+            Some(Err(e)) => Err(e),
+            _ => Err(unsupported("Option of a table, use an empty Vec")),
+        };
+    }
+    if name == "Vec" {
+        let row_type =
+            generic_argument(segment).ok_or_else(|| unsupported("Vec without a row type"))?;
+        return match field_kind(row_type)? {
+            FieldKind::Structure => Ok(FieldKind::Table(Box::new(row_type.clone()))),
+            // Vec<u8> holds the value of a RAW or XSTRING
+            FieldKind::Value if is_ident(row_type, "u8") => Ok(FieldKind::Value),
+            _ => Err(unsupported("table rows must be structs deriving RfcTable")),
+        };
+    }
+    if VALUE_TYPES.contains(&name.as_str()) {
+        Ok(FieldKind::Value)
+    } else {
+        Ok(FieldKind::Structure)
+    }
+}
+
+fn generic_argument(segment: &syn::PathSegment) -> Option<&Type> {
+    if let PathArguments::AngleBracketed(args) = &segment.arguments {
+        if let Some(GenericArgument::Type(inner_type)) = args.args.first() {
+            return Some(inner_type);
         }
     }
+    None
+}
+
+fn is_ident(ty: &Type, name: &str) -> bool {
+    matches!(ty, Type::Path(type_path) if type_path.path.is_ident(name))
 }
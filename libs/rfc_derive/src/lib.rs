@@ -2,9 +2,13 @@ use proc_macro::TokenStream;
 use syn::parse_macro_input;
 
 mod attr;
+mod enumeration;
 mod field;
 mod model;
+mod params;
 mod result;
+mod row;
+mod structure;
 mod table;
 mod util;
 
@@ -21,3 +25,31 @@ pub fn derive_table(input: TokenStream) -> TokenStream {
         .unwrap_or_else(syn::Error::into_compile_error)
         .into()
 }
+
+#[proc_macro_derive(RfcStructure, attributes(sap))]
+pub fn derive_structure(input: TokenStream) -> TokenStream {
+    structure::derive(parse_macro_input!(input))
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(RfcParams, attributes(sap))]
+pub fn derive_params(input: TokenStream) -> TokenStream {
+    params::derive(parse_macro_input!(input))
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(RfcRow, attributes(sap))]
+pub fn derive_row(input: TokenStream) -> TokenStream {
+    row::derive(parse_macro_input!(input))
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(RfcEnum, attributes(sap))]
+pub fn derive_enum(input: TokenStream) -> TokenStream {
+    enumeration::derive(parse_macro_input!(input))
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
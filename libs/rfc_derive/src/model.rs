@@ -1,31 +1,61 @@
+use crate::attr::{self, ContainerAttr};
 use crate::field::Field;
 use syn::{
-    punctuated::Punctuated, token::Comma, DataStruct, DeriveInput, Field as SynField, Fields,
-    FieldsNamed, FieldsUnnamed, Result,
+    punctuated::Punctuated, token::Comma, DataStruct, DeriveInput, Error, Field as SynField,
+    Fields, FieldsNamed, Result,
 };
 
+/// How `#[sap(rename_all = "...")]` derives parameter names from field
+/// names
+pub enum RenameRule {
+    /// `ev_ebeln` is `EV_EBELN`
+    Uppercase,
+}
+
+impl RenameRule {
+    pub fn apply(&self, field_name: &str) -> String {
+        match self {
+            RenameRule::Uppercase => field_name.to_uppercase(),
+        }
+    }
+}
+
 pub struct Model {
     fields: Vec<Field>,
 }
 
 impl Model {
     pub fn from_item(item: &DeriveInput) -> Result<Self> {
-        let DeriveInput { data, .. } = &item;
-
-        let fields = match *data {
-            syn::Data::Struct(DataStruct {
-                fields: Fields::Named(FieldsNamed { ref named, .. }),
-                ..
-            }) => Some(named),
-            syn::Data::Struct(DataStruct {
-                fields: Fields::Unnamed(FieldsUnnamed { ref unnamed, .. }),
-                ..
-            }) => Some(unnamed),
-            _ => None,
+        let DeriveInput { data, attrs, .. } = &item;
+
+        let mut rename_all = None;
+        for attr in attr::parse_attributes(attrs)? {
+            match attr.item {
+                ContainerAttr::RenameAll(_ident, value) => match &*value.value() {
+                    "UPPERCASE" => rename_all = Some(RenameRule::Uppercase),
+                    _ => {
+                        return Err(Error::new_spanned(
+                            value,
+                            "unknown rename rule\nhelp: the supported rule is `\"UPPERCASE\"`",
+                        ))
+                    }
+                },
+            }
+        }
+
+        let syn::Data::Struct(DataStruct {
+            fields: Fields::Named(FieldsNamed { named, .. }),
+            ..
+        }) = data
+        else {
+            return Err(Error::new_spanned(
+                item,
+                "this trait can only be derived for structs with named fields",
+            ));
         };
 
         Ok(Self {
-            fields: fields_from_item_data(fields)?,
+            fields: fields_from_item_data(named, rename_all.as_ref())?,
         })
     }
 
@@ -34,14 +64,12 @@ impl Model {
     }
 }
 
-fn fields_from_item_data(fields: Option<&Punctuated<SynField, Comma>>) -> Result<Vec<Field>> {
+fn fields_from_item_data(
+    fields: &Punctuated<SynField, Comma>,
+    rename_all: Option<&RenameRule>,
+) -> Result<Vec<Field>> {
     fields
-        .map(|fields| {
-            fields
-                .iter()
-                .enumerate()
-                .map(|(i, f)| Field::from_struct_field(f, i))
-                .collect::<Result<Vec<_>>>()
-        })
-        .unwrap_or_else(|| Ok(Vec::new()))
+        .iter()
+        .map(|f| Field::from_struct_field(f, rename_all))
+        .collect()
 }
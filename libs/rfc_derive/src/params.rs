@@ -0,0 +1,58 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, Result};
+
+use crate::field::{Access, FieldKind};
+use crate::model::Model;
+use crate::result::check_parameters;
+
+pub fn derive(item: DeriveInput) -> Result<TokenStream> {
+    let model = Model::from_item(&item)?;
+    let struct_name = &item.ident;
+
+    let mut write_params_impl = quote! {};
+
+    for field in model.fields() {
+        let field_name = field.ident.as_ref().unwrap();
+        let alias_name = field.alias_name();
+        let write_field = field.write_field(quote! { param }, quote! { self.#field_name });
+
+        let write_param = if let FieldKind::Skip = field.kind {
+            continue;
+        } else if field.tolerates_missing() {
+            quote! {
+                if let Some(param) = method.get_mut_parameter(#alias_name) {
+                    #write_field
+                }
+            }
+        } else {
+            quote! {{
+                let name = method.name().to_string();
+                let param = method.get_mut_parameter(#alias_name).ok_or_else(|| {
+                    rsrfc::error::RfcErrorInfo::invalid_parameter(&format!("Function module {} has no parameter {}", name, #alias_name))
+                })?;
+                #write_field
+            }}
+        };
+
+        write_params_impl = quote! {
+            #write_params_impl
+            #write_param
+        };
+    }
+
+    let check_impl = check_parameters(&model, Access::Write);
+
+    Ok(quote! {
+        impl rsrfc::IntoRfcParams for #struct_name {
+            fn write_params(&self, method: &mut rsrfc::RfcFunction) -> ::std::result::Result<(), rsrfc::error::RfcErrorInfo> {
+                #write_params_impl
+                Ok(())
+            }
+
+            fn check(method: &mut rsrfc::RfcFunction, mismatches: &mut Vec<String>) {
+                #check_impl
+            }
+        }
+    })
+}
@@ -3,6 +3,7 @@ use quote::quote;
 use syn::{DeriveInput, Result};
 
 // use crate::error::Result;
+use crate::field::{Access, FieldKind};
 use crate::model::Model;
 
 pub fn derive(item: DeriveInput) -> Result<TokenStream> {
@@ -15,66 +16,76 @@ pub fn derive(item: DeriveInput) -> Result<TokenStream> {
         let field_name = field.ident.as_ref().unwrap();
         let alias_name = field.alias_name();
 
-        if field.is_type("Vec") {
-            let inner_type = field.inner_type().unwrap();
-            let inner_type_name = quote! { #inner_type };
-            from_method_impl = quote! {
-                #from_method_impl
-                #field_name: {
-                    let param = method.get_mut_parameter(#alias_name).ok_or(RfcErrorInfo::custom("unknown field #alias_name"))?;
-                    let total = param.get_row_count()?;
-                    let mut data: Vec<#inner_type_name> = vec![];
-
-                    for i in 0..total {
-                        param.set_row(i)?;
-                        let el = #inner_type_name::from_table(param)?;
-                        data.push(el);
-                    }
-                    data
-                },
+        let read_field = field.read_field(quote! { param });
+        let value = if let FieldKind::Skip = field.kind {
+            read_field
+        } else if field.tolerates_missing() {
+            quote! {
+                match method.get_mut_parameter(#alias_name) {
+                    Some(param) => #read_field,
+                    None => Default::default(),
+                }
             }
-        // } else if field.is_type("struct") {
-        //     from_method_impl = quote! {
-        //         #from_method_impl
-        //         #field_name: Material {
-        //             name: String::from("")
-        //         }
-        //     }
-        // } else {
-        //     let inner_type = field.ty.clone();
-        //     let inner_type_name = quote! { #inner_type };
-
-        //     from_method_impl = quote! {
-        //         #field_name: {
-        //             let param = method.get_mut_parameter(#alias_name).ok_or(RfcErrorInfo::custom("unknown field #alias_name"))?;
-        //             let prop = #inner_type_name::from_table(param)?;
-        //             prop
-        //         },
-        //     }
-        // }
         } else {
-            from_method_impl = quote! {
-                #from_method_impl
-                #field_name: {
-                    let param = method.get_mut_parameter(#alias_name).ok_or(RfcErrorInfo::custom("unknown field #alias_name"))?;
-                    // let idx = param.get_field_index_by_name(#alias_name)?;
-                    let content = param.get_chars()?.trim_end().into();
-                    content
-                },
-            }
+            quote! {{
+                let name = method.name().to_string();
+                let param = method.get_mut_parameter(#alias_name).ok_or_else(|| {
+                    rsrfc::error::RfcErrorInfo::invalid_parameter(&format!("Function module {} has no parameter {}", name, #alias_name))
+                })?;
+                #read_field
+            }}
+        };
+        from_method_impl = quote! {
+            #from_method_impl
+            #field_name: #value,
         }
     }
 
-    Ok(quote! {
-        impl FromMethod for #struct_name {
-            fn from_method(method: &mut rsrfc::RfcFunction) -> Result<Self, RfcErrorInfo> {
+    let check_impl = check_parameters(&_model, Access::Read);
 
-                let mut response = Self {
+    Ok(quote! {
+        impl rsrfc::FromMethod for #struct_name {
+            fn from_method(method: &mut rsrfc::RfcFunction) -> ::std::result::Result<Self, rsrfc::error::RfcErrorInfo> {
+                Ok(Self {
                     #from_method_impl
-                };
+                })
+            }
 
-                Ok(response)
+            fn check(method: &mut rsrfc::RfcFunction, mismatches: &mut Vec<String>) {
+                #check_impl
             }
         }
     })
 }
+
+/// The statements checking each field against the parameter of the same
+/// alias of `method`
+pub fn check_parameters(model: &Model, access: Access) -> TokenStream {
+    let access_path = match access {
+        Access::Read => quote! { rsrfc::check::Access::Read },
+        Access::Write => quote! { rsrfc::check::Access::Write },
+    };
+    let mut checks = quote! {};
+
+    for field in model.fields() {
+        let alias_name = field.alias_name();
+        let required = !field.tolerates_missing();
+        let check = field.check_field(
+            |shape| {
+                quote! {
+                    rsrfc::check::check_parameter(
+                        method, #alias_name, #shape, #access_path, #required, mismatches,
+                    )
+                }
+            },
+            access,
+        );
+
+        checks = quote! {
+            #checks
+            #check
+        };
+    }
+
+    checks
+}
@@ -0,0 +1,80 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, Result};
+
+use crate::field::{Access, FieldKind};
+use crate::model::Model;
+use crate::table::{check_fields, field_indices};
+
+/// A structure or table row is written like any other value, so that
+/// `param.set(&row)` fills the fields of a structure parameter or of the
+/// current row of a table. Tables look up the fields once with `ToTable`.
+pub fn derive(item: DeriveInput) -> Result<TokenStream> {
+    let model = Model::from_item(&item)?;
+    let struct_name = &item.ident;
+    let field_indices_impl = field_indices(&model);
+    let check_fields_impl = check_fields(&model, Access::Write);
+
+    let mut to_row_impl = quote! {};
+    let mut position = 0usize;
+
+    for field in model.fields() {
+        let field_name = field.ident.as_ref().unwrap();
+        let write_field = field.write_field(quote! { field }, quote! { self.#field_name });
+
+        if let FieldKind::Skip = field.kind {
+            continue;
+        }
+        let write_row_field = if field.tolerates_missing() {
+            quote! {
+                if let Some(idx) = indices.get(#position).copied().flatten() {
+                    let field = param.get_field_by_index(idx)?;
+                    #write_field
+                }
+            }
+        } else {
+            let alias_name = field.alias_name();
+            quote! {{
+                let idx = indices.get(#position).copied().flatten().ok_or_else(|| {
+                    rsrfc::error::RfcErrorInfo::invalid_parameter(&format!("{} has no field {}", param.name, #alias_name))
+                })?;
+                let field = param.get_field_by_index(idx)?;
+                #write_field
+            }}
+        };
+        position += 1;
+
+        to_row_impl = quote! {
+            #to_row_impl
+            #write_row_field
+        };
+    }
+
+    Ok(quote! {
+        impl rsrfc::ToTable for #struct_name {
+            fn field_indices(param: &mut rsrfc::RfcParameter) -> ::std::result::Result<Vec<Option<u32>>, rsrfc::error::RfcErrorInfo> {
+                Ok(#field_indices_impl)
+            }
+
+            fn to_row(&self, param: &mut rsrfc::RfcParameter, indices: &[Option<u32>]) -> ::std::result::Result<(), rsrfc::error::RfcErrorInfo> {
+                #to_row_impl
+                Ok(())
+            }
+
+            fn check_fields(param: &mut rsrfc::RfcParameter, mismatches: &mut Vec<String>) {
+                #check_fields_impl
+            }
+        }
+
+        impl rsrfc::ToRfcValue for #struct_name {
+            fn to_rfc(&self, param: &mut rsrfc::RfcParameter) -> ::std::result::Result<(), rsrfc::error::RfcErrorInfo> {
+                let indices = <Self as rsrfc::ToTable>::field_indices(param)?;
+                <Self as rsrfc::ToTable>::to_row(self, param, &indices)
+            }
+
+            fn check_fields(param: &mut rsrfc::RfcParameter, mismatches: &mut Vec<String>) {
+                <Self as rsrfc::ToTable>::check_fields(param, mismatches)
+            }
+        }
+    })
+}
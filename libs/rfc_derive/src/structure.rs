@@ -0,0 +1,30 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, Result};
+
+use crate::field::Access;
+use crate::model::Model;
+use crate::table::{check_fields, field_indices, read_fields};
+
+pub fn derive(item: DeriveInput) -> Result<TokenStream> {
+    let model = Model::from_item(&item)?;
+    let struct_name = &item.ident;
+    let field_indices_impl = field_indices(&model);
+    let from_structure_impl = read_fields(&model);
+    let check_fields_impl = check_fields(&model, Access::Read);
+
+    Ok(quote! {
+        impl rsrfc::FromStructure for #struct_name {
+            fn from_structure(param: &mut rsrfc::RfcParameter) -> ::std::result::Result<Self, rsrfc::error::RfcErrorInfo> {
+                let indices: Vec<Option<u32>> = #field_indices_impl;
+                Ok(Self {
+                    #from_structure_impl
+                })
+            }
+
+            fn check_fields(param: &mut rsrfc::RfcParameter, mismatches: &mut Vec<String>) {
+                #check_fields_impl
+            }
+        }
+    })
+}
@@ -2,37 +2,134 @@ use proc_macro2::TokenStream;
 use quote::quote;
 use syn::{DeriveInput, Result};
 
+use crate::field::{Access, FieldKind};
 use crate::model::Model;
 
 pub fn derive(item: DeriveInput) -> Result<TokenStream> {
     let model = Model::from_item(&item)?;
     let struct_name = &item.ident;
+    let field_indices_impl = field_indices(&model);
+    let from_row_impl = read_fields(&model);
+    let check_fields_impl = check_fields(&model, Access::Read);
 
-    let mut from_table_impl = quote! {};
+    Ok(quote! {
+        impl rsrfc::FromTable for #struct_name {
+            fn from_table(param: &mut rsrfc::RfcParameter) -> ::std::result::Result<Self, rsrfc::error::RfcErrorInfo> {
+                let indices = <Self as rsrfc::FromTable>::field_indices(param)?;
+                <Self as rsrfc::FromTable>::from_row(param, &indices)
+            }
+
+            fn field_indices(param: &mut rsrfc::RfcParameter) -> ::std::result::Result<Vec<Option<u32>>, rsrfc::error::RfcErrorInfo> {
+                Ok(#field_indices_impl)
+            }
+
+            fn from_row(param: &mut rsrfc::RfcParameter, indices: &[Option<u32>]) -> ::std::result::Result<Self, rsrfc::error::RfcErrorInfo> {
+                Ok(Self {
+                    #from_row_impl
+                })
+            }
+
+            fn check_fields(param: &mut rsrfc::RfcParameter, mismatches: &mut Vec<String>) {
+                #check_fields_impl
+            }
+        }
+    })
+}
+
+/// The expression looking up the index of each field of `param`, a
+/// structure or table, once. Fields that may be missing have None.
+pub fn field_indices(model: &Model) -> TokenStream {
+    let mut indices = quote! {};
+
+    for field in model.fields() {
+        let alias_name = field.alias_name();
+        let index = if let FieldKind::Skip = field.kind {
+            continue;
+        } else if field.tolerates_missing() {
+            quote! {
+                if param.has_field(#alias_name) {
+                    Some(param.get_field_index_by_name(#alias_name)?)
+                } else {
+                    None
+                }
+            }
+        } else {
+            quote! { Some(param.get_field_index_by_name(#alias_name)?) }
+        };
+
+        indices = quote! {
+            #indices
+            #index,
+        };
+    }
+
+    quote! { vec![#indices] }
+}
+
+/// Field initializers reading the fields of `param`, a structure or the
+/// current row of a table, at the `indices` from `field_indices`
+pub fn read_fields(model: &Model) -> TokenStream {
+    let mut fields = quote! {};
+    let mut position = 0usize;
 
     for field in model.fields() {
         let field_name = field.ident.as_ref().unwrap();
         let alias_name = field.alias_name();
+        let read_field = field.read_field(quote! { param.get_field_by_index(idx)? });
+        let value = if let FieldKind::Skip = field.kind {
+            read_field
+        } else {
+            let missing = if field.tolerates_missing() {
+                quote! { Default::default() }
+            } else {
+                quote! {
+                    return Err(rsrfc::error::RfcErrorInfo::invalid_parameter(&format!(
+                        "{} has no field {}",
+                        param.name, #alias_name
+                    )))
+                }
+            };
+            let value = quote! {
+                match indices.get(#position).copied().flatten() {
+                    Some(idx) => #read_field,
+                    None => #missing,
+                }
+            };
+            position += 1;
+            value
+        };
 
-        from_table_impl = quote! {
-            #from_table_impl
-            #field_name: {
-                let idx = param.get_field_index_by_name(#alias_name)?;
-                let content = param.get_field_by_index(idx)?.get_chars()?.trim_end().into();
-                content
-            },
+        fields = quote! {
+            #fields
+            #field_name: #value,
         };
     }
 
-    Ok(quote! {
-        impl FromTable for #struct_name {
-            fn from_table(param: &mut rsrfc::RfcParameter) -> Result<Self, RfcErrorInfo> {
-                let mut result = Self {
-                    #from_table_impl
-                };
+    fields
+}
 
-                Ok(result)
-            }
-        }
-    })
+/// The statements checking each field of `param`, a structure or table,
+/// against the metadata
+pub fn check_fields(model: &Model, access: Access) -> TokenStream {
+    let mut checks = quote! {};
+
+    for field in model.fields() {
+        let alias_name = field.alias_name();
+        let required = !field.tolerates_missing();
+        let check = field.check_field(
+            |shape| {
+                quote! {
+                    rsrfc::check::check_field(param, #alias_name, #shape, #required, mismatches)
+                }
+            },
+            access,
+        );
+
+        checks = quote! {
+            #checks
+            #check
+        };
+    }
+
+    checks
 }
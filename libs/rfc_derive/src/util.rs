@@ -1,9 +1,14 @@
 use syn::{
     parse::{Parse, ParseStream, Result},
     token::Eq,
+    Ident,
 };
 
 pub const ALIAS_NOTE: &str = "alias = \"EV_TYPE\"";
+pub const WITH_NOTE: &str = "with = \"path::to::module\"";
+pub const TRIM_NOTE: &str = "trim = false";
+pub const VALUE_NOTE: &str = "value = \"E\"";
+pub const RENAME_ALL_NOTE: &str = "rename_all = \"UPPERCASE\"";
 
 pub fn parse_eq<T: Parse>(input: ParseStream, help: &str) -> Result<T> {
     if input.is_empty() {
@@ -19,3 +24,18 @@ pub fn parse_eq<T: Parse>(input: ParseStream, help: &str) -> Result<T> {
     input.parse::<Eq>()?;
     input.parse()
 }
+
+pub fn unknown_attribute(name: &Ident, expected: &[&str]) -> syn::Error {
+    syn::Error::new(
+        name.span(),
+        format!(
+            "unknown attribute `{name}`\n\
+             help: expected one of {}",
+            expected
+                .iter()
+                .map(|e| format!("`{e}`"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    )
+}
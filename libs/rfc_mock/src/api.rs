@@ -0,0 +1,1169 @@
+//! The exported part of sapnwrfc.h.
+//!
+//! Every function follows the contract of its SDK counterpart: handles are
+//! opaque pointers handed out by this library, strings are SAP_UC (UTF-16)
+//! and errors are reported through the RFC_ERROR_INFO out parameter.
+#![allow(non_snake_case, clippy::missing_safety_doc)]
+
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::ptr::null_mut;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::data::{Container, ContainerKind, Failure, FunctionDesc, Result, TypeDesc, Value};
+use crate::decfloat::{DECIMAL128, DECIMAL64};
+use crate::ffi::*;
+use crate::registry::registry;
+use crate::script::ScriptedError;
+
+/// RFC_CONNECTION_HANDLE. RfcCancel may be called from another thread
+/// while a call is running, hence the atomics.
+pub struct Connection {
+    user: String,
+    broken: AtomicBool,
+    canceled: AtomicBool,
+}
+
+unsafe fn clear(err: *mut RfcErrorInfo) {
+    if let Some(err) = err.as_mut() {
+        err.code = RfcRc::Ok;
+        err.group = RfcErrorGroup::Ok;
+        fill(&mut err.key, "");
+        fill(&mut err.message, "");
+        fill(&mut err.abap_msg_class, "");
+        fill(&mut err.abap_msg_type, "");
+        fill(&mut err.abap_msg_number, "");
+        fill(&mut err.abap_msg_v1, "");
+        fill(&mut err.abap_msg_v2, "");
+        fill(&mut err.abap_msg_v3, "");
+        fill(&mut err.abap_msg_v4, "");
+    }
+}
+
+unsafe fn report(err: *mut RfcErrorInfo, f: &Failure) -> RfcRc {
+    clear(err);
+    if let Some(err) = err.as_mut() {
+        err.code = f.code;
+        err.group = f.code.group();
+        fill(&mut err.key, &f.key);
+        fill(&mut err.message, &f.message);
+    }
+    f.code
+}
+
+unsafe fn report_scripted(err: *mut RfcErrorInfo, e: &ScriptedError) -> RfcRc {
+    clear(err);
+    if let Some(err) = err.as_mut() {
+        err.code = e.code;
+        err.group = e.group.unwrap_or_else(|| e.code.group());
+        fill(&mut err.key, &e.key);
+        fill(&mut err.message, &e.message);
+        fill(&mut err.abap_msg_class, &e.abap_msg_class);
+        fill(&mut err.abap_msg_type, &e.abap_msg_type);
+        fill(&mut err.abap_msg_number, &e.abap_msg_number);
+        fill(&mut err.abap_msg_v1, &e.abap_msg_v1);
+        fill(&mut err.abap_msg_v2, &e.abap_msg_v2);
+        fill(&mut err.abap_msg_v3, &e.abap_msg_v3);
+        fill(&mut err.abap_msg_v4, &e.abap_msg_v4);
+    }
+    e.code
+}
+
+unsafe fn outcome(err: *mut RfcErrorInfo, f: impl FnOnce() -> Result<()>) -> RfcRc {
+    match f() {
+        Ok(()) => {
+            clear(err);
+            RfcRc::Ok
+        }
+        Err(e) => report(err, &e),
+    }
+}
+
+unsafe fn handle_outcome<T>(err: *mut RfcErrorInfo, f: impl FnOnce() -> Result<*mut T>) -> *mut T {
+    match f() {
+        Ok(h) => {
+            clear(err);
+            h
+        }
+        Err(e) => {
+            report(err, &e);
+            null_mut()
+        }
+    }
+}
+
+fn invalid_handle() -> Failure {
+    Failure::new(RfcRc::InvalidHandle, "RFC_INVALID_HANDLE", "Invalid handle")
+}
+
+unsafe fn connection<'a>(handle: *mut c_void) -> Result<&'a Connection> {
+    match (handle as *const Connection).as_ref() {
+        Some(c) if !c.broken.load(Ordering::SeqCst) => Ok(c),
+        Some(_) => Err(Failure::new(
+            RfcRc::InvalidHandle,
+            "RFC_INVALID_HANDLE",
+            "The connection has been closed",
+        )),
+        None => Err(invalid_handle()),
+    }
+}
+
+unsafe fn container<'a>(handle: *mut c_void) -> Result<&'a mut Container> {
+    (handle as *mut Container)
+        .as_mut()
+        .ok_or_else(invalid_handle)
+}
+
+unsafe fn table<'a>(handle: *mut c_void) -> Result<&'a mut Container> {
+    let c = container(handle)?;
+    match c.kind {
+        ContainerKind::Table(_) => Ok(c),
+        _ => Err(Failure::new(
+            RfcRc::InvalidParameter,
+            "RFC_INVALID_PARAMETER",
+            "Not a table handle",
+        )),
+    }
+}
+
+unsafe fn type_desc<'a>(handle: *const c_void) -> Result<&'a TypeDesc> {
+    (handle as *const TypeDesc)
+        .as_ref()
+        .ok_or_else(invalid_handle)
+}
+
+unsafe fn function_desc<'a>(handle: *const c_void) -> Result<&'a FunctionDesc> {
+    (handle as *const FunctionDesc)
+        .as_ref()
+        .ok_or_else(invalid_handle)
+}
+
+unsafe fn utf16<'a>(ptr: *const u16, len: u32) -> &'a [u16] {
+    if ptr.is_null() || len == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(ptr, len as usize)
+    }
+}
+
+/// Store a value without giving up the allocation of structures and tables,
+/// callers may still hold handles to them
+fn assign(slot: &mut Value, new: Value) {
+    match (slot, new) {
+        (Value::Structure(old), Value::Structure(new)) => {
+            for (o, n) in old.values.iter_mut().zip(new.values) {
+                assign(o, n);
+            }
+        }
+        (Value::Table(old), Value::Table(new)) => {
+            old.rows = new.rows;
+            old.cursor = new.cursor;
+        }
+        (slot, new) => *slot = new,
+    }
+}
+
+/// Copy `value` into a SAP_UC buffer of `len` characters, padded with blanks
+unsafe fn write_padded(buf: *mut u16, len: u32, value: &str) -> Result<()> {
+    let encoded: Vec<u16> = value.encode_utf16().collect();
+    if encoded.len() > len as usize {
+        return Err(Failure::new(
+            RfcRc::BufferTooSmall,
+            "RFC_BUFFER_TOO_SMALL",
+            &format!("Buffer of {} characters too small for '{}'", len, value),
+        ));
+    }
+    let out = std::slice::from_raw_parts_mut(buf, len as usize);
+    out.fill(b' ' as u16);
+    out[..encoded.len()].copy_from_slice(&encoded);
+    Ok(())
+}
+
+/// The stand-in behaves like SDK 7.50 patch level 12
+pub const VERSION: (u32, u32, u32) = (7500, 0, 12);
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcGetVersion(
+    major: *mut u32,
+    minor: *mut u32,
+    patch: *mut u32,
+) -> *const u16 {
+    static TEXT: OnceLock<Vec<u16>> = OnceLock::new();
+    if let Some(major) = major.as_mut() {
+        *major = VERSION.0;
+    }
+    if let Some(minor) = minor.as_mut() {
+        *minor = VERSION.1;
+    }
+    if let Some(patch) = patch.as_mut() {
+        *patch = VERSION.2;
+    }
+    TEXT.get_or_init(|| "750 Patch Level 12 (stand-in)\0".encode_utf16().collect())
+        .as_ptr()
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcOpenConnection(
+    parameters: *const RfcConnectionParameter,
+    param_count: u32,
+    err: *mut RfcErrorInfo,
+) -> *mut c_void {
+    let mut params = HashMap::new();
+    for i in 0..param_count as usize {
+        let p = &*parameters.add(i);
+        params.insert(read_str(p.name).to_lowercase(), read_str(p.value));
+    }
+    let user = params.get("user").cloned().unwrap_or_default();
+    if let Some(e) = registry().logon(&user, &params) {
+        report_scripted(err, &e);
+        return null_mut();
+    }
+    clear(err);
+    Box::into_raw(Box::new(Connection {
+        user,
+        broken: AtomicBool::new(false),
+        canceled: AtomicBool::new(false),
+    })) as *mut c_void
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcCloseConnection(handle: *mut c_void, err: *mut RfcErrorInfo) -> RfcRc {
+    if handle.is_null() {
+        return report(err, &invalid_handle());
+    }
+    let conn = Box::from_raw(handle as *mut Connection);
+    registry().logoff(&conn.user);
+    clear(err);
+    RfcRc::Ok
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcPing(handle: *mut c_void, err: *mut RfcErrorInfo) -> RfcRc {
+    outcome(err, || connection(handle).map(|_| ()))
+}
+
+/// Interrupts a running call, which then fails with RFC_CANCELED. Like in
+/// the SDK the connection is unusable afterwards and only good for
+/// RfcCloseConnection.
+#[no_mangle]
+pub unsafe extern "C" fn RfcCancel(handle: *mut c_void, err: *mut RfcErrorInfo) -> RfcRc {
+    outcome(err, || {
+        let conn = connection(handle)?;
+        conn.canceled.store(true, Ordering::SeqCst);
+        conn.broken.store(true, Ordering::SeqCst);
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcGetFunctionDesc(
+    handle: *mut c_void,
+    func_name: *const u16,
+    err: *mut RfcErrorInfo,
+) -> *mut c_void {
+    handle_outcome(err, || {
+        connection(handle)?;
+        let name = read_str(func_name);
+        let desc = registry().function(&name).ok_or_else(|| {
+            Failure::new(
+                RfcRc::NotFound,
+                "FU_NOT_FOUND",
+                &format!("ID:FL Type:E Number:046 {}", name.to_uppercase()),
+            )
+        })?;
+        Ok(Arc::as_ptr(&desc) as *mut c_void)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcCreateFunction(
+    handle: *mut c_void,
+    err: *mut RfcErrorInfo,
+) -> *mut c_void {
+    handle_outcome(err, || {
+        function_desc(handle)?;
+        // The registry keeps every description alive, so taking another
+        // reference from the raw handle is fine.
+        let desc = handle as *const FunctionDesc;
+        Arc::increment_strong_count(desc);
+        let desc = Arc::from_raw(desc);
+        Ok(Box::into_raw(Container::function(desc)) as *mut c_void)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcDestroyFunction(handle: *mut c_void, err: *mut RfcErrorInfo) -> RfcRc {
+    outcome(err, || {
+        container(handle)?;
+        drop(Box::from_raw(handle as *mut Container));
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcInvoke(
+    handle: *mut c_void,
+    fun: *mut c_void,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    let conn = match connection(handle) {
+        Ok(c) => c,
+        Err(e) => return report(err, &e),
+    };
+    let fun = match container(fun) {
+        Ok(c) => c,
+        Err(e) => return report(err, &e),
+    };
+    let desc = match &fun.kind {
+        ContainerKind::Function(desc) => desc.clone(),
+        _ => {
+            return report(
+                err,
+                &Failure::new(
+                    RfcRc::InvalidParameter,
+                    "RFC_INVALID_PARAMETER",
+                    "Not a function handle",
+                ),
+            )
+        }
+    };
+
+    let mut inputs = serde_json::Map::new();
+    for (p, v) in desc.parameters.iter().zip(fun.values.iter()) {
+        if p.direction != RfcDirection::Export {
+            inputs.insert(p.field.name.clone(), p.field.to_json(v));
+        }
+    }
+    registry().record_call(&desc.name, serde_json::Value::Object(inputs));
+
+    let n = desc.invocations.fetch_add(1, Ordering::SeqCst);
+    let response = match desc.responses.get(n).or(desc.responses.last()) {
+        Some(r) => r,
+        None => {
+            clear(err);
+            return RfcRc::Ok;
+        }
+    };
+    let until = Instant::now() + Duration::from_millis(response.delay_ms);
+    while Instant::now() < until && !conn.canceled.load(Ordering::SeqCst) {
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    if conn.canceled.load(Ordering::SeqCst) {
+        return report(
+            err,
+            &Failure::new(
+                RfcRc::Canceled,
+                "RFC_CANCELED",
+                "Call canceled by the caller",
+            ),
+        );
+    }
+    if response.close {
+        conn.broken.store(true, Ordering::SeqCst);
+    }
+    if let Some(e) = &response.error {
+        return report_scripted(err, e);
+    }
+    outcome(err, || {
+        for (to, from) in &response.copy {
+            let missing = |n: &str| {
+                Failure::new(
+                    RfcRc::NotFound,
+                    "RFC_NOT_FOUND",
+                    &format!("No parameter {}", n),
+                )
+            };
+            let from = desc.parameter_index(from).ok_or_else(|| missing(from))?;
+            let to = desc.parameter_index(to).ok_or_else(|| missing(to))?;
+            let value = fun.values[from].clone();
+            assign(&mut fun.values[to], value);
+        }
+        for (name, json) in &response.exports {
+            let index = desc.parameter_index(name).ok_or_else(|| {
+                Failure::new(
+                    RfcRc::NotFound,
+                    "RFC_NOT_FOUND",
+                    &format!("No parameter {}", name),
+                )
+            })?;
+            let value = desc.parameters[index].field.parse_json(json)?;
+            assign(&mut fun.values[index], value);
+        }
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcGetParameterCount(
+    handle: *const c_void,
+    count: *mut u32,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || {
+        *count = function_desc(handle)?.parameters.len() as u32;
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcGetParameterDescByIndex(
+    handle: *const c_void,
+    index: u32,
+    param_desc: *mut RfcParameterDesc,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || {
+        let desc = function_desc(handle)?;
+        let p = desc.parameters.get(index as usize).ok_or_else(|| {
+            Failure::new(
+                RfcRc::InvalidParameter,
+                "RFC_INVALID_PARAMETER",
+                &format!("Parameter index {} out of range", index),
+            )
+        })?;
+        let out = &mut *param_desc;
+        fill(&mut out.name, &p.field.name);
+        out.field_type = p.field.field_type;
+        out.direction = p.direction;
+        out.nuc_length = p.field.nuc_length;
+        out.uc_length = p.field.uc_length;
+        out.decimals = p.field.decimals;
+        out.type_desc_handle = match &p.field.type_desc {
+            Some(td) => Arc::as_ptr(td) as *const c_void,
+            None => std::ptr::null(),
+        };
+        fill(&mut out.default_value, p.default.as_deref().unwrap_or(""));
+        fill(&mut out.parameter_text, "");
+        out.optional = p.optional as u8;
+        out.extended_description = null_mut();
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcDescribeType(
+    handle: *mut c_void,
+    err: *mut RfcErrorInfo,
+) -> *mut c_void {
+    handle_outcome(err, || {
+        let c = container(handle)?;
+        let td = c.type_desc().ok_or_else(|| {
+            Failure::new(
+                RfcRc::InvalidParameter,
+                "RFC_INVALID_PARAMETER",
+                "Not a structure or table handle",
+            )
+        })?;
+        Ok(Arc::as_ptr(td) as *mut c_void)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcGetFieldCount(
+    handle: *const c_void,
+    count: *mut u32,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || {
+        *count = type_desc(handle)?.fields.len() as u32;
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcGetFieldDescByIndex(
+    handle: *const c_void,
+    index: u32,
+    field_desc: *mut RfcFieldDesc,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || {
+        let td = type_desc(handle)?;
+        let f = td.fields.get(index as usize).ok_or_else(|| {
+            Failure::new(
+                RfcRc::InvalidParameter,
+                "RFC_INVALID_PARAMETER",
+                &format!("Field index {} out of range", index),
+            )
+        })?;
+        let out = &mut *field_desc;
+        fill(&mut out.name, &f.name);
+        out.field_type = f.field_type;
+        out.nuc_length = f.nuc_length;
+        out.nuc_offset = f.nuc_offset;
+        out.uc_length = f.uc_length;
+        out.uc_offset = f.uc_offset;
+        out.decimals = f.decimals;
+        out.type_desc_handle = match &f.type_desc {
+            Some(td) => Arc::as_ptr(td) as *const c_void,
+            None => std::ptr::null(),
+        };
+        out.extended_description = null_mut();
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcGetStructureByIndex(
+    handle: *mut c_void,
+    index: u32,
+    structure: *mut *mut c_void,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || {
+        container(handle)?.with_field(index, |meta, v| match v {
+            Value::Structure(s) => {
+                *structure = &mut **s as *mut Container as *mut c_void;
+                Ok(())
+            }
+            _ => Err(Failure::conversion(&format!(
+                "{} is not a structure",
+                meta.name
+            ))),
+        })
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcGetTableByIndex(
+    handle: *mut c_void,
+    index: u32,
+    table: *mut *mut c_void,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || {
+        container(handle)?.with_field(index, |meta, v| match v {
+            Value::Table(t) => {
+                *table = &mut **t as *mut Container as *mut c_void;
+                Ok(())
+            }
+            _ => Err(Failure::conversion(&format!(
+                "{} is not a table",
+                meta.name
+            ))),
+        })
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcGetCharsByIndex(
+    handle: *mut c_void,
+    index: u32,
+    value: *mut u16,
+    length: u32,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || {
+        let s = container(handle)?.with_field(index, |meta, v| meta.to_chars(v))?;
+        write_padded(value, length, &s)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcSetCharsByIndex(
+    handle: *mut c_void,
+    index: u32,
+    value: *const u16,
+    length: u32,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || {
+        let s = String::from_utf16_lossy(utf16(value, length));
+        container(handle)?.with_field(index, |meta, v| {
+            assign(v, meta.parse_chars(&s)?);
+            Ok(())
+        })
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcSetIntByIndex(
+    handle: *mut c_void,
+    index: u32,
+    value: i32,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || {
+        container(handle)?.with_field(index, |meta, v| {
+            assign(v, meta.convert_int(value as i64)?);
+            Ok(())
+        })
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcSetInt1ByIndex(
+    handle: *mut c_void,
+    index: u32,
+    value: u8,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || {
+        container(handle)?.with_field(index, |meta, v| {
+            assign(v, meta.convert_int(value as i64)?);
+            Ok(())
+        })
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcSetInt2ByIndex(
+    handle: *mut c_void,
+    index: u32,
+    value: i16,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || {
+        container(handle)?.with_field(index, |meta, v| {
+            assign(v, meta.convert_int(value as i64)?);
+            Ok(())
+        })
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcSetInt8ByIndex(
+    handle: *mut c_void,
+    index: u32,
+    value: i64,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || {
+        container(handle)?.with_field(index, |meta, v| {
+            assign(v, meta.convert_int(value)?);
+            Ok(())
+        })
+    })
+}
+
+/// Read an integer and check that it fits the integer type of the getter
+unsafe fn get_int<T: TryFrom<i64>>(handle: *mut c_void, index: u32, value: *mut T) -> Result<()> {
+    let v = container(handle)?.with_field(index, |meta, v| {
+        let int = meta.to_int(v)?;
+        T::try_from(int).map_err(|_| {
+            Failure::conversion(&format!(
+                "Value {} of field {} is out of range for {}",
+                int,
+                meta.name,
+                std::any::type_name::<T>()
+            ))
+        })
+    })?;
+    *value = v;
+    Ok(())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcGetIntByIndex(
+    handle: *mut c_void,
+    index: u32,
+    value: *mut i32,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || get_int(handle, index, value))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcGetInt1ByIndex(
+    handle: *mut c_void,
+    index: u32,
+    value: *mut u8,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || get_int(handle, index, value))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcGetInt2ByIndex(
+    handle: *mut c_void,
+    index: u32,
+    value: *mut i16,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || get_int(handle, index, value))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcGetInt8ByIndex(
+    handle: *mut c_void,
+    index: u32,
+    value: *mut i64,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || get_int(handle, index, value))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcGetFloatByIndex(
+    handle: *mut c_void,
+    index: u32,
+    value: *mut f64,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || {
+        *value = container(handle)?.with_field(index, |meta, v| meta.to_float(v))?;
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcSetFloatByIndex(
+    handle: *mut c_void,
+    index: u32,
+    value: f64,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || {
+        container(handle)?.with_field(index, |meta, v| {
+            assign(v, meta.convert_float(value)?);
+            Ok(())
+        })
+    })
+}
+
+/// RFC_DATE and RFC_TIME are fixed size character arrays without a
+/// terminating zero
+unsafe fn set_date_time(
+    handle: *mut c_void,
+    index: u32,
+    value: *const u16,
+    field_type: RfcType,
+) -> Result<()> {
+    let len = if field_type == RfcType::Date { 8 } else { 6 };
+    let s = String::from_utf16_lossy(utf16(value, len));
+    container(handle)?.with_field(index, |meta, v| {
+        if meta.field_type != field_type {
+            return Err(Failure::conversion(&format!(
+                "{} is not of type {:?}",
+                meta.name, field_type
+            )));
+        }
+        assign(v, meta.parse_chars(&s)?);
+        Ok(())
+    })
+}
+
+unsafe fn get_date_time(
+    handle: *mut c_void,
+    index: u32,
+    value: *mut u16,
+    field_type: RfcType,
+) -> Result<()> {
+    let len = if field_type == RfcType::Date { 8 } else { 6 };
+    let s = container(handle)?.with_field(index, |meta, v| {
+        if meta.field_type != field_type {
+            return Err(Failure::conversion(&format!(
+                "{} is not of type {:?}",
+                meta.name, field_type
+            )));
+        }
+        meta.to_chars(v)
+    })?;
+    write_padded(value, len, &s)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcSetDateByIndex(
+    handle: *mut c_void,
+    index: u32,
+    value: *const u16,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || set_date_time(handle, index, value, RfcType::Date))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcGetDateByIndex(
+    handle: *mut c_void,
+    index: u32,
+    value: *mut u16,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || get_date_time(handle, index, value, RfcType::Date))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcSetTimeByIndex(
+    handle: *mut c_void,
+    index: u32,
+    value: *const u16,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || set_date_time(handle, index, value, RfcType::Time))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcGetTimeByIndex(
+    handle: *mut c_void,
+    index: u32,
+    value: *mut u16,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || get_date_time(handle, index, value, RfcType::Time))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcGetStringLengthByIndex(
+    handle: *mut c_void,
+    index: u32,
+    length: *mut u32,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || {
+        *length = container(handle)?.with_field(index, |meta, v| match v {
+            Value::Bytes(b) if meta.field_type == RfcType::XString => Ok(b.len() as u32),
+            _ => Ok(meta.to_chars(v)?.encode_utf16().count() as u32),
+        })?;
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcGetStringByIndex(
+    handle: *mut c_void,
+    index: u32,
+    buf: *mut u16,
+    buf_len: u32,
+    out_len: *mut u32,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || {
+        let s = container(handle)?.with_field(index, |meta, v| meta.to_chars(v))?;
+        let encoded: Vec<u16> = s.encode_utf16().collect();
+        *out_len = encoded.len() as u32;
+        if encoded.len() + 1 > buf_len as usize {
+            return Err(Failure::new(
+                RfcRc::BufferTooSmall,
+                "RFC_BUFFER_TOO_SMALL",
+                &format!("Buffer of {} characters too small", buf_len),
+            ));
+        }
+        std::ptr::copy_nonoverlapping(encoded.as_ptr(), buf, encoded.len());
+        *buf.add(encoded.len()) = 0;
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcSetXStringByIndex(
+    handle: *mut c_void,
+    index: u32,
+    value: *const u8,
+    len: u32,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || {
+        let bytes = if value.is_null() {
+            &[][..]
+        } else {
+            std::slice::from_raw_parts(value, len as usize)
+        };
+        container(handle)?.with_field(index, |meta, v| {
+            assign(v, meta.convert_bytes(bytes)?);
+            Ok(())
+        })
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcGetXStringByIndex(
+    handle: *mut c_void,
+    index: u32,
+    value: *mut u8,
+    buf_len: u32,
+    out_len: *mut u32,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || {
+        let bytes = container(handle)?.with_field(index, |meta, v| meta.to_bytes(v))?;
+        *out_len = bytes.len() as u32;
+        if bytes.len() > buf_len as usize {
+            return Err(Failure::new(
+                RfcRc::BufferTooSmall,
+                "RFC_BUFFER_TOO_SMALL",
+                &format!("Buffer of {} bytes too small", buf_len),
+            ));
+        }
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), value, bytes.len());
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcSetBytesByIndex(
+    handle: *mut c_void,
+    index: u32,
+    value: *const u8,
+    len: u32,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || {
+        let bytes = if value.is_null() {
+            &[][..]
+        } else {
+            std::slice::from_raw_parts(value, len as usize)
+        };
+        container(handle)?.with_field(index, |meta, v| {
+            if meta.field_type != RfcType::Byte {
+                return Err(Failure::conversion(&format!("{} is not RAW", meta.name)));
+            }
+            assign(v, meta.convert_bytes(bytes)?);
+            Ok(())
+        })
+    })
+}
+
+/// Unlike RfcGetXString there is no length out parameter, the buffer
+/// receives the value of the field padded with zeros
+#[no_mangle]
+pub unsafe extern "C" fn RfcGetBytesByIndex(
+    handle: *mut c_void,
+    index: u32,
+    value: *mut u8,
+    buf_len: u32,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || {
+        let bytes = container(handle)?.with_field(index, |meta, v| meta.to_bytes(v))?;
+        if bytes.len() > buf_len as usize {
+            return Err(Failure::new(
+                RfcRc::BufferTooSmall,
+                "RFC_BUFFER_TOO_SMALL",
+                &format!("Buffer of {} bytes too small", buf_len),
+            ));
+        }
+        let out = std::slice::from_raw_parts_mut(value, buf_len as usize);
+        out.fill(0);
+        out[..bytes.len()].copy_from_slice(&bytes);
+        Ok(())
+    })
+}
+
+/// Decimal floating point fields keep their value as text, the setters
+/// and getters convert from and to the DPD encoding
+unsafe fn set_decfloat(
+    handle: *mut c_void,
+    index: u32,
+    field_type: RfcType,
+    bits: u128,
+) -> Result<()> {
+    container(handle)?.with_field(index, |meta, v| {
+        let format = match meta.field_type {
+            RfcType::Decf16 if field_type == RfcType::Decf16 => &DECIMAL64,
+            RfcType::Decf34 if field_type == RfcType::Decf34 => &DECIMAL128,
+            _ => {
+                return Err(Failure::conversion(&format!(
+                    "{} is not of type {:?}",
+                    meta.name, field_type
+                )))
+            }
+        };
+        assign(v, meta.parse_chars(&format.decode(bits)?)?);
+        Ok(())
+    })
+}
+
+unsafe fn get_decfloat(handle: *mut c_void, index: u32, field_type: RfcType) -> Result<u128> {
+    container(handle)?.with_field(index, |meta, v| {
+        let format = match field_type {
+            RfcType::Decf16 => &DECIMAL64,
+            _ => &DECIMAL128,
+        };
+        match v {
+            Value::Number(s)
+                if meta.field_type == RfcType::Bcd || meta.field_type == field_type =>
+            {
+                format.encode(s)
+            }
+            Value::Int(i) => format.encode(&i.to_string()),
+            Value::Float(f) => format.encode(&format!("{:E}", f)),
+            _ => Err(Failure::conversion(&format!(
+                "{} cannot be read as {:?}",
+                meta.name, field_type
+            ))),
+        }
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcSetDecF16ByIndex(
+    handle: *mut c_void,
+    index: u32,
+    value: RfcDecF16,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || {
+        let bits = u64::from_ne_bytes(value.bytes) as u128;
+        set_decfloat(handle, index, RfcType::Decf16, bits)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcGetDecF16ByIndex(
+    handle: *mut c_void,
+    index: u32,
+    value: *mut RfcDecF16,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || {
+        let bits = get_decfloat(handle, index, RfcType::Decf16)? as u64;
+        *value = RfcDecF16 {
+            bytes: bits.to_ne_bytes(),
+        };
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcSetDecF34ByIndex(
+    handle: *mut c_void,
+    index: u32,
+    value: RfcDecF34,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || {
+        let bits = u128::from_ne_bytes(value.bytes);
+        set_decfloat(handle, index, RfcType::Decf34, bits)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcGetDecF34ByIndex(
+    handle: *mut c_void,
+    index: u32,
+    value: *mut RfcDecF34,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || {
+        let bits = get_decfloat(handle, index, RfcType::Decf34)?;
+        *value = RfcDecF34 {
+            bytes: bits.to_ne_bytes(),
+        };
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcGetRowCount(
+    handle: *mut c_void,
+    row_count: *mut u32,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || {
+        *row_count = table(handle)?.rows.len() as u32;
+        Ok(())
+    })
+}
+
+fn move_eof() -> Failure {
+    Failure::new(
+        RfcRc::TableMoveEof,
+        "RFC_TABLE_MOVE_EOF",
+        "Cannot move behind the last row",
+    )
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcMoveTo(
+    handle: *mut c_void,
+    index: u32,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || {
+        let t = table(handle)?;
+        if index as usize >= t.rows.len() {
+            return Err(move_eof());
+        }
+        t.cursor = Some(index as usize);
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcMoveToFirstRow(handle: *mut c_void, err: *mut RfcErrorInfo) -> RfcRc {
+    RfcMoveTo(handle, 0, err)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcMoveToLastRow(handle: *mut c_void, err: *mut RfcErrorInfo) -> RfcRc {
+    outcome(err, || {
+        let t = table(handle)?;
+        if t.rows.is_empty() {
+            return Err(move_eof());
+        }
+        t.cursor = Some(t.rows.len() - 1);
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcMoveToNextRow(handle: *mut c_void, err: *mut RfcErrorInfo) -> RfcRc {
+    outcome(err, || {
+        let t = table(handle)?;
+        match t.cursor {
+            Some(i) if i + 1 < t.rows.len() => {
+                t.cursor = Some(i + 1);
+                Ok(())
+            }
+            _ => Err(move_eof()),
+        }
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcMoveToPreviousRow(
+    handle: *mut c_void,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || {
+        let t = table(handle)?;
+        match t.cursor {
+            Some(i) if i > 0 && i <= t.rows.len() => {
+                t.cursor = Some(i - 1);
+                Ok(())
+            }
+            _ => Err(Failure::new(
+                RfcRc::TableMoveBof,
+                "RFC_TABLE_MOVE_BOF",
+                "Cannot move before the first row",
+            )),
+        }
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcAppendNewRow(
+    handle: *mut c_void,
+    err: *mut RfcErrorInfo,
+) -> *mut c_void {
+    handle_outcome(err, || {
+        let t = table(handle)?;
+        let line_type = t.type_desc().cloned().ok_or_else(invalid_handle)?;
+        t.rows.push(Container::structure(line_type));
+        t.cursor = Some(t.rows.len() - 1);
+        let row = t.rows.last_mut().ok_or_else(invalid_handle)?;
+        Ok(&mut **row as *mut Container as *mut c_void)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcAppendNewRows(
+    handle: *mut c_void,
+    row_count: u32,
+    err: *mut RfcErrorInfo,
+) -> RfcRc {
+    outcome(err, || {
+        let t = table(handle)?;
+        let line_type = t.type_desc().cloned().ok_or_else(invalid_handle)?;
+        let first = t.rows.len();
+        for _ in 0..row_count {
+            t.rows.push(Container::structure(line_type.clone()));
+        }
+        if row_count > 0 {
+            t.cursor = Some(first);
+        }
+        Ok(())
+    })
+}
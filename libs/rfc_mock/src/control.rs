@@ -0,0 +1,84 @@
+//! Entry points that are not part of the SDK. Tests use them, through
+//! [`MockLib`](crate::MockLib), to script the library after loading it.
+#![allow(non_snake_case, clippy::missing_safety_doc)]
+
+use std::ffi::{c_char, CStr, CString};
+use std::ptr::null_mut;
+
+use crate::registry::registry;
+use crate::script::{FunctionModule, LogonFailure};
+
+unsafe fn parse<T: serde::de::DeserializeOwned>(json: *const c_char) -> Option<T> {
+    if json.is_null() {
+        return None;
+    }
+    match serde_json::from_slice(CStr::from_ptr(json).to_bytes()) {
+        Ok(v) => Some(v),
+        Err(e) => {
+            eprintln!("sapnwrfc mock: invalid script: {}", e);
+            None
+        }
+    }
+}
+
+fn to_c(value: &impl serde::Serialize) -> *mut c_char {
+    serde_json::to_string(value)
+        .ok()
+        .and_then(|s| CString::new(s).ok())
+        .map(CString::into_raw)
+        .unwrap_or(null_mut())
+}
+
+/// Install or replace a function module, described as JSON. Returns 0 on
+/// success.
+#[no_mangle]
+pub unsafe extern "C" fn RfcMockInstall(json: *const c_char) -> i32 {
+    match parse::<FunctionModule>(json) {
+        Some(module) => {
+            registry().install(&module);
+            0
+        }
+        None => 1,
+    }
+}
+
+/// Script failing logons. Returns 0 on success.
+#[no_mangle]
+pub unsafe extern "C" fn RfcMockFailLogons(json: *const c_char) -> i32 {
+    match parse::<LogonFailure>(json) {
+        Some(f) => {
+            registry().fail_logons(&f.user, f.count, f.error);
+            0
+        }
+        None => 1,
+    }
+}
+
+/// Inputs of every invocation of a function module as JSON array. The
+/// result has to be released with `RfcMockFreeString`.
+#[no_mangle]
+pub unsafe extern "C" fn RfcMockCalls(name: *const c_char) -> *mut c_char {
+    if name.is_null() {
+        return null_mut();
+    }
+    let name = CStr::from_ptr(name).to_string_lossy();
+    to_c(&registry().calls(&name))
+}
+
+/// Logon bookkeeping of one user as JSON. The result has to be released with
+/// `RfcMockFreeString`.
+#[no_mangle]
+pub unsafe extern "C" fn RfcMockLogons(user: *const c_char) -> *mut c_char {
+    if user.is_null() {
+        return null_mut();
+    }
+    let user = CStr::from_ptr(user).to_string_lossy();
+    to_c(&registry().logons(&user))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn RfcMockFreeString(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
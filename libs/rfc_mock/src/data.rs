@@ -0,0 +1,679 @@
+//! Metadata and data containers behind the opaque SDK handles.
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::Arc;
+
+use serde_json::{Map, Number, Value as Json};
+
+use crate::ffi::{RfcDirection, RfcRc, RfcType};
+use crate::script::{self, DataType, Direction};
+
+/// An error as it ends up in RFC_ERROR_INFO
+#[derive(Debug, Clone)]
+pub struct Failure {
+    pub code: RfcRc,
+    pub key: String,
+    pub message: String,
+}
+
+impl Failure {
+    pub fn new(code: RfcRc, key: &str, message: &str) -> Failure {
+        Failure {
+            code,
+            key: key.into(),
+            message: message.into(),
+        }
+    }
+
+    pub fn conversion(message: &str) -> Failure {
+        Failure::new(RfcRc::ConversionFailure, "RFC_CONVERSION_FAILURE", message)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Failure>;
+
+/// Description of one field of a structure, or of one function parameter
+#[derive(Debug, Clone)]
+pub struct FieldMeta {
+    pub name: String,
+    pub field_type: RfcType,
+    pub nuc_length: u32,
+    pub uc_length: u32,
+    pub nuc_offset: u32,
+    pub uc_offset: u32,
+    pub decimals: u32,
+    pub type_desc: Option<Arc<TypeDesc>>,
+}
+
+/// RFC_TYPE_DESC_HANDLE
+#[derive(Debug)]
+pub struct TypeDesc {
+    pub fields: Vec<FieldMeta>,
+    pub nuc_length: u32,
+    pub uc_length: u32,
+}
+
+/// One entry of RFC_FUNCTION_DESC_HANDLE
+#[derive(Debug)]
+pub struct ParamMeta {
+    pub field: FieldMeta,
+    pub direction: RfcDirection,
+    pub optional: bool,
+    pub default: Option<String>,
+}
+
+/// RFC_FUNCTION_DESC_HANDLE
+#[derive(Debug)]
+pub struct FunctionDesc {
+    pub name: String,
+    pub parameters: Vec<ParamMeta>,
+    pub responses: Vec<script::Response>,
+    pub invocations: AtomicUsize,
+}
+
+impl FieldMeta {
+    fn from_script(name: &str, data_type: &DataType) -> FieldMeta {
+        let (field_type, nuc_length, uc_length, decimals) = match data_type {
+            DataType::Char { length } => (RfcType::Char, *length, 2 * length, 0),
+            DataType::Num { length } => (RfcType::Num, *length, 2 * length, 0),
+            DataType::Date => (RfcType::Date, 8, 16, 0),
+            DataType::Time => (RfcType::Time, 6, 12, 0),
+            DataType::Bcd { length, decimals } => (RfcType::Bcd, *length, *length, *decimals),
+            DataType::Int => (RfcType::Int, 4, 4, 0),
+            DataType::Int1 => (RfcType::Int1, 1, 1, 0),
+            DataType::Int2 => (RfcType::Int2, 2, 2, 0),
+            DataType::Int8 => (RfcType::Int8, 8, 8, 0),
+            DataType::Float => (RfcType::Float, 8, 8, 0),
+            DataType::String => (RfcType::String, 8, 8, 0),
+            DataType::Xstring => (RfcType::XString, 8, 8, 0),
+            DataType::Byte { length } => (RfcType::Byte, *length, *length, 0),
+            DataType::Decf16 => (RfcType::Decf16, 8, 8, 0),
+            DataType::Decf34 => (RfcType::Decf34, 16, 16, 0),
+            DataType::Utclong => (RfcType::UtcLong, 8, 8, 0),
+            DataType::Structure { fields } => {
+                let td = TypeDesc::from_script(fields);
+                return FieldMeta {
+                    name: name.into(),
+                    field_type: RfcType::Structure,
+                    nuc_length: td.nuc_length,
+                    uc_length: td.uc_length,
+                    nuc_offset: 0,
+                    uc_offset: 0,
+                    decimals: 0,
+                    type_desc: Some(Arc::new(td)),
+                };
+            }
+            DataType::Table { fields } => {
+                let td = TypeDesc::from_script(fields);
+                return FieldMeta {
+                    name: name.into(),
+                    field_type: RfcType::Table,
+                    nuc_length: 8,
+                    uc_length: 8,
+                    nuc_offset: 0,
+                    uc_offset: 0,
+                    decimals: 0,
+                    type_desc: Some(Arc::new(td)),
+                };
+            }
+        };
+        FieldMeta {
+            name: name.into(),
+            field_type,
+            nuc_length,
+            uc_length,
+            nuc_offset: 0,
+            uc_offset: 0,
+            decimals,
+            type_desc: None,
+        }
+    }
+
+    /// The value a freshly created container holds for this field
+    pub fn initial(&self) -> Value {
+        let len = self.nuc_length as usize;
+        match self.field_type {
+            RfcType::Char => Value::Chars(" ".repeat(len)),
+            RfcType::Num | RfcType::Date | RfcType::Time => Value::Chars("0".repeat(len)),
+            RfcType::Bcd | RfcType::Decf16 | RfcType::Decf34 => Value::Number(self.zero()),
+            RfcType::Int | RfcType::Int1 | RfcType::Int2 | RfcType::Int8 => Value::Int(0),
+            RfcType::Float => Value::Float(0.0),
+            RfcType::String | RfcType::UtcLong => Value::Text(String::new()),
+            RfcType::Byte => Value::Bytes(vec![0; len]),
+            RfcType::XString => Value::Bytes(Vec::new()),
+            RfcType::Structure => Value::Structure(Container::structure(self.line_type())),
+            RfcType::Table => Value::Table(Container::table(self.line_type())),
+        }
+    }
+
+    fn line_type(&self) -> Arc<TypeDesc> {
+        self.type_desc
+            .clone()
+            .expect("structured field without type")
+    }
+
+    fn zero(&self) -> String {
+        if self.field_type == RfcType::Bcd && self.decimals > 0 {
+            format!("0.{}", "0".repeat(self.decimals as usize))
+        } else {
+            "0".into()
+        }
+    }
+
+    fn is_int(&self) -> bool {
+        matches!(
+            self.field_type,
+            RfcType::Int | RfcType::Int1 | RfcType::Int2 | RfcType::Int8
+        )
+    }
+
+    fn not_supported(&self, what: &str) -> Failure {
+        Failure::conversion(&format!(
+            "Cannot {} field {} of type {:?}",
+            what, self.name, self.field_type
+        ))
+    }
+
+    /// RfcSetChars and RfcSetString semantics
+    pub fn parse_chars(&self, value: &str) -> Result<Value> {
+        let len = self.nuc_length as usize;
+        match self.field_type {
+            RfcType::Char => {
+                let mut s: String = value.chars().take(len).collect();
+                let pad = len - s.chars().count();
+                s.push_str(&" ".repeat(pad));
+                Ok(Value::Chars(s))
+            }
+            RfcType::Num | RfcType::Date | RfcType::Time => {
+                let digits = value.trim();
+                if !digits.chars().all(|c| c.is_ascii_digit()) {
+                    return Err(Failure::conversion(&format!(
+                        "Value '{}' for field {} contains non-digits",
+                        value, self.name
+                    )));
+                }
+                if digits.len() > len {
+                    return Err(Failure::conversion(&format!(
+                        "Value '{}' does not fit into field {} of length {}",
+                        value, self.name, len
+                    )));
+                }
+                Ok(Value::Chars(format!("{:0>len$}", digits, len = len)))
+            }
+            RfcType::Bcd | RfcType::Decf16 | RfcType::Decf34 => {
+                Ok(Value::Number(self.parse_number(value.trim())?))
+            }
+            RfcType::Int | RfcType::Int1 | RfcType::Int2 | RfcType::Int8 => {
+                let trimmed = value.trim();
+                let v = if trimmed.is_empty() {
+                    0
+                } else {
+                    trimmed.parse::<i64>().map_err(|_| {
+                        Failure::conversion(&format!(
+                            "Value '{}' for field {} is not an integer",
+                            value, self.name
+                        ))
+                    })?
+                };
+                self.convert_int(v)
+            }
+            RfcType::Float => {
+                let trimmed = value.trim();
+                let v = if trimmed.is_empty() {
+                    0.0
+                } else {
+                    trimmed.parse::<f64>().map_err(|_| {
+                        Failure::conversion(&format!(
+                            "Value '{}' for field {} is not a number",
+                            value, self.name
+                        ))
+                    })?
+                };
+                Ok(Value::Float(v))
+            }
+            RfcType::String | RfcType::UtcLong => Ok(Value::Text(value.into())),
+            RfcType::Byte | RfcType::XString => self.convert_bytes(&decode_hex(value.trim())?),
+            RfcType::Structure | RfcType::Table => Err(self.not_supported("set chars of")),
+        }
+    }
+
+    /// RfcSetInt semantics, including the range checks of the integer types
+    pub fn convert_int(&self, value: i64) -> Result<Value> {
+        let (min, max) = match self.field_type {
+            RfcType::Int1 => (0, u8::MAX as i64),
+            RfcType::Int2 => (i16::MIN as i64, i16::MAX as i64),
+            RfcType::Int => (i32::MIN as i64, i32::MAX as i64),
+            RfcType::Int8 => (i64::MIN, i64::MAX),
+            RfcType::Float => return Ok(Value::Float(value as f64)),
+            RfcType::Structure | RfcType::Table => return Err(self.not_supported("set int of")),
+            _ => return self.parse_chars(&value.to_string()),
+        };
+        if value < min || value > max {
+            return Err(Failure::conversion(&format!(
+                "Value {} out of range for field {} of type {:?}",
+                value, self.name, self.field_type
+            )));
+        }
+        Ok(Value::Int(value))
+    }
+
+    /// RfcSetFloat semantics
+    pub fn convert_float(&self, value: f64) -> Result<Value> {
+        match self.field_type {
+            RfcType::Float => Ok(Value::Float(value)),
+            RfcType::Bcd | RfcType::Decf16 | RfcType::Decf34 => {
+                let s = if self.field_type == RfcType::Bcd {
+                    format!("{:.*}", self.decimals as usize, value)
+                } else {
+                    value.to_string()
+                };
+                self.parse_chars(&s)
+            }
+            _ => Err(self.not_supported("set float of")),
+        }
+    }
+
+    /// RfcSetXString and RfcSetBytes semantics
+    pub fn convert_bytes(&self, value: &[u8]) -> Result<Value> {
+        match self.field_type {
+            RfcType::XString => Ok(Value::Bytes(value.to_vec())),
+            RfcType::Byte => {
+                let len = self.nuc_length as usize;
+                let mut v: Vec<u8> = value.iter().copied().take(len).collect();
+                v.resize(len, 0);
+                Ok(Value::Bytes(v))
+            }
+            _ => Err(self.not_supported("set bytes of")),
+        }
+    }
+
+    fn parse_number(&self, value: &str) -> Result<String> {
+        let invalid = || {
+            Failure::conversion(&format!(
+                "Value '{}' for field {} is not a decimal number",
+                value, self.name
+            ))
+        };
+        if value.is_empty() {
+            return Ok(self.zero());
+        }
+        if self.field_type != RfcType::Bcd {
+            value.parse::<f64>().map_err(|_| invalid())?;
+            return Ok(value.into());
+        }
+        let (negative, unsigned) = match value.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, value.strip_prefix('+').unwrap_or(value)),
+        };
+        let (int_part, frac_part) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+        if int_part.is_empty() && frac_part.is_empty()
+            || !int_part.chars().all(|c| c.is_ascii_digit())
+            || !frac_part.chars().all(|c| c.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+        let decimals = self.decimals as usize;
+        let digits = 2 * self.nuc_length as usize - 1;
+        let int_part = int_part.trim_start_matches('0');
+        let frac_trimmed = frac_part.trim_end_matches('0');
+        if frac_trimmed.len() > decimals || int_part.len() > digits - decimals {
+            return Err(Failure::conversion(&format!(
+                "Value '{}' overflows field {} (P {} DECIMALS {})",
+                value, self.name, digits, decimals
+            )));
+        }
+        let int_part = if int_part.is_empty() { "0" } else { int_part };
+        let mut s = String::new();
+        let is_zero = int_part == "0" && frac_trimmed.is_empty();
+        if negative && !is_zero {
+            s.push('-');
+        }
+        s.push_str(int_part);
+        if decimals > 0 {
+            s.push('.');
+            s.push_str(&format!(
+                "{:0<decimals$}",
+                frac_trimmed,
+                decimals = decimals
+            ));
+        }
+        Ok(s)
+    }
+
+    /// RfcGetChars and RfcGetString semantics
+    pub fn to_chars(&self, value: &Value) -> Result<String> {
+        match value {
+            Value::Chars(s) | Value::Number(s) | Value::Text(s) => Ok(s.clone()),
+            Value::Int(v) => Ok(v.to_string()),
+            Value::Float(v) => Ok(format!("{:E}", v)),
+            Value::Bytes(v) => Ok(encode_hex(v)),
+            Value::Structure(_) | Value::Table(_) => Err(self.not_supported("read chars of")),
+        }
+    }
+
+    /// RfcGetInt semantics: integers, and characters and packed numbers
+    /// holding an integer
+    pub fn to_int(&self, value: &Value) -> Result<i64> {
+        let invalid = |v: &str| {
+            Failure::conversion(&format!(
+                "Value '{}' of field {} is not an integer",
+                v, self.name
+            ))
+        };
+        match value {
+            Value::Int(v) => Ok(*v),
+            Value::Chars(s) | Value::Number(s) | Value::Text(s) => {
+                let trimmed = s.trim();
+                if trimmed.is_empty() {
+                    return Ok(0);
+                }
+                let (int_part, frac_part) = trimmed.split_once('.').unwrap_or((trimmed, ""));
+                if !frac_part.chars().all(|c| c == '0') {
+                    return Err(invalid(s));
+                }
+                int_part.parse().map_err(|_| invalid(s))
+            }
+            _ => Err(self.not_supported("read int of")),
+        }
+    }
+
+    /// RfcGetFloat semantics
+    pub fn to_float(&self, value: &Value) -> Result<f64> {
+        match value {
+            Value::Float(v) => Ok(*v),
+            Value::Int(v) => Ok(*v as f64),
+            Value::Number(s) => s.parse().map_err(|_| {
+                Failure::conversion(&format!(
+                    "Value '{}' of field {} is not a number",
+                    s, self.name
+                ))
+            }),
+            _ => Err(self.not_supported("read float of")),
+        }
+    }
+
+    /// RfcGetXString and RfcGetBytes semantics
+    pub fn to_bytes(&self, value: &Value) -> Result<Vec<u8>> {
+        match value {
+            Value::Bytes(v) => Ok(v.clone()),
+            _ => Err(self.not_supported("read bytes of")),
+        }
+    }
+
+    /// Value as it shows up in the recorded calls of the mock
+    pub fn to_json(&self, value: &Value) -> Json {
+        match value {
+            Value::Chars(s) if self.field_type == RfcType::Char => {
+                Json::String(s.trim_end().into())
+            }
+            Value::Chars(s) | Value::Number(s) | Value::Text(s) => Json::String(s.clone()),
+            Value::Int(v) => Json::Number((*v).into()),
+            Value::Float(v) => Number::from_f64(*v).map(Json::Number).unwrap_or(Json::Null),
+            Value::Bytes(v) => Json::String(encode_hex(v)),
+            Value::Structure(c) => c.to_json_object(),
+            Value::Table(c) => Json::Array(c.rows.iter().map(|r| r.to_json_object()).collect()),
+        }
+    }
+
+    /// Convert a scripted JSON value
+    pub fn parse_json(&self, json: &Json) -> Result<Value> {
+        match (self.field_type, json) {
+            (_, Json::Null) => Ok(self.initial()),
+            (RfcType::Structure, Json::Object(map)) => {
+                let mut c = Container::structure(self.line_type());
+                c.fill_from_json(map)?;
+                Ok(Value::Structure(c))
+            }
+            (RfcType::Table, Json::Array(rows)) => {
+                let mut c = Container::table(self.line_type());
+                for row in rows {
+                    let map = row.as_object().ok_or_else(|| {
+                        Failure::conversion(&format!("Rows of {} must be objects", self.name))
+                    })?;
+                    let mut r = Container::structure(self.line_type());
+                    r.fill_from_json(map)?;
+                    c.rows.push(r);
+                }
+                c.cursor = if c.rows.is_empty() { None } else { Some(0) };
+                Ok(Value::Table(c))
+            }
+            (RfcType::Structure | RfcType::Table, _) => Err(Failure::conversion(&format!(
+                "Unexpected JSON value for {}",
+                self.name
+            ))),
+            (_, Json::Bool(b)) => self.parse_chars(if *b { "X" } else { "" }),
+            (_, Json::Number(n)) if self.is_int() => match n.as_i64() {
+                Some(v) => self.convert_int(v),
+                None => Err(Failure::conversion(&format!("{} is not an integer", n))),
+            },
+            (RfcType::Float, Json::Number(n)) => Ok(Value::Float(n.as_f64().unwrap_or_default())),
+            (_, Json::Number(n)) => self.parse_chars(&n.to_string()),
+            (_, Json::String(s)) => self.parse_chars(s),
+            _ => Err(Failure::conversion(&format!(
+                "Unexpected JSON value for {}",
+                self.name
+            ))),
+        }
+    }
+}
+
+impl TypeDesc {
+    fn from_script(fields: &[script::Field]) -> TypeDesc {
+        let mut metas = Vec::with_capacity(fields.len());
+        let mut nuc_offset = 0;
+        let mut uc_offset = 0;
+        for f in fields {
+            let mut meta = FieldMeta::from_script(&f.name, &f.data_type);
+            meta.nuc_offset = nuc_offset;
+            meta.uc_offset = uc_offset;
+            nuc_offset += meta.nuc_length;
+            uc_offset += meta.uc_length;
+            metas.push(meta);
+        }
+        TypeDesc {
+            fields: metas,
+            nuc_length: nuc_offset,
+            uc_length: uc_offset,
+        }
+    }
+}
+
+impl FunctionDesc {
+    pub fn from_script(module: &script::FunctionModule) -> FunctionDesc {
+        let parameters = module
+            .parameters
+            .iter()
+            .map(|p| ParamMeta {
+                field: FieldMeta::from_script(&p.name, &p.data_type),
+                direction: match p.direction {
+                    Direction::Importing => RfcDirection::Import,
+                    Direction::Exporting => RfcDirection::Export,
+                    Direction::Changing => RfcDirection::Changing,
+                    Direction::Tables => RfcDirection::Tables,
+                },
+                optional: p.optional,
+                default: p.default.clone(),
+            })
+            .collect();
+        FunctionDesc {
+            name: module.name.to_uppercase(),
+            parameters,
+            responses: module.responses.clone(),
+            invocations: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn parameter_index(&self, name: &str) -> Option<usize> {
+        self.parameters
+            .iter()
+            .position(|p| p.field.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// The value of one field
+#[derive(Debug, Clone)]
+pub enum Value {
+    /// CHAR, NUM, DATE and TIME, always exactly as long as the field
+    Chars(String),
+    /// BCD and the decimal floating point types in canonical notation
+    Number(String),
+    Int(i64),
+    Float(f64),
+    /// STRING and UTCLONG
+    Text(String),
+    /// RAW and XSTRING
+    Bytes(Vec<u8>),
+    Structure(Box<Container>),
+    Table(Box<Container>),
+}
+
+#[derive(Debug, Clone)]
+pub enum ContainerKind {
+    Function(Arc<FunctionDesc>),
+    Structure(Arc<TypeDesc>),
+    Table(Arc<TypeDesc>),
+}
+
+/// Anything a DATA_CONTAINER_HANDLE can point to: functions, structures
+/// and tables. Field accessors on a table operate on its current row.
+#[derive(Debug, Clone)]
+pub struct Container {
+    pub kind: ContainerKind,
+    pub values: Vec<Value>,
+    /// Boxed so that row handles stay valid when rows are appended
+    #[allow(clippy::vec_box)]
+    pub rows: Vec<Box<Container>>,
+    pub cursor: Option<usize>,
+}
+
+impl Container {
+    pub fn function(desc: Arc<FunctionDesc>) -> Box<Container> {
+        let values = desc.parameters.iter().map(|p| p.field.initial()).collect();
+        Box::new(Container {
+            kind: ContainerKind::Function(desc),
+            values,
+            rows: Vec::new(),
+            cursor: None,
+        })
+    }
+
+    pub fn structure(desc: Arc<TypeDesc>) -> Box<Container> {
+        let values = desc.fields.iter().map(|f| f.initial()).collect();
+        Box::new(Container {
+            kind: ContainerKind::Structure(desc),
+            values,
+            rows: Vec::new(),
+            cursor: None,
+        })
+    }
+
+    pub fn table(desc: Arc<TypeDesc>) -> Box<Container> {
+        Box::new(Container {
+            kind: ContainerKind::Table(desc),
+            values: Vec::new(),
+            rows: Vec::new(),
+            cursor: None,
+        })
+    }
+
+    /// Type of a structure, or line type of a table
+    pub fn type_desc(&self) -> Option<&Arc<TypeDesc>> {
+        match &self.kind {
+            ContainerKind::Function(_) => None,
+            ContainerKind::Structure(td) | ContainerKind::Table(td) => Some(td),
+        }
+    }
+
+    pub fn field_count(&self) -> usize {
+        match &self.kind {
+            ContainerKind::Function(fd) => fd.parameters.len(),
+            ContainerKind::Structure(td) | ContainerKind::Table(td) => td.fields.len(),
+        }
+    }
+
+    pub fn meta(&self, index: usize) -> Option<&FieldMeta> {
+        match &self.kind {
+            ContainerKind::Function(fd) => fd.parameters.get(index).map(|p| &p.field),
+            ContainerKind::Structure(td) | ContainerKind::Table(td) => td.fields.get(index),
+        }
+    }
+
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        (0..self.field_count()).find(|i| {
+            self.meta(*i)
+                .map(|m| m.name.eq_ignore_ascii_case(name))
+                .unwrap_or(false)
+        })
+    }
+
+    /// The container whose fields are accessed: the current row for tables
+    pub fn target(&mut self) -> Result<&mut Container> {
+        if let ContainerKind::Table(_) = self.kind {
+            match self.cursor {
+                Some(i) if i < self.rows.len() => Ok(&mut self.rows[i]),
+                _ => Err(Failure::new(
+                    RfcRc::TableMoveEof,
+                    "RFC_TABLE_MOVE_EOF",
+                    "The table has no current row",
+                )),
+            }
+        } else {
+            Ok(self)
+        }
+    }
+
+    /// Run `f` with the metadata and value of field `index`
+    pub fn with_field<T>(
+        &mut self,
+        index: u32,
+        f: impl FnOnce(&FieldMeta, &mut Value) -> Result<T>,
+    ) -> Result<T> {
+        let target = self.target()?;
+        let meta = target.meta(index as usize).cloned().ok_or_else(|| {
+            Failure::new(
+                RfcRc::InvalidParameter,
+                "RFC_INVALID_PARAMETER",
+                &format!("Field index {} out of range", index),
+            )
+        })?;
+        f(&meta, &mut target.values[index as usize])
+    }
+
+    pub fn fill_from_json(&mut self, map: &Map<String, Json>) -> Result<()> {
+        for (name, json) in map {
+            let index = self.index_of(name).ok_or_else(|| {
+                Failure::new(
+                    RfcRc::NotFound,
+                    "RFC_NOT_FOUND",
+                    &format!("Field {} not found", name),
+                )
+            })?;
+            let meta = self.meta(index).cloned().unwrap();
+            self.values[index] = meta.parse_json(json)?;
+        }
+        Ok(())
+    }
+
+    pub fn to_json_object(&self) -> Json {
+        let mut map = Map::new();
+        for (i, v) in self.values.iter().enumerate() {
+            let meta = self.meta(i).unwrap();
+            map.insert(meta.name.clone(), meta.to_json(v));
+        }
+        Json::Object(map)
+    }
+}
+
+fn encode_hex(v: &[u8]) -> String {
+    v.iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+fn decode_hex(s: &str) -> Result<Vec<u8>> {
+    if !s.len().is_multiple_of(2) || !s.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(Failure::conversion(&format!("'{}' is not a hex string", s)));
+    }
+    Ok((0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect())
+}
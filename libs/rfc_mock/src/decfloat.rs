@@ -0,0 +1,134 @@
+//! RFC_DECF16 and RFC_DECF34: IEEE 754 decimal64 and decimal128 in densely
+//! packed decimal encoding. The stand-in keeps decimal floating point
+//! values as text and only converts at the API boundary.
+
+use crate::data::{Failure, Result};
+
+pub struct Format {
+    declets: u32,
+    exponent_bits: u32,
+    bias: i32,
+}
+
+pub const DECIMAL64: Format = Format {
+    declets: 5,
+    exponent_bits: 8,
+    bias: 398,
+};
+
+pub const DECIMAL128: Format = Format {
+    declets: 11,
+    exponent_bits: 12,
+    bias: 6176,
+};
+
+fn declet(value: u32) -> u32 {
+    let d = [value / 100, value / 10 % 10, value % 10];
+    let bit = |digit: usize, n: u32| d[digit] >> n & 1;
+    let (b, c, dd) = (bit(0, 2), bit(0, 1), bit(0, 0));
+    let (f, g, h) = (bit(1, 2), bit(1, 1), bit(1, 0));
+    let (j, k, m) = (bit(2, 2), bit(2, 1), bit(2, 0));
+    let pattern = match (d[0] > 7, d[1] > 7, d[2] > 7) {
+        (false, false, false) => [b, c, dd, f, g, h, 0, j, k, m],
+        (false, false, true) => [b, c, dd, f, g, h, 1, 0, 0, m],
+        (false, true, false) => [b, c, dd, j, k, h, 1, 0, 1, m],
+        (true, false, false) => [j, k, dd, f, g, h, 1, 1, 0, m],
+        (true, true, false) => [j, k, dd, 0, 0, h, 1, 1, 1, m],
+        (true, false, true) => [f, g, dd, 0, 1, h, 1, 1, 1, m],
+        (false, true, true) => [b, c, dd, 1, 0, h, 1, 1, 1, m],
+        (true, true, true) => [0, 0, dd, 1, 1, h, 1, 1, 1, m],
+    };
+    pattern.iter().fold(0, |acc, b| acc << 1 | b)
+}
+
+fn undeclet(bits: u32) -> u32 {
+    (0..1000)
+        .find(|v| declet(*v) == bits || (bits & 0x6e == 0x6e && declet(*v) == bits & 0xff))
+        .unwrap_or(0)
+}
+
+impl Format {
+    fn width(&self) -> u32 {
+        6 + self.exponent_bits + 10 * self.declets
+    }
+
+    /// Encode a number in decimal or scientific notation
+    pub fn encode(&self, text: &str) -> Result<u128> {
+        let invalid = || Failure::conversion(&format!("'{}' is not a decimal number", text));
+        let text = text.trim();
+        let (negative, unsigned) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text.strip_prefix('+').unwrap_or(text)),
+        };
+        let (mantissa, exponent) = match unsigned.split_once(['E', 'e']) {
+            Some((m, e)) => (m, e.parse::<i32>().map_err(|_| invalid())?),
+            None => (unsigned, 0),
+        };
+        let (int_part, frac_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        let digits = format!("{}{}", int_part, frac_part);
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let digits = digits.trim_start_matches('0');
+        if digits.len() > 3 * self.declets as usize + 1 {
+            return Err(Failure::conversion(&format!(
+                "'{}' has too many digits",
+                text
+            )));
+        }
+        let mut coefficient: u128 = if digits.is_empty() {
+            0
+        } else {
+            digits.parse().unwrap()
+        };
+        let biased = exponent - frac_part.len() as i32 + self.bias;
+        if biased < 0 || biased >= 3 << self.exponent_bits {
+            return Err(Failure::conversion(&format!(
+                "The exponent of '{}' is out of range",
+                text
+            )));
+        }
+        let biased = biased as u128;
+
+        let mut bits = 0;
+        for i in 0..self.declets {
+            bits |= (declet((coefficient % 1000) as u32) as u128) << (10 * i);
+            coefficient /= 1000;
+        }
+        let msb = biased >> self.exponent_bits;
+        let combination = if coefficient < 8 {
+            msb << 3 | coefficient
+        } else {
+            0b11000 | msb << 1 | (coefficient - 8)
+        };
+        let coefficient_bits = 10 * self.declets;
+        bits |= (biased & ((1 << self.exponent_bits) - 1)) << coefficient_bits;
+        bits |= combination << (coefficient_bits + self.exponent_bits);
+        bits |= (negative as u128) << (self.width() - 1);
+        Ok(bits)
+    }
+
+    /// Decode into scientific notation
+    pub fn decode(&self, bits: u128) -> Result<String> {
+        let coefficient_bits = 10 * self.declets;
+        let combination = bits >> (coefficient_bits + self.exponent_bits) & 0b11111;
+        let (msb, mut coefficient) = if combination >> 3 != 0b11 {
+            (combination >> 3, combination & 0b111)
+        } else if combination >> 1 & 0b11 != 0b11 {
+            (combination >> 1 & 0b11, 8 + (combination & 1))
+        } else {
+            return Err(Failure::conversion("Infinity and NaN are not supported"));
+        };
+        for i in (0..self.declets).rev() {
+            coefficient = coefficient * 1000 + undeclet((bits >> (10 * i) & 0x3ff) as u32) as u128;
+        }
+        let continuation = bits >> coefficient_bits & ((1 << self.exponent_bits) - 1);
+        let exponent = (msb << self.exponent_bits | continuation) as i32 - self.bias;
+        let sign = if bits >> (self.width() - 1) & 1 == 1 {
+            "-"
+        } else {
+            ""
+        };
+        Ok(format!("{}{}E{}", sign, coefficient, exponent))
+    }
+}
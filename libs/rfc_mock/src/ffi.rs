@@ -0,0 +1,211 @@
+//! C level types of sapnwrfc.h, as far as the stand-in library needs them.
+
+use serde::{Deserialize, Serialize};
+
+/// Return codes of the RFC API (RFC_RC)
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RfcRc {
+    Ok,
+    CommunicationFailure,
+    LogonFailure,
+    AbapRuntimeFailure,
+    AbapMessage,
+    AbapException,
+    Closed,
+    Canceled,
+    Timeout,
+    MemoryInsufficient,
+    VersionMismatch,
+    InvalidProtocol,
+    SerializationFailure,
+    InvalidHandle,
+    Retry,
+    ExternalFailure,
+    Executed,
+    NotFound,
+    NotSupported,
+    IllegalState,
+    InvalidParameter,
+    CodepageConversionFailure,
+    ConversionFailure,
+    BufferTooSmall,
+    TableMoveBof,
+    TableMoveEof,
+    StartSapguiFailure,
+    AbapClassException,
+    UnknownError,
+    AuthorizationFailure,
+    AuthenticationFailure,
+    CryptolibFailure,
+    IoFailure,
+    LockingFailure,
+}
+
+/// Error groups of the RFC API (RFC_ERROR_GROUP)
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RfcErrorGroup {
+    Ok,
+    AbapApplicationFailure,
+    AbapRuntimeFailure,
+    LogonFailure,
+    CommunicationFailure,
+    ExternalRuntimeFailure,
+    ExternalApplicationFailure,
+    ExternalAuthorizationFailure,
+    ExternalAuthenticationFailure,
+    CryptolibFailure,
+    LockingFailure,
+}
+
+impl RfcRc {
+    /// The error group the SDK reports together with this code
+    pub fn group(&self) -> RfcErrorGroup {
+        match self {
+            RfcRc::Ok => RfcErrorGroup::Ok,
+            RfcRc::CommunicationFailure | RfcRc::Closed | RfcRc::Timeout | RfcRc::Canceled => {
+                RfcErrorGroup::CommunicationFailure
+            }
+            RfcRc::LogonFailure => RfcErrorGroup::LogonFailure,
+            RfcRc::AbapRuntimeFailure | RfcRc::AbapMessage => RfcErrorGroup::AbapRuntimeFailure,
+            RfcRc::AbapException | RfcRc::AbapClassException => {
+                RfcErrorGroup::AbapApplicationFailure
+            }
+            RfcRc::AuthorizationFailure => RfcErrorGroup::ExternalAuthorizationFailure,
+            RfcRc::AuthenticationFailure => RfcErrorGroup::ExternalAuthenticationFailure,
+            RfcRc::CryptolibFailure => RfcErrorGroup::CryptolibFailure,
+            RfcRc::LockingFailure => RfcErrorGroup::LockingFailure,
+            _ => RfcErrorGroup::ExternalRuntimeFailure,
+        }
+    }
+}
+
+/// RFCTYPE
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RfcType {
+    Char = 0,
+    Date = 1,
+    Bcd = 2,
+    Time = 3,
+    Byte = 4,
+    Table = 5,
+    Num = 6,
+    Float = 7,
+    Int = 8,
+    Int2 = 9,
+    Int1 = 10,
+    Structure = 17,
+    Decf16 = 23,
+    Decf34 = 24,
+    String = 29,
+    XString = 30,
+    Int8 = 31,
+    UtcLong = 32,
+}
+
+/// RFC_DIRECTION
+#[repr(u32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RfcDirection {
+    Import = 1,
+    Export = 2,
+    Changing = 3,
+    Tables = 7,
+}
+
+/// RFC_ERROR_INFO
+#[repr(C)]
+pub struct RfcErrorInfo {
+    pub code: RfcRc,
+    pub group: RfcErrorGroup,
+    pub key: [u16; 128],
+    pub message: [u16; 512],
+    pub abap_msg_class: [u16; 21],
+    pub abap_msg_type: [u16; 2],
+    pub abap_msg_number: [u16; 4],
+    pub abap_msg_v1: [u16; 51],
+    pub abap_msg_v2: [u16; 51],
+    pub abap_msg_v3: [u16; 51],
+    pub abap_msg_v4: [u16; 51],
+}
+
+/// RFC_CONNECTION_PARAMETER
+#[repr(C)]
+pub struct RfcConnectionParameter {
+    pub name: *const u16,
+    pub value: *const u16,
+}
+
+/// RFC_FIELD_DESC
+#[repr(C)]
+pub struct RfcFieldDesc {
+    pub name: [u16; 31],
+    pub field_type: RfcType,
+    pub nuc_length: u32,
+    pub nuc_offset: u32,
+    pub uc_length: u32,
+    pub uc_offset: u32,
+    pub decimals: u32,
+    pub type_desc_handle: *const std::ffi::c_void,
+    pub extended_description: *mut std::ffi::c_void,
+}
+
+/// RFC_DECF16, aligned like the double of the SDK union
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub union RfcDecF16 {
+    pub bytes: [u8; 8],
+    pub align: f64,
+}
+
+/// RFC_DECF34
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub union RfcDecF34 {
+    pub bytes: [u8; 16],
+    pub align: f64,
+}
+
+/// RFC_PARAMETER_DESC
+#[repr(C)]
+pub struct RfcParameterDesc {
+    pub name: [u16; 31],
+    pub field_type: RfcType,
+    pub direction: RfcDirection,
+    pub nuc_length: u32,
+    pub uc_length: u32,
+    pub decimals: u32,
+    pub type_desc_handle: *const std::ffi::c_void,
+    pub default_value: [u16; 31],
+    pub parameter_text: [u16; 80],
+    pub optional: u8,
+    pub extended_description: *mut std::ffi::c_void,
+}
+
+/// Copy `value` into a fixed size, zero terminated SAP_UC buffer
+pub fn fill(buf: &mut [u16], value: &str) {
+    buf.fill(0);
+    let max = buf.len().saturating_sub(1);
+    for (dst, src) in buf.iter_mut().take(max).zip(value.encode_utf16()) {
+        *dst = src;
+    }
+}
+
+/// Read a zero terminated SAP_UC string
+///
+/// # Safety
+/// `ptr` must be null or point to a zero terminated UTF-16 string.
+pub unsafe fn read_str(ptr: *const u16) -> String {
+    if ptr.is_null() {
+        return String::new();
+    }
+    let mut len = 0;
+    while *ptr.add(len) != 0 {
+        len += 1;
+    }
+    String::from_utf16_lossy(std::slice::from_raw_parts(ptr, len))
+}
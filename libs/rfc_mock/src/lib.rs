@@ -0,0 +1,39 @@
+//! A stand-in for the SAP NW RFC library.
+//!
+//! The cdylib build of this crate is called libsapnwrfc and exports the
+//! subset of sapnwrfc.h that rsrfc binds. Instead of talking to an SAP
+//! system it executes function modules from an in-memory registry, which
+//! makes it possible to test rsrfc end to end without the proprietary SDK.
+//!
+//! Tests script the library through [`MockLib`], which loads the very same
+//! shared object that rsrfc loads:
+//!
+//! ```no_run
+//! use sapnwrfc::{DataType, FunctionModule, MockLib, Response};
+//!
+//! let mock = MockLib::load().unwrap();
+//! mock.install(
+//!     &FunctionModule::new("Z_ECHO")
+//!         .importing("IV_TEXT", DataType::char(10))
+//!         .exporting("EV_TEXT", DataType::char(10))
+//!         .respond(Response::new().copy("EV_TEXT", "IV_TEXT")),
+//! );
+//! ```
+//!
+//! Add the crate as a dev-dependency so that cargo builds the shared object
+//! next to the test binaries, where the dynamic loader finds it.
+extern crate dlopen;
+#[macro_use]
+extern crate dlopen_derive;
+
+pub mod api;
+pub mod control;
+mod data;
+mod decfloat;
+mod ffi;
+mod mocklib;
+mod registry;
+pub mod script;
+
+pub use mocklib::MockLib;
+pub use script::*;
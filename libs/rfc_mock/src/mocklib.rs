@@ -0,0 +1,74 @@
+#![allow(non_snake_case)]
+
+use std::ffi::{c_char, CStr, CString};
+
+use dlopen::wrapper::{Container, WrapperApi};
+
+use crate::script::*;
+
+#[derive(WrapperApi)]
+struct MockApi {
+    RfcMockInstall: unsafe extern "C" fn(json: *const c_char) -> i32,
+    RfcMockFailLogons: unsafe extern "C" fn(json: *const c_char) -> i32,
+    RfcMockCalls: unsafe extern "C" fn(name: *const c_char) -> *mut c_char,
+    RfcMockLogons: unsafe extern "C" fn(user: *const c_char) -> *mut c_char,
+    RfcMockFreeString: unsafe extern "C" fn(s: *mut c_char),
+}
+
+/// Handle to the loaded stand-in library
+pub struct MockLib {
+    api: Container<MockApi>,
+}
+
+#[cfg(all(target_family = "unix", not(target_vendor = "apple")))]
+const LIB_NAME: &str = "libsapnwrfc.so";
+#[cfg(all(target_family = "unix", target_vendor = "apple"))]
+const LIB_NAME: &str = "libsapnwrfc.dylib";
+#[cfg(target_family = "windows")]
+const LIB_NAME: &str = "sapnwrfc.dll";
+
+impl MockLib {
+    /// Load the library from the dynamic loader's search path
+    pub fn load() -> Result<MockLib, dlopen::Error> {
+        let api = unsafe { Container::load(LIB_NAME) }?;
+        Ok(MockLib { api })
+    }
+
+    /// Install a function module, replacing one with the same name
+    pub fn install(&self, module: &FunctionModule) {
+        let json = CString::new(serde_json::to_string(module).unwrap()).unwrap();
+        let rc = unsafe { self.api.RfcMockInstall(json.as_ptr()) };
+        assert_eq!(rc, 0, "unable to install {}", module.name);
+    }
+
+    /// Let the next `count` logons of `user` fail
+    pub fn fail_logons(&self, user: &str, count: u32, error: ScriptedError) {
+        let f = LogonFailure {
+            user: user.into(),
+            count,
+            error,
+        };
+        let json = CString::new(serde_json::to_string(&f).unwrap()).unwrap();
+        let rc = unsafe { self.api.RfcMockFailLogons(json.as_ptr()) };
+        assert_eq!(rc, 0, "unable to script logon failures");
+    }
+
+    /// Inputs of every invocation of the function module so far
+    pub fn calls(&self, name: &str) -> Vec<serde_json::Value> {
+        let name = CString::new(name).unwrap();
+        self.fetch(unsafe { self.api.RfcMockCalls(name.as_ptr()) })
+    }
+
+    /// Logon bookkeeping of `user`
+    pub fn logons(&self, user: &str) -> Logons {
+        let user = CString::new(user).unwrap();
+        self.fetch(unsafe { self.api.RfcMockLogons(user.as_ptr()) })
+    }
+
+    fn fetch<T: serde::de::DeserializeOwned>(&self, s: *mut c_char) -> T {
+        assert!(!s.is_null());
+        let v = serde_json::from_slice(unsafe { CStr::from_ptr(s) }.to_bytes()).unwrap();
+        unsafe { self.api.RfcMockFreeString(s) };
+        v
+    }
+}
@@ -0,0 +1,96 @@
+//! Process wide state of the stand-in library.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+
+use serde_json::Value as Json;
+
+use crate::data::FunctionDesc;
+use crate::script::{FunctionModule, Logons, ScriptedError};
+
+#[derive(Default)]
+pub struct Registry {
+    functions: HashMap<String, Arc<FunctionDesc>>,
+    /// Descriptions that were replaced by a later install. Callers may still
+    /// hold handles to them, so they are never freed.
+    retired: Vec<Arc<FunctionDesc>>,
+    calls: HashMap<String, Vec<Json>>,
+    logons: HashMap<String, Logons>,
+    logon_failures: HashMap<String, (u32, ScriptedError)>,
+}
+
+pub fn registry() -> MutexGuard<'static, Registry> {
+    static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+    REGISTRY
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+impl Registry {
+    pub fn install(&mut self, module: &FunctionModule) {
+        let desc = Arc::new(FunctionDesc::from_script(module));
+        if let Some(old) = self.functions.insert(desc.name.clone(), desc) {
+            self.retired.push(old);
+        }
+    }
+
+    pub fn function(&self, name: &str) -> Option<Arc<FunctionDesc>> {
+        self.functions.get(&name.to_uppercase()).cloned()
+    }
+
+    pub fn record_call(&mut self, name: &str, inputs: Json) {
+        self.calls
+            .entry(name.to_uppercase())
+            .or_default()
+            .push(inputs);
+    }
+
+    pub fn calls(&self, name: &str) -> Vec<Json> {
+        self.calls
+            .get(&name.to_uppercase())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn fail_logons(&mut self, user: &str, count: u32, error: ScriptedError) {
+        self.logon_failures
+            .insert(user.to_uppercase(), (count, error));
+    }
+
+    /// Book a logon attempt; returns the scripted error if it has to fail
+    pub fn logon(
+        &mut self,
+        user: &str,
+        parameters: &HashMap<String, String>,
+    ) -> Option<ScriptedError> {
+        let user = user.to_uppercase();
+        let entry = self.logons.entry(user.clone()).or_default();
+        entry.last_parameters = parameters
+            .iter()
+            .map(|(k, v)| (k.clone(), Json::String(v.clone())))
+            .collect();
+        if let Some((count, error)) = self.logon_failures.get_mut(&user) {
+            if *count > 0 {
+                *count -= 1;
+                return Some(error.clone());
+            }
+        }
+        entry.total += 1;
+        entry.open += 1;
+        None
+    }
+
+    pub fn logoff(&mut self, user: &str) {
+        if let Some(entry) = self.logons.get_mut(&user.to_uppercase()) {
+            entry.open = entry.open.saturating_sub(1);
+        }
+    }
+
+    pub fn logons(&self, user: &str) -> Logons {
+        self.logons
+            .get(&user.to_uppercase())
+            .cloned()
+            .unwrap_or_default()
+    }
+}
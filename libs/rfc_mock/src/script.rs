@@ -0,0 +1,273 @@
+//! Description of scripted function modules.
+//!
+//! Function modules are described with plain serde types so that tests can
+//! build them in Rust and hand them to the loaded library as JSON.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+pub use crate::ffi::{RfcErrorGroup, RfcRc};
+
+/// ABAP data type of a parameter or structure field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum DataType {
+    Char { length: u32 },
+    Num { length: u32 },
+    Date,
+    Time,
+    Bcd { length: u32, decimals: u32 },
+    Int,
+    Int1,
+    Int2,
+    Int8,
+    Float,
+    String,
+    Xstring,
+    Byte { length: u32 },
+    Decf16,
+    Decf34,
+    Utclong,
+    Structure { fields: Vec<Field> },
+    Table { fields: Vec<Field> },
+}
+
+impl DataType {
+    pub fn char(length: u32) -> DataType {
+        DataType::Char { length }
+    }
+
+    pub fn num(length: u32) -> DataType {
+        DataType::Num { length }
+    }
+
+    /// Packed number, `length` is the length in bytes
+    pub fn bcd(length: u32, decimals: u32) -> DataType {
+        DataType::Bcd { length, decimals }
+    }
+
+    pub fn byte(length: u32) -> DataType {
+        DataType::Byte { length }
+    }
+
+    pub fn structure(fields: Vec<Field>) -> DataType {
+        DataType::Structure { fields }
+    }
+
+    pub fn table(fields: Vec<Field>) -> DataType {
+        DataType::Table { fields }
+    }
+}
+
+/// One field of a structure or table line type
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Field {
+    pub name: String,
+    #[serde(flatten)]
+    pub data_type: DataType,
+}
+
+impl Field {
+    pub fn new(name: &str, data_type: DataType) -> Field {
+        Field {
+            name: name.into(),
+            data_type,
+        }
+    }
+}
+
+/// Parameter direction as seen from the caller
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Importing,
+    Exporting,
+    Changing,
+    Tables,
+}
+
+/// One parameter of a function module
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Parameter {
+    pub name: String,
+    pub direction: Direction,
+    #[serde(flatten)]
+    pub data_type: DataType,
+    #[serde(default)]
+    pub optional: bool,
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+/// An error the library reports instead of executing the function
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScriptedError {
+    pub code: RfcRc,
+    #[serde(default)]
+    pub group: Option<RfcErrorGroup>,
+    #[serde(default)]
+    pub key: String,
+    #[serde(default)]
+    pub message: String,
+    #[serde(default)]
+    pub abap_msg_class: String,
+    #[serde(default)]
+    pub abap_msg_type: String,
+    #[serde(default)]
+    pub abap_msg_number: String,
+    #[serde(default)]
+    pub abap_msg_v1: String,
+    #[serde(default)]
+    pub abap_msg_v2: String,
+    #[serde(default)]
+    pub abap_msg_v3: String,
+    #[serde(default)]
+    pub abap_msg_v4: String,
+}
+
+impl ScriptedError {
+    pub fn new(code: RfcRc, key: &str, message: &str) -> ScriptedError {
+        ScriptedError {
+            code,
+            group: None,
+            key: key.into(),
+            message: message.into(),
+            abap_msg_class: String::new(),
+            abap_msg_type: String::new(),
+            abap_msg_number: String::new(),
+            abap_msg_v1: String::new(),
+            abap_msg_v2: String::new(),
+            abap_msg_v3: String::new(),
+            abap_msg_v4: String::new(),
+        }
+    }
+}
+
+/// What happens when the function module is invoked.
+///
+/// Values in `exports` are JSON: scalars as strings or numbers, structures as
+/// objects and tables as arrays of objects. XSTRING and RAW values are hex
+/// encoded strings. `copy` maps a target parameter to the parameter whose
+/// value it receives, which makes it easy to echo inputs back.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Response {
+    #[serde(default)]
+    pub copy: Vec<(String, String)>,
+    #[serde(default)]
+    pub exports: Map<String, Value>,
+    #[serde(default)]
+    pub error: Option<ScriptedError>,
+    /// Drop the connection after this call, like a network failure would
+    #[serde(default)]
+    pub close: bool,
+    /// Milliseconds the call takes. RfcCancel interrupts the wait.
+    #[serde(default)]
+    pub delay_ms: u64,
+}
+
+impl Response {
+    pub fn new() -> Response {
+        Response::default()
+    }
+
+    pub fn export(mut self, name: &str, value: Value) -> Response {
+        self.exports.insert(name.into(), value);
+        self
+    }
+
+    pub fn copy(mut self, to: &str, from: &str) -> Response {
+        self.copy.push((to.into(), from.into()));
+        self
+    }
+
+    pub fn error(mut self, error: ScriptedError) -> Response {
+        self.error = Some(error);
+        self
+    }
+
+    pub fn close(mut self) -> Response {
+        self.close = true;
+        self
+    }
+
+    pub fn delay(mut self, delay: Duration) -> Response {
+        self.delay_ms = delay.as_millis() as u64;
+        self
+    }
+}
+
+/// A scripted function module.
+///
+/// Each invocation consumes the next entry of `responses`; once the script
+/// is exhausted the last response is repeated.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FunctionModule {
+    pub name: String,
+    #[serde(default)]
+    pub parameters: Vec<Parameter>,
+    #[serde(default)]
+    pub responses: Vec<Response>,
+}
+
+impl FunctionModule {
+    pub fn new(name: &str) -> FunctionModule {
+        FunctionModule {
+            name: name.into(),
+            parameters: Vec::new(),
+            responses: Vec::new(),
+        }
+    }
+
+    pub fn parameter(mut self, name: &str, direction: Direction, data_type: DataType) -> Self {
+        self.parameters.push(Parameter {
+            name: name.into(),
+            direction,
+            data_type,
+            optional: false,
+            default: None,
+        });
+        self
+    }
+
+    pub fn importing(self, name: &str, data_type: DataType) -> Self {
+        self.parameter(name, Direction::Importing, data_type)
+    }
+
+    pub fn exporting(self, name: &str, data_type: DataType) -> Self {
+        self.parameter(name, Direction::Exporting, data_type)
+    }
+
+    pub fn changing(self, name: &str, data_type: DataType) -> Self {
+        self.parameter(name, Direction::Changing, data_type)
+    }
+
+    pub fn tables(self, name: &str, data_type: DataType) -> Self {
+        self.parameter(name, Direction::Tables, data_type)
+    }
+
+    pub fn respond(mut self, response: Response) -> Self {
+        self.responses.push(response);
+        self
+    }
+}
+
+/// Make the next `count` logons of `user` fail with `error`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogonFailure {
+    pub user: String,
+    pub count: u32,
+    pub error: ScriptedError,
+}
+
+/// Logon bookkeeping for one user
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Logons {
+    /// Number of successful logons
+    pub total: u32,
+    /// Number of connections that are currently open
+    pub open: u32,
+    /// Connection parameters of the most recent logon attempt
+    pub last_parameters: Map<String, Value>,
+}
@@ -1,6 +1,4 @@
-use std::collections::HashMap;
-
-use rsrfc::{error::*, *};
+use rsrfc::*;
 
 fn main() {
     println!("main函数开始执行");
@@ -33,6 +31,28 @@ struct SimpleResult {
     et_data: Vec<Field>,
 }
 
+#[derive(RfcRow)]
+struct Requisition {
+    #[sap(alias = "BANFN")]
+    banfn: String,
+    #[sap(alias = "BUKRS")]
+    bukrs: String,
+    #[sap(alias = "WERKS")]
+    werks: String,
+    #[sap(alias = "MENGE")]
+    menge: f64,
+    #[sap(alias = "MEINS")]
+    meins: String,
+}
+
+#[derive(RfcParams)]
+struct SimpleRequest {
+    #[sap(alias = "IV_ZSQDH")]
+    iv_zsqdh: String,
+    #[sap(alias = "CT_DATA")]
+    ct_data: Vec<Requisition>,
+}
+
 fn test() {
     println!("Testing RfcClient creation and destruction");
     {
@@ -41,19 +61,17 @@ fn test() {
         match client_result {
             Ok(client) => {
                 println!("RfcClient created successfully");
-                let mut params: HashMap<&str, ParamType<'_>> = HashMap::new();
-                params.insert("IV_ZSQDH", ParamType::Value(ParamValue::Str("********")));
-                params.insert(
-                    "CT_DATA",
-                    ParamType::Table(vec![vec![
-                        ("BANFN", ParamValue::Str("********")),
-                        ("BUKRS", ParamValue::Str("****")),
-                        ("WERKS", ParamValue::Str("****")),
-                        ("MENGE", ParamValue::Dec(1.0)),
-                        ("MEINS", ParamValue::Str("**")),
-                    ]]),
-                );
-                match client.execute::<SimpleResult>("********", params) {
+                let request = SimpleRequest {
+                    iv_zsqdh: "********".into(),
+                    ct_data: vec![Requisition {
+                        banfn: "********".into(),
+                        bukrs: "****".into(),
+                        werks: "****".into(),
+                        menge: 1.0,
+                        meins: "**".into(),
+                    }],
+                };
+                match client.call::<_, SimpleResult>("********", &request) {
                     Ok(result) => {
                         println!("RFC call successful");
                         println!("RFC return result: {:?}", result);